@@ -0,0 +1,199 @@
+//! Finding the callers and callees of functions.
+
+use crate::{source_files, CallHierarchyCall, CallHierarchyItem};
+use fast_hash::FxHashMap;
+use paths::{PathId, PathMap, WithPath};
+use sml_namespace::SymbolKind;
+use sml_statics_types::St;
+use sml_syntax::ast::{self, AstNode as _};
+use sml_syntax::{SyntaxNode, SyntaxToken};
+use text_pos::{PositionUtf16, RangeUtf16};
+
+pub(crate) fn prepare(
+  st: &St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<CallHierarchyItem> {
+  let ft = source_files::file_and_token(source_files, pos)?;
+  let name = ft.token.text();
+  let defs = ft.get_defs(pos.path)?;
+  defs.into_iter().find_map(|def| item(st, source_files, def.to_regular_idx()?, name))
+}
+
+pub(crate) fn incoming(
+  st: &St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<Vec<CallHierarchyCall>> {
+  let ft = source_files::file_and_token(source_files, pos)?;
+  let defs = ft.get_defs(pos.path)?;
+  let mut callers = FxHashMap::<(PathId, SyntaxNode), Vec<RangeUtf16>>::default();
+  for (&path, file) in source_files {
+    for call in calls(file) {
+      if !file.info.get_defs(call.func).iter().any(|def| defs.contains(def)) {
+        continue;
+      }
+      let Some((binding, _)) = caller(&call.name) else { continue };
+      let Some(range) = file.syntax.pos_db.range_utf16(call.name.text_range()) else { continue };
+      callers.entry((path, binding)).or_default().push(range);
+    }
+  }
+  let mut ret: Vec<_> = callers
+    .into_iter()
+    .filter_map(|((path, binding), ranges)| {
+      let (_, name) = binding_name(&binding)?;
+      let item = item_for_token(st, source_files.get(&path)?, path, name)?;
+      Some(CallHierarchyCall { item, ranges })
+    })
+    .collect();
+  sort(&mut ret);
+  Some(ret)
+}
+
+pub(crate) fn outgoing(
+  st: &St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<Vec<CallHierarchyCall>> {
+  let ft = source_files::file_and_token(source_files, pos)?;
+  let (binding, _) = caller(&ft.token)?;
+  let mut callees = FxHashMap::<WithPath<sml_hir::Idx>, (String, Vec<RangeUtf16>)>::default();
+  for call in calls(ft.file) {
+    if !binding.text_range().contains_range(call.name.text_range()) {
+      continue;
+    }
+    // calls in a nested function binding belong to that function, not this one.
+    if caller(&call.name).map_or(true, |(x, _)| x != binding) {
+      continue;
+    }
+    let Some(range) = ft.file.syntax.pos_db.range_utf16(call.name.text_range()) else { continue };
+    for def in ft.file.info.get_defs(call.func) {
+      let Some(idx) = def.to_regular_idx() else { continue };
+      let entry = callees.entry(idx).or_insert_with(|| (call.name.text().to_owned(), Vec::new()));
+      entry.1.push(range);
+    }
+  }
+  let mut ret: Vec<_> = callees
+    .into_iter()
+    .filter_map(|(idx, (name, ranges))| {
+      let item = item(st, source_files, idx, &name)?;
+      Some(CallHierarchyCall { item, ranges })
+    })
+    .collect();
+  sort(&mut ret);
+  Some(ret)
+}
+
+/// An application whose function is a path.
+struct Call {
+  /// The index of the path expression.
+  func: sml_hir::Idx,
+  /// The last name in the path.
+  name: SyntaxToken,
+}
+
+fn calls(file: &mlb_statics::SourceFile) -> impl Iterator<Item = Call> + '_ {
+  let arenas = &file.syntax.lower.arenas;
+  arenas.exp.iter().filter_map(|(_, exp)| {
+    let sml_hir::Exp::App(Some(func), _) = exp else { return None };
+    let sml_hir::Exp::Path(path) = &arenas.exp[*func] else { return None };
+    let func = sml_hir::Idx::from(*func);
+    let name = source_files::name_tokens(file, func, path.last().as_str()).into_iter().next()?;
+    Some(Call { func, name })
+  })
+}
+
+/// Returns the item for the binding whose name is defined at the index.
+fn item(
+  st: &St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  idx: WithPath<sml_hir::Idx>,
+  name: &str,
+) -> Option<CallHierarchyItem> {
+  let file = source_files.get(&idx.path)?;
+  let token = source_files::name_tokens(file, idx.val, name).into_iter().next()?;
+  item_for_token(st, file, idx.path, token)
+}
+
+/// Returns the item for the binding whose name is the token.
+fn item_for_token(
+  st: &St,
+  file: &mlb_statics::SourceFile,
+  path: PathId,
+  token: SyntaxToken,
+) -> Option<CallHierarchyItem> {
+  let binding = token.parent_ancestors().find_map(|node| {
+    let (is_fn, binding_name) = binding_name(&node)?;
+    (binding_name == token).then_some((node, is_fn))
+  });
+  // only names of `fun` and `val` bindings, not e.g. parameters, are items.
+  let (binding, is_fn) = binding?;
+  let range = path.wrap(file.syntax.pos_db.range_utf16(binding.text_range())?);
+  let selection_range = file.syntax.pos_db.range_utf16(token.text_range())?;
+  let ft = source_files::FileAndToken { file, token };
+  let detail = ft.get_ptr_and_indices().and_then(|(_, indices)| {
+    let ty_scheme = indices.iter().find_map(|&idx| file.info.get_ty_scheme(idx))?;
+    Some(ty_scheme.display(st, config::DiagnosticLines::One).to_string())
+  });
+  Some(CallHierarchyItem {
+    name: ft.token.text().to_owned(),
+    kind: if is_fn { SymbolKind::Function } else { SymbolKind::Value },
+    detail,
+    range,
+    selection_range,
+  })
+}
+
+/// Returns the binding the token is in that counts as the caller of things in it, and whether it
+/// binds a function.
+///
+/// This is the innermost binding that binds a function, or else the innermost binding that binds a
+/// name.
+fn caller(token: &SyntaxToken) -> Option<(SyntaxNode, bool)> {
+  let mut fallback = None::<SyntaxNode>;
+  for node in token.parent_ancestors() {
+    let Some((is_fn, _)) = binding_name(&node) else { continue };
+    if is_fn {
+      return Some((node, true));
+    }
+    if fallback.is_none() {
+      fallback = Some(node);
+    }
+  }
+  fallback.map(|node| (node, false))
+}
+
+/// If the node is a `fun` binding, or a `val` binding of a single name, returns whether it binds a
+/// function and the name token.
+fn binding_name(node: &SyntaxNode) -> Option<(bool, SyntaxToken)> {
+  if let Some(fun_bind) = ast::FunBind::cast(node.clone()) {
+    let tok = match fun_bind.fun_bind_cases().next()?.fun_bind_case_head()? {
+      ast::FunBindCaseHead::PrefixFunBindCaseHead(head) => head.name_star_eq()?.token,
+      ast::FunBindCaseHead::InfixFunBindCaseHead(head) => head.name_star_eq()?.token,
+    };
+    return Some((true, tok));
+  }
+  let val_bind = ast::ValBind::cast(node.clone())?;
+  let ast::Pat::ConPat(pat) = val_bind.pat()? else { return None };
+  if pat.pat().is_some() {
+    return None;
+  }
+  let mut names = pat.path()?.name_star_eq_dots();
+  let name = names.next()?.name_star_eq()?.token;
+  if names.next().is_some() {
+    return None;
+  }
+  let is_fn =
+    val_bind.eq_exp().and_then(|x| x.exp()).map_or(false, |exp| matches!(exp, ast::Exp::FnExp(_)));
+  Some((is_fn, name))
+}
+
+fn sort(calls: &mut [CallHierarchyCall]) {
+  calls.sort_unstable_by(|a, b| {
+    let a =
+      (a.item.range.path, a.item.selection_range.start.line, a.item.selection_range.start.col);
+    let b =
+      (b.item.range.path, b.item.selection_range.start.line, b.item.selection_range.start.col);
+    a.cmp(&b)
+  });
+}
@@ -13,6 +13,17 @@ pub struct Diagnostic<R> {
   pub code: diagnostic::Code,
   /// The severity.
   pub severity: diagnostic::Severity,
+  /// The mechanical fix, if there is one.
+  pub fix: Option<Fix<R>>,
+}
+
+/// A mechanical fix for a diagnostic.
+#[derive(Debug)]
+pub struct Fix<R> {
+  /// The title.
+  pub title: String,
+  /// The edits, which do not overlap.
+  pub edits: Vec<(R, String)>,
 }
 
 impl<R> Diagnostic<R> {
@@ -24,6 +35,7 @@ impl<R> Diagnostic<R> {
       message: "comment prevents formatting".to_owned(),
       code: diagnostic::Code::n(6001),
       severity: diagnostic::Severity::Warning,
+      fix: None,
     }
   }
 }
@@ -57,21 +69,24 @@ where
     config::init::DiagnosticsIgnore::AfterSyntax => true,
     config::init::DiagnosticsIgnore::All => return Vec::new(),
   };
+  let root = file.syntax.parse.syntax();
+  let pos_db = &file.syntax.pos_db;
   let mut ret: Vec<_> = std::iter::empty()
     .chain(file.syntax.lex_errors.iter().filter_map(|err| {
-      let range = f(&file.syntax.pos_db, err.range())?;
+      let range = f(pos_db, err.range())?;
       let message = err.to_string();
-      Some(Diagnostic { range, message, code: err.code(), severity: err.severity() })
+      Some(Diagnostic { range, message, code: err.code(), severity: err.severity(), fix: None })
     }))
     .chain(file.syntax.parse.errors.iter().filter_map(|err| {
-      let range = f(&file.syntax.pos_db, err.range())?;
+      let range = f(pos_db, err.range())?;
       let message = err.to_string();
-      Some(Diagnostic { range, message, code: err.code(), severity: err.severity() })
+      Some(Diagnostic { range, message, code: err.code(), severity: err.severity(), fix: None })
     }))
     .chain(file.syntax.lower.errors.iter().filter_map(|err| {
-      let range = f(&file.syntax.pos_db, err.range())?;
+      let range = f(pos_db, err.range())?;
       let message = err.to_string();
-      Some(Diagnostic { range, message, code: err.code(), severity: err.severity() })
+      let fix = crate::fix::lower(&root, err).and_then(|fix| map_fix(pos_db, fix, &f));
+      Some(Diagnostic { range, message, code: err.code(), severity: err.severity(), fix })
     }))
     .collect();
   let has_any_error = ret.iter().any(|x| matches!(x.severity, diagnostic::Severity::Error));
@@ -79,17 +94,18 @@ where
     ret.extend(file.statics_errors.iter().filter_map(|err| {
       let idx = err.idx();
       let syntax = file.syntax.lower.ptrs.hir_to_ast(idx).expect("no pointer for idx");
-      let node = syntax.to_node(file.syntax.parse.root.syntax());
-      let range = f(&file.syntax.pos_db, sml_syntax::node_range(&node))?;
+      let node = syntax.to_node(&root);
+      let range = f(pos_db, sml_syntax::node_range(&node))?;
       let message = err.display(syms_tys, options.lines).to_string();
-      Some(Diagnostic { range, message, code: err.code(), severity: err.severity() })
+      let fix = crate::fix::statics(&node, err).and_then(|fix| map_fix(pos_db, fix, &f));
+      Some(Diagnostic { range, message, code: err.code(), severity: err.severity(), fix })
     }));
     if matches!(options.format, config::init::FormatEngine::Naive) {
       if let Err(sml_naive_fmt::Error::Comments(ranges)) =
-        sml_naive_fmt::check(&file.syntax.parse.root)
+        sml_naive_fmt::check(&file.syntax.parse.root())
       {
         ret.extend(ranges.into_iter().filter_map(|range| {
-          let range = f(&file.syntax.pos_db, range)?;
+          let range = f(pos_db, range)?;
           Some(Diagnostic::naive_fmt_comment(range))
         }));
       }
@@ -97,3 +113,15 @@ where
   }
   ret
 }
+
+fn map_fix<F, R>(
+  pos_db: &text_pos::PositionDb,
+  fix: Fix<text_size_util::TextRange>,
+  f: F,
+) -> Option<Fix<R>>
+where
+  F: Fn(&text_pos::PositionDb, text_size_util::TextRange) -> Option<R>,
+{
+  let edits = fix.edits.into_iter().map(|(range, text)| Some((f(pos_db, range)?, text)));
+  Some(Fix { title: fix.title, edits: edits.collect::<Option<_>>()? })
+}
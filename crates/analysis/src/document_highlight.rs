@@ -0,0 +1,65 @@
+//! Highlighting the occurrences of an item in a single file.
+
+use crate::{source_files, DocumentHighlight, DocumentHighlightKind};
+use fast_hash::FxHashMap;
+use paths::{PathMap, WithPath};
+use sml_syntax::ast::{self, AstNode as _};
+use sml_syntax::{SyntaxKind, SyntaxToken};
+use text_pos::PositionUtf16;
+
+pub(crate) fn get(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<Vec<DocumentHighlight>> {
+  let ft = source_files::file_and_token(source_files, pos)?;
+  if !matches!(ft.token.kind(), SyntaxKind::Name | SyntaxKind::Star) {
+    return None;
+  }
+  let name = ft.token.text();
+  let defs = ft.get_defs(pos.path)?;
+  let mut tokens = FxHashMap::<SyntaxToken, DocumentHighlightKind>::default();
+  for &def in &defs {
+    if let Some(idx) = def.to_regular_idx() {
+      if idx.path == pos.path {
+        for tok in source_files::name_tokens(ft.file, idx.val, name) {
+          tokens.insert(tok, DocumentHighlightKind::Write);
+        }
+      }
+    }
+    for idx in ft.file.info.get_with_def(def) {
+      for tok in source_files::name_tokens(ft.file, idx, name) {
+        let kind = if is_assign_target(&tok) {
+          DocumentHighlightKind::Write
+        } else {
+          DocumentHighlightKind::Read
+        };
+        tokens.entry(tok).or_insert(kind);
+      }
+    }
+  }
+  let is_structure = source_files::str_path_prefix(&ft.token).is_some()
+    || ft.token.parent().map_or(false, |p| p.kind() == SyntaxKind::StrBind);
+  if is_structure {
+    for tok in source_files::str_path_tokens(ft.file, name, &defs) {
+      tokens.entry(tok).or_insert(DocumentHighlightKind::Read);
+    }
+  }
+  let mut ret: Vec<_> = tokens
+    .into_iter()
+    .filter_map(|(tok, kind)| {
+      let range = ft.file.syntax.pos_db.range_utf16(tok.text_range())?;
+      Some(DocumentHighlight { range, kind })
+    })
+    .collect();
+  ret.sort_unstable_by_key(|x| (x.range.start.line, x.range.start.col));
+  Some(ret)
+}
+
+/// Returns whether the token is the name of a variable being assigned to with `:=`.
+fn is_assign_target(token: &SyntaxToken) -> bool {
+  let Some(path_exp) = token.parent_ancestors().find_map(ast::PathExp::cast) else { return false };
+  let Some(infix) = path_exp.syntax().parent().and_then(ast::InfixExp::cast) else { return false };
+  let is_assign = infix.name_star_eq().map_or(false, |x| x.token.text() == ":=");
+  let is_lhs = infix.lhs().map_or(false, |lhs| lhs.syntax() == path_exp.syntax());
+  is_assign && is_lhs
+}
@@ -0,0 +1,210 @@
+//! Extracting an expression into a `val`.
+
+use crate::fix::{allowed, Prec};
+use crate::inline_val;
+use paths::{PathId, PathMap, WithPath};
+use sml_statics_types::def;
+use sml_syntax::ast::{self, AstNode as _, SyntaxNodePtr};
+use sml_syntax::rowan::NodeOrToken;
+use sml_syntax::{SyntaxKind, SyntaxNode};
+use text_pos::RangeUtf16;
+use text_size_util::{TextRange, TextSize};
+
+const NAME: &str = "extracted";
+
+/// Returns the edits to extract the expression selected by the range into a `val` in a `let`
+/// around it, or if `top_level`, into a declaration before the enclosing structure-level
+/// declaration.
+pub(crate) fn get(
+  st: &sml_statics_types::St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  range: WithPath<RangeUtf16>,
+  top_level: bool,
+) -> Option<Vec<(RangeUtf16, String)>> {
+  let path = range.path;
+  let file = source_files.get(&path)?;
+  let root = file.syntax.parse.syntax();
+  let range = file.syntax.pos_db.text_range_utf16(range.val)?;
+  // the selection may have whitespace around the expression.
+  let selected = root.text().slice(range).to_string();
+  let start = range.start() + text_len(&selected[..selected.len() - selected.trim_start().len()]);
+  let end = range.end() - text_len(&selected[selected.trim_end().len()..]);
+  if start >= end {
+    return None;
+  }
+  let range = TextRange::new(start, end);
+  let node = match root.covering_element(range) {
+    NodeOrToken::Node(node) => node,
+    NodeOrToken::Token(tok) => tok.parent()?,
+  };
+  let exp = node.ancestors().find(|node| {
+    ast::Exp::can_cast(node.kind())
+      && sml_syntax::trimmed(node).map(|(range, _)| range) == Some(range)
+  })?;
+  let exp_text = sml_syntax::trimmed(&exp)?.1.to_string();
+  let name = fresh_name(&root);
+  let cx = Cx { st, file, path, exp, range, exp_text, name };
+  let edits = if top_level { cx.top_level()? } else { cx.let_exp()? };
+  edits
+    .into_iter()
+    .map(|(range, text)| Some((file.syntax.pos_db.range_utf16(range)?, text)))
+    .collect()
+}
+
+fn text_len(s: &str) -> TextSize {
+  TextSize::try_from(s.len()).expect("text too long")
+}
+
+/// Returns the expression around the node that evaluates it exactly once, if any.
+fn enclosing_exp(node: &SyntaxNode) -> Option<SyntaxNode> {
+  let mut cur = node.parent()?;
+  while matches!(cur.kind(), SyntaxKind::ExpArg | SyntaxKind::ExpRow | SyntaxKind::EqExp) {
+    if cur.kind() == SyntaxKind::EqExp && cur.parent()?.kind() != SyntaxKind::ExpRow {
+      return None;
+    }
+    cur = cur.parent()?;
+  }
+  matches!(
+    cur.kind(),
+    SyntaxKind::ParenExp
+      | SyntaxKind::TypedExp
+      | SyntaxKind::TupleExp
+      | SyntaxKind::ListExp
+      | SyntaxKind::VectorExp
+      | SyntaxKind::RecordExp
+      | SyntaxKind::AppExp
+      | SyntaxKind::InfixExp
+  )
+  .then_some(cur)
+}
+
+/// Returns the expressions the node evaluates directly.
+fn operands(node: &SyntaxNode) -> impl Iterator<Item = SyntaxNode> + '_ {
+  node.descendants().skip(1).filter(move |x| {
+    ast::Exp::can_cast(x.kind())
+      && x.ancestors().skip(1).find(|x| ast::Exp::can_cast(x.kind())).as_ref() == Some(node)
+  })
+}
+
+/// Returns a name for the new `val` that is not the same as any name in the file.
+fn fresh_name(root: &SyntaxNode) -> String {
+  let names: fast_hash::FxHashSet<_> = root
+    .descendants_with_tokens()
+    .filter_map(NodeOrToken::into_token)
+    .filter(|tok| tok.kind() == SyntaxKind::Name)
+    .map(|tok| tok.text().to_owned())
+    .collect();
+  std::iter::once(NAME.to_owned())
+    .chain((1u32..).map(|n| format!("{NAME}{n}")))
+    .find(|name| !names.contains(name))
+    .expect("infinite iterator")
+}
+
+struct Cx<'a> {
+  st: &'a sml_statics_types::St,
+  file: &'a mlb_statics::SourceFile,
+  path: PathId,
+  /// the selected expression.
+  exp: SyntaxNode,
+  /// the range of the expression, without trivia.
+  range: TextRange,
+  exp_text: String,
+  name: String,
+}
+
+impl Cx<'_> {
+  /// Wraps an expression around the selected one in a `let`. This is the outermost expression that
+  /// evaluates the selected one exactly once, with no effects from its other operands. Moving the
+  /// selected expression before them thus does not change what happens.
+  fn let_exp(&self) -> Option<Vec<(TextRange, String)>> {
+    let mut outer = self.exp.clone();
+    while let Some(parent) = enclosing_exp(&outer) {
+      let pure = operands(&parent).filter(|node| *node != outer).all(|node| {
+        ast::Exp::cast(node).map_or(false, |exp| inline_val::nonexpansive(self.st, self.file, &exp))
+      });
+      if !pure {
+        break;
+      }
+      outer = parent;
+    }
+    let (outer_range, outer_text) = sml_syntax::trimmed(&outer)?;
+    let outer_text = outer_text.to_string();
+    let start = usize::from(self.range.start() - outer_range.start());
+    let end = usize::from(self.range.end() - outer_range.start());
+    let (name, exp) = (&self.name, &self.exp_text);
+    let body = format!("{}{name}{}", &outer_text[..start], &outer_text[end..]);
+    Some(vec![(outer_range, format!("let val {name} = {exp} in {body} end"))])
+  }
+
+  /// Adds a declaration before the enclosing structure-level declaration, with the variables the
+  /// expression uses that are bound in that declaration as parameters. If there are none and the
+  /// expression is expansive, it becomes a function of `()`.
+  fn top_level(&self) -> Option<Vec<(TextRange, String)>> {
+    let dec = self.exp.ancestors().find(|node| {
+      node.kind() == SyntaxKind::DecInSeq
+        && !node.ancestors().any(|node| node.kind() == SyntaxKind::LetExp)
+    })?;
+    let mut token = dec.first_token()?;
+    while sml_syntax::is_trivia(&token) {
+      token = token.next_token()?;
+    }
+    let dec_start = token.text_range().start();
+    if dec_start == self.range.start() {
+      return None;
+    }
+    let indent = token
+      .prev_token()
+      .filter(|tok| tok.kind() == SyntaxKind::Whitespace)
+      .and_then(|tok| tok.text().rsplit_once('\n').map(|(_, indent)| indent.to_owned()))
+      .unwrap_or_default();
+    let params = self.local_vars(dec.text_range());
+    let (name, exp) = (&self.name, &self.exp_text);
+    let (new_dec, new_exp) = if !params.is_empty() {
+      let params = params.join(" ");
+      let new_exp = format!("{name} {params}");
+      let new_exp = if Prec::App > allowed(&self.exp) { format!("({new_exp})") } else { new_exp };
+      (format!("fun {name} {params} = {exp}"), new_exp)
+    } else if inline_val::nonexpansive(self.st, self.file, &ast::Exp::cast(self.exp.clone())?) {
+      (format!("val {name} = {exp}"), name.clone())
+    } else {
+      // evaluate it where and as many times as it was before, which may be never or many times in a
+      // function body, loop, or conditional.
+      let new_exp = format!("{name} ()");
+      let new_exp = if Prec::App > allowed(&self.exp) { format!("({new_exp})") } else { new_exp };
+      (format!("fun {name} () = {exp}"), new_exp)
+    };
+    Some(vec![(TextRange::empty(dec_start), format!("{new_dec}\n{indent}")), (self.range, new_exp)])
+  }
+
+  /// Returns the names of the variables the expression uses that are bound in the `dec_range` but
+  /// outside the expression, in the order they are first used.
+  fn local_vars(&self, dec_range: TextRange) -> Vec<String> {
+    let ptrs = &self.file.syntax.lower.ptrs;
+    let mut ret = Vec::<String>::new();
+    for path_exp in self.exp.descendants().filter_map(ast::PathExp::cast) {
+      let Some(path) = path_exp.path() else { continue };
+      let mut names = path.name_star_eq_dots();
+      let (Some(name), None) = (names.next(), names.next()) else { continue };
+      let Some(name) = name.name_star_eq() else { continue };
+      let Some(idx) = ptrs.ast_to_hir(&SyntaxNodePtr::new(path_exp.syntax())) else { continue };
+      let local = self.file.info.get_defs(idx).into_iter().any(|def| {
+        let def::Def::Path(def::Path::Regular(path), idx) = def else { return false };
+        let Some(ptr) = ptrs.hir_to_ast(idx) else { return false };
+        let def_range = ptr.text_range();
+        path == self.path
+          && dec_range.contains_range(def_range)
+          && !self.range.contains_range(def_range)
+      });
+      let text = name.token.text();
+      let text = if text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        text.to_owned()
+      } else {
+        format!("(op {text})")
+      };
+      if local && !ret.contains(&text) {
+        ret.push(text);
+      }
+    }
+    ret
+  }
+}
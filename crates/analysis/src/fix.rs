@@ -0,0 +1,274 @@
+//! Computing the text edits for the mechanical fixes of diagnostics.
+
+use crate::diagnostic::Fix;
+use sml_syntax::ast::{self, AstNode as _};
+use sml_syntax::rowan::NodeOrToken;
+use sml_syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+use text_size_util::TextRange;
+
+pub(crate) fn lower(root: &SyntaxNode, err: &sml_hir_lower::Error) -> Option<Fix<TextRange>> {
+  let range = err.range();
+  match err.fix()? {
+    sml_hir_lower::Fix::RemoveParens => {
+      let node = node_at(root, range, |k| {
+        matches!(k, SyntaxKind::ParenExp | SyntaxKind::ParenPat | SyntaxKind::ParenTy)
+      })?;
+      let mut edits = Vec::<(TextRange, String)>::with_capacity(2);
+      for tok in node.children_with_tokens().filter_map(NodeOrToken::into_token) {
+        if matches!(tok.kind(), SyntaxKind::LRound | SyntaxKind::RRound) {
+          edits.push((tok.text_range(), remove(&tok)));
+        }
+      }
+      Some(Fix { title: "Remove unnecessary parentheses".to_owned(), edits })
+    }
+    sml_hir_lower::Fix::RemoveToken => {
+      let NodeOrToken::Token(tok) = root.covering_element(range) else { return None };
+      let title = format!("Remove `{}`", tok.text());
+      Some(Fix { title, edits: vec![(range, remove(&tok))] })
+    }
+    sml_hir_lower::Fix::SimplifyBool => {
+      let node = node_at(root, range, |k| {
+        matches!(k, SyntaxKind::AndalsoExp | SyntaxKind::OrelseExp | SyntaxKind::IfExp)
+      })?;
+      let (text, prec) = simplify_bool(ast::Exp::cast(node.clone())?)?;
+      replace(&node, "Simplify `bool` expression", text, prec)
+    }
+    sml_hir_lower::Fix::CaseToLet => {
+      let case = ast::CaseExp::cast(node_at(root, range, |k| k == SyntaxKind::CaseExp)?)?;
+      let arm = case.matcher()?.arms().next()?;
+      let pat = sml_syntax::trimmed(arm.pat()?.syntax())?.1;
+      let head = sml_syntax::trimmed(case.exp()?.syntax())?.1;
+      let body = sml_syntax::trimmed(arm.exp()?.syntax())?.1;
+      let text = format!("let val {pat} = {head} in {body} end");
+      replace(case.syntax(), "Rewrite `case` as `let`", text, Prec::Atomic)
+    }
+  }
+}
+
+pub(crate) fn statics(node: &SyntaxNode, err: &sml_statics::Error) -> Option<Fix<TextRange>> {
+  match err.fix()? {
+    sml_statics::Fix::CaseToIf => {
+      let case = ast::CaseExp::cast(node.clone())?;
+      let mut arms = case.matcher()?.arms();
+      let fst = arms.next()?;
+      let snd = arms.next()?;
+      if arms.next().is_some() {
+        return None;
+      }
+      let fst_pat = bool_pat(&fst.pat()?)?;
+      let snd_pat = snd.pat()?;
+      if !matches!(snd_pat, ast::Pat::WildcardPat(_)) && bool_pat(&snd_pat) != Some(!fst_pat) {
+        return None;
+      }
+      let (yes, no) = if fst_pat { (fst.exp()?, snd.exp()?) } else { (snd.exp()?, fst.exp()?) };
+      let head = sml_syntax::trimmed(case.exp()?.syntax())?.1;
+      let yes = sml_syntax::trimmed(yes.syntax())?.1;
+      let no = sml_syntax::trimmed(no.syntax())?.1;
+      let text = format!("if {head} then {yes} else {no}");
+      replace(node, "Rewrite `case` as `if`", text, Prec::Any)
+    }
+    sml_statics::Fix::EtaReduce => {
+      let fn_exp = ast::FnExp::cast(node.clone())?;
+      let mut body = fn_exp.matcher()?.arms().next()?.exp()?;
+      while let ast::Exp::ParenExp(exp) = body {
+        body = exp.exp()?;
+      }
+      let ast::Exp::AppExp(app) = body else { return None };
+      let func = app.func()?;
+      let text = sml_syntax::trimmed(func.syntax())?.1.to_string();
+      replace(node, "Eta-reduce", text, prec(&func))
+    }
+  }
+}
+
+/// Returns the innermost node whose diagnostic range is exactly the range and whose kind satisfies
+/// the predicate.
+fn node_at<F>(root: &SyntaxNode, range: TextRange, f: F) -> Option<SyntaxNode>
+where
+  F: Fn(SyntaxKind) -> bool,
+{
+  let node = match root.covering_element(range) {
+    NodeOrToken::Node(node) => node,
+    NodeOrToken::Token(tok) => tok.parent()?,
+  };
+  node.ancestors().find(|node| f(node.kind()) && sml_syntax::node_range(node) == range)
+}
+
+/// Returns the text to replace the token with to remove it. This is usually empty, but is a space
+/// if the tokens around the token would otherwise lex as one.
+fn remove(tok: &SyntaxToken) -> String {
+  let before = tok.prev_token().and_then(|x| x.text().chars().last());
+  let after = tok.next_token().and_then(|x| x.text().chars().next());
+  match (before, after) {
+    (Some(a), Some(b)) if (is_alpha(a) && is_alpha(b)) || (is_symbolic(a) && is_symbolic(b)) => {
+      " ".to_owned()
+    }
+    _ => String::new(),
+  }
+}
+
+fn is_alpha(c: char) -> bool {
+  c.is_ascii_alphanumeric() || matches!(c, '_' | '\'')
+}
+
+fn is_symbolic(c: char) -> bool {
+  "!%&$#+-/:<=>?@\\~`^|*".contains(c)
+}
+
+/// Returns a fix that replaces the node with the text, whose outermost construct has the
+/// precedence, parenthesizing the text if the context of the node requires it.
+fn replace(node: &SyntaxNode, title: &str, text: String, prec: Prec) -> Option<Fix<TextRange>> {
+  let (range, _) = sml_syntax::trimmed(node)?;
+  let text = if prec > allowed(node) { format!("({text})") } else { text };
+  Some(Fix { title: title.to_owned(), edits: vec![(range, text)] })
+}
+
+/// The precedence of an expression, from tightest to loosest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Prec {
+  Atomic,
+  App,
+  Infix,
+  Typed,
+  Andalso,
+  Orelse,
+  Any,
+}
+
+pub(crate) fn prec(exp: &ast::Exp) -> Prec {
+  match exp {
+    ast::Exp::SConExp(_)
+    | ast::Exp::PathExp(_)
+    | ast::Exp::RecordExp(_)
+    | ast::Exp::SelectorExp(_)
+    | ast::Exp::ParenExp(_)
+    | ast::Exp::TupleExp(_)
+    | ast::Exp::ListExp(_)
+    | ast::Exp::VectorExp(_)
+    | ast::Exp::SeqExp(_)
+    | ast::Exp::LetExp(_)
+    | ast::Exp::HoleExp(_)
+    | ast::Exp::WildcardExp(_)
+    | ast::Exp::OpAndalsoExp(_)
+    | ast::Exp::OpOrelseExp(_) => Prec::Atomic,
+    ast::Exp::AppExp(_) => Prec::App,
+    ast::Exp::InfixExp(_) => Prec::Infix,
+    ast::Exp::TypedExp(_) => Prec::Typed,
+    ast::Exp::AndalsoExp(_) => Prec::Andalso,
+    ast::Exp::OrelseExp(_) => Prec::Orelse,
+    _ => Prec::Any,
+  }
+}
+
+/// Returns the loosest precedence an expression may have in the place of the node without
+/// parentheses. This is conservative.
+pub(crate) fn allowed(node: &SyntaxNode) -> Prec {
+  match node.parent().map(|x| x.kind()) {
+    Some(SyntaxKind::AppExp) => Prec::Atomic,
+    Some(SyntaxKind::InfixExp) => Prec::App,
+    Some(SyntaxKind::TypedExp) => Prec::Infix,
+    Some(SyntaxKind::AndalsoExp) => Prec::Typed,
+    Some(SyntaxKind::OrelseExp) => Prec::Andalso,
+    Some(SyntaxKind::HandleExp) => Prec::Orelse,
+    _ => Prec::Any,
+  }
+}
+
+/// Returns the text of the expression, parenthesized if its precedence is looser than `max`.
+fn operand(exp: &ast::Exp, max: Prec) -> Option<String> {
+  let text = sml_syntax::trimmed(exp.syntax())?.1.to_string();
+  Some(if prec(exp) > max { format!("({text})") } else { text })
+}
+
+/// Returns a simpler expression equivalent to the `andalso`, `orelse`, or `if` expression, with its
+/// precedence.
+fn simplify_bool(exp: ast::Exp) -> Option<(String, Prec)> {
+  let same = |exp: ast::Exp| Some((sml_syntax::trimmed(exp.syntax())?.1.to_string(), prec(&exp)));
+  match exp {
+    ast::Exp::AndalsoExp(exp) => {
+      let (lhs, rhs) = (exp.lhs()?, exp.rhs()?);
+      match (bool_lit(&lhs), bool_lit(&rhs)) {
+        (Some(true), _) => same(rhs),
+        (Some(false), _) => Some(("false".to_owned(), Prec::Atomic)),
+        (None, Some(true)) => same(lhs),
+        _ => None,
+      }
+    }
+    ast::Exp::OrelseExp(exp) => {
+      let (lhs, rhs) = (exp.lhs()?, exp.rhs()?);
+      match (bool_lit(&lhs), bool_lit(&rhs)) {
+        (Some(false), _) => same(rhs),
+        (Some(true), _) => Some(("true".to_owned(), Prec::Atomic)),
+        (None, Some(false)) => same(lhs),
+        _ => None,
+      }
+    }
+    ast::Exp::IfExp(exp) => {
+      let (cond, yes, no) = (exp.cond()?, exp.yes()?, exp.no()?);
+      match (bool_lit(&cond), bool_lit(&yes), bool_lit(&no)) {
+        (Some(true), _, _) => same(yes),
+        (Some(false), _, _) => same(no),
+        (None, Some(true), Some(false)) => same(cond),
+        (None, Some(false), Some(true)) => {
+          Some((format!("not {}", operand(&cond, Prec::Atomic)?), Prec::App))
+        }
+        (None, Some(true), _) => {
+          let text =
+            format!("{} orelse {}", operand(&cond, Prec::Andalso)?, operand(&no, Prec::Andalso)?);
+          Some((text, Prec::Orelse))
+        }
+        (None, _, Some(false)) => {
+          let text =
+            format!("{} andalso {}", operand(&cond, Prec::Typed)?, operand(&yes, Prec::Typed)?);
+          Some((text, Prec::Andalso))
+        }
+        (None, Some(false), _) => {
+          let text =
+            format!("not {} andalso {}", operand(&cond, Prec::Atomic)?, operand(&no, Prec::Typed)?);
+          Some((text, Prec::Andalso))
+        }
+        (None, _, Some(true)) => {
+          let text = format!(
+            "not {} orelse {}",
+            operand(&cond, Prec::Atomic)?,
+            operand(&yes, Prec::Andalso)?
+          );
+          Some((text, Prec::Orelse))
+        }
+        _ => None,
+      }
+    }
+    _ => None,
+  }
+}
+
+/// Returns whether the expression is the literal `true` or `false`, if it is either.
+fn bool_lit(exp: &ast::Exp) -> Option<bool> {
+  let ast::Exp::PathExp(exp) = exp else { return None };
+  if exp.op_kw().is_some() {
+    return None;
+  }
+  bool_name(&exp.path()?)
+}
+
+/// Returns whether the pattern is the constructor `true` or `false`, if it is either.
+fn bool_pat(pat: &ast::Pat) -> Option<bool> {
+  let ast::Pat::ConPat(pat) = pat else { return None };
+  if pat.op_kw().is_some() || pat.pat().is_some() {
+    return None;
+  }
+  bool_name(&pat.path()?)
+}
+
+fn bool_name(path: &ast::Path) -> Option<bool> {
+  let mut iter = path.name_star_eq_dots();
+  let name = iter.next()?.name_star_eq()?;
+  if iter.next().is_some() {
+    return None;
+  }
+  match name.token.text() {
+    "true" => Some(true),
+    "false" => Some(false),
+    _ => None,
+  }
+}
@@ -0,0 +1,56 @@
+//! Computing the ranges that may be folded.
+
+use crate::{FoldingRange, FoldingRangeKind};
+use sml_syntax::ast::{self, AstNode as _};
+use sml_syntax::{rowan::NodeOrToken, SyntaxKind};
+use text_pos::PositionDb;
+use text_size_util::TextRange;
+
+pub(crate) fn sml(file: &mlb_statics::SourceFile) -> Vec<FoldingRange> {
+  let ranges = file.syntax.parse.syntax().descendants_with_tokens().filter_map(|elem| {
+    match elem {
+      NodeOrToken::Token(token) => (token.kind() == SyntaxKind::BlockComment)
+        .then(|| (token.text_range(), FoldingRangeKind::Comment)),
+      NodeOrToken::Node(node) => {
+        let kind = match node.kind() {
+          SyntaxKind::StructStrExp
+          | SyntaxKind::SigSigExp
+          | SyntaxKind::LetExp
+          | SyntaxKind::LetStrExp
+          | SyntaxKind::LocalDec => FoldingRangeKind::Region,
+          SyntaxKind::CaseExp => {
+            let case = ast::CaseExp::cast(node.clone())?;
+            (case.matcher()?.arms().count() > 1).then_some(FoldingRangeKind::Region)?
+          }
+          _ => return None,
+        };
+        // nodes may end with trivia, like the whitespace before the next line.
+        Some((sml_syntax::trimmed(&node)?.0, kind))
+      }
+    }
+  });
+  get(&file.syntax.pos_db, ranges)
+}
+
+pub(crate) fn group(path: &std::path::Path, contents: &str) -> Option<Vec<FoldingRange>> {
+  let ranges = match path.extension()?.to_str()? {
+    "mlb" => mlb_syntax::fold_ranges(contents).ok()?,
+    "cm" => cm_syntax::fold_ranges(contents).ok()?,
+    _ => return None,
+  };
+  let pos_db = PositionDb::new(contents);
+  Some(get(&pos_db, ranges.into_iter().map(|range| (range, FoldingRangeKind::Region))))
+}
+
+/// Returns the folding ranges for the text ranges that span more than one line.
+fn get<I>(pos_db: &PositionDb, iter: I) -> Vec<FoldingRange>
+where
+  I: Iterator<Item = (TextRange, FoldingRangeKind)>,
+{
+  iter
+    .filter_map(|(range, kind)| {
+      let range = pos_db.range_utf16(range)?;
+      (range.start.line < range.end.line).then_some(FoldingRange { range, kind })
+    })
+    .collect()
+}
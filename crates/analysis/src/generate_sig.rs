@@ -0,0 +1,246 @@
+//! Generating a signature from a structure.
+
+use crate::source_files;
+use paths::{PathId, PathMap, WithPath};
+use sml_statics_types::info::{IdStatus, TyInfo};
+use sml_statics_types::ty::{TyData, TyScheme};
+use sml_statics_types::{def, env::Env, sym::Equality};
+use sml_syntax::ast::{self, AstNode as _};
+use sml_syntax::{SyntaxKind, SyntaxNode};
+use str_util::Name;
+use text_pos::{PositionUtf16, RangeUtf16};
+use text_size_util::TextRange;
+
+const INDENT: &str = "  ";
+const LINES: config::DiagnosticLines = config::DiagnosticLines::One;
+
+/// Returns the name of the signature and the edits to add it before the top-level declaration
+/// containing the structure at the position, and to ascribe the structure to it if `ascribe`.
+pub(crate) fn get(
+  st: &sml_statics_types::St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+  ascribe: bool,
+) -> Option<(String, Vec<(RangeUtf16, String)>)> {
+  let ft = source_files::file_and_token(source_files, pos)?;
+  // only offer this on the head of the binding, not everywhere in the structure.
+  let parent = ft.token.parent()?;
+  let str_bind = match ast::StructureDec::cast(parent.clone()) {
+    Some(str_dec) => str_dec.str_binds().next()?,
+    None => ast::StrBind::cast(parent)?,
+  };
+  if str_bind.ascription_tail().is_some()
+    || !matches!(str_bind.eq_str_exp()?.str_exp()?, ast::StrExp::StructStrExp(_))
+  {
+    return None;
+  }
+  let name_token = str_bind.name()?;
+  let name = Name::new(name_token.text());
+  let str_dec = str_bind.syntax().parent()?;
+  let cx = Cx { st, file: ft.file, path: pos.path };
+  let mut prefix = Vec::<Name>::new();
+  let env = cx.find(&ft.file.info.basis().env, &str_dec, &name, &mut prefix)?;
+  let sig_name = sig_name(name.as_str());
+  // signatures may only be declared at the top level.
+  let top_dec = str_dec.ancestors().filter(|node| node.kind() == SyntaxKind::DecInSeq).last()?;
+  let mut token = top_dec.first_token()?;
+  while matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::BlockComment) {
+    token = token.next_token()?;
+  }
+  let indent = token
+    .prev_token()
+    .filter(|tok| tok.kind() == SyntaxKind::Whitespace)
+    .and_then(|tok| tok.text().rsplit_once('\n').map(|(_, indent)| indent.to_owned()))
+    .unwrap_or_default();
+  let mut text = format!("signature {sig_name} = sig\n");
+  for line in cx.specs(env, &mut prefix) {
+    text.push_str(&format!("{indent}{INDENT}{line}\n"));
+  }
+  text.push_str(&format!("{indent}end\n\n{indent}"));
+  let pos_db = &ft.file.syntax.pos_db;
+  let mut ret = vec![(pos_db.range_utf16(TextRange::empty(token.text_range().start()))?, text)];
+  if ascribe {
+    let range = TextRange::empty(name_token.text_range().end());
+    ret.push((pos_db.range_utf16(range)?, format!(" : {sig_name}")));
+  }
+  Some((sig_name, ret))
+}
+
+/// Returns the conventional signature name for the structure name, like `FOO_BAR` for `FooBar`.
+fn sig_name(name: &str) -> String {
+  let mut ret = String::with_capacity(name.len());
+  let mut after_lower = false;
+  for c in name.chars() {
+    if c.is_ascii_uppercase() && after_lower {
+      ret.push('_');
+    }
+    after_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+    ret.push(c.to_ascii_uppercase());
+  }
+  ret
+}
+
+struct Cx<'a> {
+  st: &'a sml_statics_types::St,
+  file: &'a mlb_statics::SourceFile,
+  path: PathId,
+}
+
+struct Item {
+  /// where the thing this specifies was defined, for putting the specs in source order.
+  pos: u32,
+  name: Name,
+  kind: Kind,
+}
+
+enum Kind {
+  Lines(Vec<String>),
+  /// datatypes declared together must be specified together, since they may refer to each other.
+  Datatype(Option<def::Def>, String),
+}
+
+impl Cx<'_> {
+  /// Returns the env for the structure named `name` declared by the `str_dec`, nested anywhere in
+  /// `env`, and sets `prefix` to the path to it.
+  fn find<'e>(
+    &self,
+    env: &'e Env,
+    str_dec: &SyntaxNode,
+    name: &Name,
+    prefix: &mut Vec<Name>,
+  ) -> Option<&'e Env> {
+    for (str_name, str_env) in env.str_env.iter() {
+      prefix.push(str_name.clone());
+      if str_name == name && self.node(str_env.def).as_ref() == Some(str_dec) {
+        return Some(str_env);
+      }
+      if let Some(ret) = self.find(str_env, str_dec, name, prefix) {
+        return Some(ret);
+      }
+      prefix.pop();
+    }
+    None
+  }
+
+  fn node(&self, def: Option<def::Def>) -> Option<SyntaxNode> {
+    let def::Def::Path(def::Path::Regular(path), idx) = def? else { return None };
+    if path != self.path {
+      return None;
+    }
+    let ptr = self.file.syntax.lower.ptrs.hir_to_ast(idx)?;
+    Some(ptr.to_node(&self.file.syntax.parse.syntax()))
+  }
+
+  /// Returns the position of the name in the node for the def, since many things may share a def
+  /// (e.g. all the datatypes in one declaration).
+  fn pos(&self, def: Option<def::Def>, name: &Name) -> u32 {
+    let Some(node) = self.node(def) else { return u32::MAX };
+    let tok = node
+      .descendants_with_tokens()
+      .filter_map(sml_syntax::rowan::NodeOrToken::into_token)
+      .find(|tok| tok.text() == name.as_str());
+    let pos = tok.map_or_else(|| node.text_range().start(), |tok| tok.text_range().start());
+    pos.into()
+  }
+
+  /// Returns the lines of the specs for everything in the env of the structure at the `prefix`.
+  fn specs(&self, env: &Env, prefix: &mut Vec<Name>) -> Vec<String> {
+    let mut items = Vec::<Item>::new();
+    for (name, str_env) in env.str_env.iter() {
+      prefix.push(name.clone());
+      let mut lines = vec![format!("structure {name} : sig")];
+      lines.extend(self.specs(str_env, prefix).into_iter().map(|line| format!("{INDENT}{line}")));
+      lines.push("end".to_owned());
+      prefix.pop();
+      let pos = self.pos(str_env.def, name);
+      items.push(Item { pos, name: name.clone(), kind: Kind::Lines(lines) });
+    }
+    for (name, ty_info) in env.ty_env.iter() {
+      let pos = self.pos(ty_info.def, name);
+      items.push(Item { pos, name: name.clone(), kind: self.ty_spec(name, ty_info, prefix) });
+    }
+    for (name, val_info) in env.val_env.iter() {
+      let line = match val_info.id_status {
+        // specified along with their datatypes.
+        IdStatus::Con => continue,
+        IdStatus::Exn(exn) => match self.st.syms.get_exn(exn).param {
+          None => format!("exception {name}"),
+          Some(param) => {
+            let param = TyScheme::zero(param);
+            format!("exception {name} of {}", param.display_relative(self.st, LINES, prefix))
+          }
+        },
+        IdStatus::Val => {
+          let ty_scheme = val_info.ty_scheme.display_relative(self.st, LINES, prefix);
+          format!("val {name} : {ty_scheme}")
+        }
+      };
+      let pos = val_info.defs.iter().map(|&def| self.pos(Some(def), name)).min();
+      let pos = pos.unwrap_or(u32::MAX);
+      items.push(Item { pos, name: name.clone(), kind: Kind::Lines(vec![line]) });
+    }
+    items.sort_by(|a, b| a.pos.cmp(&b.pos).then_with(|| a.name.as_str().cmp(b.name.as_str())));
+    let mut ret = Vec::<String>::new();
+    let mut prev_datatype = None::<def::Def>;
+    for item in items {
+      match item.kind {
+        Kind::Lines(lines) => {
+          prev_datatype = None;
+          ret.extend(lines);
+        }
+        Kind::Datatype(def, line) => {
+          let kw = if def.is_some() && def == prev_datatype { "and" } else { "datatype" };
+          prev_datatype = def;
+          ret.push(format!("{kw} {line}"));
+        }
+      }
+    }
+    ret
+  }
+
+  fn ty_spec(&self, name: &Name, ty_info: &TyInfo, prefix: &[Name]) -> Kind {
+    let head = ty_info.ty_scheme.display_head(name);
+    let sym_info = match self.st.tys.data(ty_info.ty_scheme.ty) {
+      TyData::Con(data) => self.st.syms.get(data.sym),
+      _ => None,
+    };
+    match sym_info {
+      // declared in this structure.
+      Some(sym_info) if sym_info.path.prefix() == prefix && sym_info.path.last() == name => {
+        if ty_info.val_env.is_empty() {
+          let kw = if matches!(sym_info.equality, Equality::Never) { "type" } else { "eqtype" };
+          return Kind::Lines(vec![format!("{kw} {head}")]);
+        }
+        let cons: Vec<_> = sym_info
+          .ty_info
+          .val_env
+          .iter()
+          .map(|(con, val_info)| match self.st.tys.data(val_info.ty_scheme.ty) {
+            TyData::Fn(data) => {
+              let bound_vars = val_info.ty_scheme.bound_vars.clone();
+              let param = TyScheme { bound_vars, ty: data.param };
+              format!("{con} of {}", param.display_relative(self.st, LINES, prefix))
+            }
+            _ => con.to_string(),
+          })
+          .collect();
+        Kind::Datatype(ty_info.def, format!("{head} = {}", cons.join(" | ")))
+      }
+      Some(sym_info) if !ty_info.val_env.is_empty() => {
+        let path = &sym_info.path;
+        let common = path.prefix().iter().zip(prefix).take_while(|(a, b)| a == b).count();
+        let mut other = String::new();
+        for name in &path.prefix()[common..] {
+          other.push_str(name.as_str());
+          other.push('.');
+        }
+        other.push_str(path.last().as_str());
+        Kind::Lines(vec![format!("datatype {name} = datatype {other}")])
+      }
+      _ => {
+        let ty_scheme = ty_info.ty_scheme.display_relative(self.st, LINES, prefix);
+        Kind::Lines(vec![format!("type {head} = {ty_scheme}")])
+      }
+    }
+  }
+}
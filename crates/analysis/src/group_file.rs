@@ -0,0 +1,343 @@
+//! Working with group files, like MLB and CM files.
+
+use crate::{source_files, DocumentLink, GroupCompletionItem, GroupCompletionKind};
+use fast_hash::FxHashSet;
+use paths::{PathId, PathMap, WithPath};
+use sml_namespace::Module;
+use std::fmt::Write as _;
+use std::path::Path;
+use text_pos::{PositionDb, PositionUtf16, RangeUtf16};
+use text_size_util::{TextRange, TextSize};
+
+pub(crate) fn links(input: &input::Input, path: PathId) -> Option<Vec<DocumentLink>> {
+  let group = input.groups.get(&path)?;
+  let ret = group
+    .paths
+    .iter()
+    .filter_map(|p| Some(DocumentLink { range: group.pos_db.range_utf16(p.range)?, target: p.val }))
+    .collect();
+  Some(ret)
+}
+
+pub(crate) fn defs(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  input: &input::Input,
+  pos: WithPath<PositionUtf16>,
+) -> Option<Vec<WithPath<RangeUtf16>>> {
+  let group = input.groups.get(&pos.path)?;
+  let offset = group.pos_db.text_size_utf16(pos.val)?;
+  if let Some(p) = group.paths.iter().find(|p| p.range.contains_inclusive(offset)) {
+    let start = PositionUtf16 { line: 0, col: 0 };
+    return Some(vec![p.val.wrap(RangeUtf16 { start, end: start })]);
+  }
+  match name_at(&group.bas_dec, offset)? {
+    NameAt::Basis(name) => {
+      let mut ret = basis_defs(pos.path, group, name);
+      if ret.is_empty() {
+        // the basis may be bound in a group file that this one includes.
+        for (&path, group) in &input.groups {
+          ret.extend(basis_defs(path, group, name));
+        }
+      }
+      Some(ret)
+    }
+    NameAt::Export(ns, name) => {
+      let in_group: Vec<_> = group
+        .paths
+        .iter()
+        .filter_map(|p| Some((p.val, source_files.get(&p.val)?)))
+        .filter_map(|(path, file)| top_def(source_files, path, file, ns, name))
+        .collect();
+      if !in_group.is_empty() {
+        return Some(in_group);
+      }
+      let ret = source_files
+        .iter()
+        .filter_map(|(&path, file)| top_def(source_files, path, file, ns, name))
+        .collect();
+      Some(ret)
+    }
+  }
+}
+
+pub(crate) fn hover(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  input: &input::Input,
+  paths: &paths::Store,
+  pos: WithPath<PositionUtf16>,
+) -> Option<(String, RangeUtf16)> {
+  let group = input.groups.get(&pos.path)?;
+  let offset = group.pos_db.text_size_utf16(pos.val)?;
+  let p = group.paths.iter().find(|p| p.range.contains_inclusive(offset))?;
+  let mut exports = Vec::<(Module, &str_util::Name)>::new();
+  let mut visited = FxHashSet::<PathId>::default();
+  path_exports(source_files, input, p.val, &mut visited, &mut exports);
+  exports.sort_unstable();
+  exports.dedup();
+  let mut md = format!("`{}`", paths.get_path(p.val).as_path().display());
+  if !exports.is_empty() {
+    md.push_str("\n\n```sml\n");
+    for (ns, name) in exports {
+      writeln!(md, "{ns} {name}").unwrap();
+    }
+    md.push_str("```");
+  }
+  Some((md, group.pos_db.range_utf16(p.range)?))
+}
+
+pub(crate) fn completions<F>(
+  fs: &F,
+  path: &Path,
+  contents: &str,
+  pos: PositionUtf16,
+  path_vars: &slash_var_path::UnresolvedEnv,
+  root_group_dir: &Path,
+) -> Option<Vec<GroupCompletionItem>>
+where
+  F: paths::FileSystem,
+{
+  let (keywords, allowed): (&[&str], fn(&Path) -> bool) = match path.extension()?.to_str()? {
+    "mlb" => (&mlb_syntax::KEYWORDS, is_mlb_path),
+    "cm" => (&cm_syntax::KEYWORDS, is_cm_path),
+    _ => return None,
+  };
+  let pos_db = PositionDb::new(contents);
+  let end = pos_db.text_size_utf16(pos)?;
+  let before = contents.get(..usize::from(end))?;
+  let start = before.trim_end_matches(is_path_char).len();
+  let word = &before[start..];
+  let range = |start: usize| {
+    let start = TextSize::try_from(start).ok()?;
+    pos_db.range_utf16(TextRange::new(start, end))
+  };
+  let mut ret = Vec::<GroupCompletionItem>::new();
+  // in the middle of a path variable, like `$(FO`.
+  if let Some(idx) = word.rfind("$(") {
+    if !word[idx..].contains(')') {
+      let range = range(start + idx + 2)?;
+      ret.extend(path_vars.iter().map(|(name, entry)| {
+        let detail = match entry.kind {
+          slash_var_path::EnvEntryKind::Value => entry.suffix.to_string(),
+          slash_var_path::EnvEntryKind::WorkspacePath => {
+            format!("{}, relative to the root group file", entry.suffix)
+          }
+        };
+        GroupCompletionItem {
+          label: name.to_string(),
+          kind: GroupCompletionKind::PathVar,
+          detail: Some(detail),
+          range,
+        }
+      }));
+      ret.sort_unstable_by(|a, b| a.label.cmp(&b.label));
+      return Some(ret);
+    }
+  }
+  if !word.contains(['/', '.', '$']) {
+    let range = range(start)?;
+    ret.extend(keywords.iter().map(|&kw| GroupCompletionItem {
+      label: kw.to_owned(),
+      kind: GroupCompletionKind::Keyword,
+      detail: None,
+      range,
+    }));
+  }
+  let parent = path.parent()?;
+  let (dir, name_start) = match word.rfind('/') {
+    None => (parent.to_owned(), start),
+    Some(idx) => {
+      let env = slash_var_path::resolve_env(root_group_dir.to_str()?, path_vars.clone());
+      let Ok(dir) = slash_var_path::get(&word[..=idx], &env) else { return Some(ret) };
+      (parent.join(dir), start + idx + 1)
+    }
+  };
+  let range = range(name_start)?;
+  let mut entries = fs.read_dir(dir.as_path()).unwrap_or_default();
+  entries.sort_unstable();
+  for entry in entries {
+    if entry == path {
+      continue;
+    }
+    let Some(name) = entry.file_name().and_then(|x| x.to_str()) else { continue };
+    if name.starts_with('.') {
+      continue;
+    }
+    let (label, kind) = if !fs.is_file(entry.as_path()) {
+      (format!("{name}/"), GroupCompletionKind::Folder)
+    } else if allowed(entry.as_path()) {
+      (name.to_owned(), GroupCompletionKind::File)
+    } else {
+      continue;
+    };
+    ret.push(GroupCompletionItem { label, kind, detail: None, range });
+  }
+  Some(ret)
+}
+
+fn is_mlb_path(path: &Path) -> bool {
+  mlb_syntax::path_kind(path).is_some()
+}
+
+fn is_cm_path(path: &Path) -> bool {
+  cm_syntax::path_kind(path).is_some()
+}
+
+fn is_path_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '/' | '.' | '$' | '(' | ')' | '\'')
+}
+
+/// A name in a group file that refers to something defined elsewhere.
+enum NameAt<'a> {
+  Basis(&'a str_util::Name),
+  Export(Module, &'a str_util::Name),
+}
+
+fn name_at(dec: &mlb_hir::BasDec, offset: TextSize) -> Option<NameAt<'_>> {
+  match dec {
+    mlb_hir::BasDec::Basis(_, exp) => name_at_exp(exp, offset),
+    mlb_hir::BasDec::Open(name) => {
+      name.range.contains_inclusive(offset).then_some(NameAt::Basis(&name.val))
+    }
+    mlb_hir::BasDec::Local(local_dec, in_dec) => {
+      name_at(local_dec, offset).or_else(|| name_at(in_dec, offset))
+    }
+    mlb_hir::BasDec::Export(ns, lhs, rhs) => {
+      // in CM, the range may be of a path that exports many names, not of the name itself.
+      let is_name = |name: &str_util::Name, range: TextRange| {
+        range.contains_inclusive(offset) && usize::from(range.len()) == name.as_str().len()
+      };
+      (is_name(&lhs.val, lhs.range) || is_name(&rhs.val, rhs.range))
+        .then_some(NameAt::Export(*ns, &rhs.val))
+    }
+    mlb_hir::BasDec::Ann(_, dec) => name_at(dec, offset),
+    mlb_hir::BasDec::Path(_, _) | mlb_hir::BasDec::SourcePathSet(_) => None,
+    mlb_hir::BasDec::Seq(decs) => decs.iter().find_map(|dec| name_at(dec, offset)),
+  }
+}
+
+fn name_at_exp(exp: &mlb_hir::BasExp, offset: TextSize) -> Option<NameAt<'_>> {
+  match exp {
+    mlb_hir::BasExp::Bas(dec) => name_at(dec, offset),
+    mlb_hir::BasExp::Name(name) => {
+      name.range.contains_inclusive(offset).then_some(NameAt::Basis(&name.val))
+    }
+    mlb_hir::BasExp::Let(dec, exp) => name_at(dec, offset).or_else(|| name_at_exp(exp, offset)),
+  }
+}
+
+/// Returns the ranges of the bindings of the basis with the name in the group.
+fn basis_defs(
+  path: PathId,
+  group: &input::Group,
+  name: &str_util::Name,
+) -> Vec<WithPath<RangeUtf16>> {
+  let mut ranges = Vec::<TextRange>::new();
+  basis_binds(&group.bas_dec, name, &mut ranges);
+  ranges.into_iter().filter_map(|range| Some(path.wrap(group.pos_db.range_utf16(range)?))).collect()
+}
+
+fn basis_binds(dec: &mlb_hir::BasDec, name: &str_util::Name, ac: &mut Vec<TextRange>) {
+  match dec {
+    mlb_hir::BasDec::Basis(lhs, exp) => {
+      if lhs.val == *name {
+        ac.push(lhs.range);
+      }
+      basis_binds_exp(exp, name, ac);
+    }
+    mlb_hir::BasDec::Local(local_dec, in_dec) => {
+      basis_binds(local_dec, name, ac);
+      basis_binds(in_dec, name, ac);
+    }
+    mlb_hir::BasDec::Ann(_, dec) => basis_binds(dec, name, ac),
+    mlb_hir::BasDec::Seq(decs) => {
+      for dec in decs {
+        basis_binds(dec, name, ac);
+      }
+    }
+    mlb_hir::BasDec::Open(_)
+    | mlb_hir::BasDec::Export(_, _, _)
+    | mlb_hir::BasDec::Path(_, _)
+    | mlb_hir::BasDec::SourcePathSet(_) => {}
+  }
+}
+
+fn basis_binds_exp(exp: &mlb_hir::BasExp, name: &str_util::Name, ac: &mut Vec<TextRange>) {
+  match exp {
+    mlb_hir::BasExp::Bas(dec) => basis_binds(dec, name, ac),
+    mlb_hir::BasExp::Name(_) => {}
+    mlb_hir::BasExp::Let(dec, exp) => {
+      basis_binds(dec, name, ac);
+      basis_binds_exp(exp, name, ac);
+    }
+  }
+}
+
+/// Returns the range of the definition of the top-level item in the file.
+fn top_def(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  path: PathId,
+  file: &mlb_statics::SourceFile,
+  ns: Module,
+  name: &str_util::Name,
+) -> Option<WithPath<RangeUtf16>> {
+  let bs = file.info.basis();
+  let def = match ns {
+    Module::Structure => bs.env.str_env.get(name)?.def?,
+    Module::Signature => bs.sig_env.get(name)?.env.def?,
+    Module::Functor => bs.fun_env.get(name)?.body_env.def?,
+  };
+  let idx = def.to_regular_idx()?;
+  // only the items defined in this file, not ones it re-exports from elsewhere.
+  if idx.path != path {
+    return None;
+  }
+  source_files::path_and_range(source_files, idx)
+}
+
+/// Collects what the file with the path exports.
+///
+/// For a source file, this is its top-level structures, signatures, and functors. For a group
+/// file, this is what it explicitly exports, or if it has no explicit exports, what all of its
+/// paths export.
+fn path_exports<'a>(
+  source_files: &'a PathMap<mlb_statics::SourceFile>,
+  input: &'a input::Input,
+  path: PathId,
+  visited: &mut FxHashSet<PathId>,
+  ac: &mut Vec<(Module, &'a str_util::Name)>,
+) {
+  if !visited.insert(path) {
+    return;
+  }
+  if let Some(file) = source_files.get(&path) {
+    let bs = file.info.basis();
+    ac.extend(bs.env.str_env.iter().map(|(name, _)| (Module::Structure, name)));
+    ac.extend(bs.sig_env.iter().map(|(name, _)| (Module::Signature, name)));
+    ac.extend(bs.fun_env.iter().map(|(name, _)| (Module::Functor, name)));
+    return;
+  }
+  let Some(group) = input.groups.get(&path) else { return };
+  let len = ac.len();
+  explicit_exports(&group.bas_dec, ac);
+  if ac.len() == len {
+    for p in &group.paths {
+      path_exports(source_files, input, p.val, visited, ac);
+    }
+  }
+}
+
+fn explicit_exports<'a>(dec: &'a mlb_hir::BasDec, ac: &mut Vec<(Module, &'a str_util::Name)>) {
+  match dec {
+    mlb_hir::BasDec::Export(ns, lhs, _) => ac.push((*ns, &lhs.val)),
+    mlb_hir::BasDec::Local(_, dec) | mlb_hir::BasDec::Ann(_, dec) => explicit_exports(dec, ac),
+    mlb_hir::BasDec::Seq(decs) => {
+      for dec in decs {
+        explicit_exports(dec, ac);
+      }
+    }
+    mlb_hir::BasDec::Basis(_, _)
+    | mlb_hir::BasDec::Open(_)
+    | mlb_hir::BasDec::Path(_, _)
+    | mlb_hir::BasDec::SourcePathSet(_) => {}
+  }
+}
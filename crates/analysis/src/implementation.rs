@@ -0,0 +1,45 @@
+//! Finding the things that implement a specification or signature.
+
+use crate::source_files;
+use paths::{PathMap, WithPath};
+use sml_syntax::ast::{self, AstNode as _};
+use sml_syntax::SyntaxKind;
+use text_pos::{PositionUtf16, RangeUtf16};
+
+pub(crate) fn get(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<Vec<WithPath<RangeUtf16>>> {
+  let ft = source_files::file_and_token(source_files, pos)?;
+  let defs = ft.get_defs(pos.path)?;
+  let mut ret: Vec<_> = source_files
+    .values()
+    .flat_map(|file| defs.iter().flat_map(|&def| file.info.get_impls(def)))
+    .filter_map(|def| range(source_files, def.to_regular_idx()?))
+    .collect();
+  ret.sort_unstable_by_key(|x| (x.path, x.val.start.line, x.val.start.col));
+  ret.dedup_by_key(|x| (x.path, x.val.start.line, x.val.start.col));
+  Some(ret)
+}
+
+/// Returns the range of the name of the structure or functor the index is the ascribed body of, if
+/// there is one. Otherwise, returns the range of the syntax for the index.
+fn range(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  idx: WithPath<sml_hir::Idx>,
+) -> Option<WithPath<RangeUtf16>> {
+  let file = source_files.get(&idx.path)?;
+  let ptr = file.syntax.lower.ptrs.hir_to_ast(idx.val)?;
+  let node = ptr.to_node(&file.syntax.parse.syntax());
+  let mut parent = node.parent()?;
+  if parent.kind() == SyntaxKind::EqStrExp {
+    parent = parent.parent()?;
+  }
+  let name = ast::StrBind::cast(parent.clone())
+    .and_then(|x| x.name())
+    .or_else(|| ast::FunctorBind::cast(parent).and_then(|x| x.functor_name()));
+  match name {
+    Some(name) => Some(idx.path.wrap(file.syntax.pos_db.range_utf16(name.text_range())?)),
+    None => source_files::path_and_range(source_files, idx),
+  }
+}
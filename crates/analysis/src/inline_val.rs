@@ -0,0 +1,247 @@
+//! Inlining a `val` into the places that use it.
+
+use crate::fix::{allowed, prec, Prec};
+use crate::source_files;
+use fast_hash::FxHashSet;
+use paths::{PathMap, WithPath};
+use sml_namespace::SymbolKind;
+use sml_statics_types::def;
+use sml_syntax::ast::{self, AstNode as _, SyntaxNodePtr};
+use sml_syntax::rowan::NodeOrToken;
+use sml_syntax::{SyntaxKind, SyntaxNode};
+use text_pos::{PositionUtf16, RangeUtf16};
+use text_size_util::TextRange;
+
+/// Returns the name of the `val` at the position, and the edits to replace each use of it with its
+/// expression and delete it.
+pub(crate) fn get(
+  st: &sml_statics_types::St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<(String, Vec<(RangeUtf16, String)>)> {
+  let ft = source_files::file_and_token(source_files, pos)?;
+  let file = ft.file;
+  let ptrs = &file.syntax.lower.ptrs;
+  let ast::Pat::ConPat(pat) = ft.token.parent_ancestors().find_map(ast::Pat::cast)? else {
+    return None;
+  };
+  let val_bind = ast::ValBind::cast(pat.syntax().parent()?)?;
+  let val_dec = ast::ValDec::cast(val_bind.syntax().parent()?)?;
+  if val_bind.rec_kw().is_some() || val_dec.val_binds().count() != 1 || pat.pat().is_some() {
+    return None;
+  }
+  let name = single_name(&pat.path()?)?;
+  let exp = val_bind.eq_exp()?.exp()?;
+  let cx = Cx { st, file };
+  if !cx.nonexpansive(&exp) {
+    return None;
+  }
+  // things in structures may be used by other structures, or required by signatures.
+  let dec = val_dec.syntax().parent()?;
+  if dec.ancestors().any(|node| node.kind() == SyntaxKind::StructStrExp) {
+    return None;
+  }
+  let def = def::Def::Path(
+    def::Path::Regular(pos.path),
+    ptrs.ast_to_hir(&SyntaxNodePtr::new(pat.syntax()))?,
+  );
+  let used_elsewhere = source_files
+    .iter()
+    .any(|(&path, file)| path != pos.path && file.info.get_with_def(def).next().is_some());
+  if used_elsewhere {
+    return None;
+  }
+  let root = file.syntax.parse.syntax();
+  let mut uses = Vec::<SyntaxNode>::new();
+  for idx in file.info.get_with_def(def) {
+    let node = ptrs.hir_to_ast(idx)?.to_node(&root);
+    if val_bind.syntax().text_range().contains_range(node.text_range()) {
+      continue;
+    }
+    let path_exp = ast::PathExp::cast(node.clone())?;
+    single_name(&path_exp.path()?)?;
+    if !uses.contains(&node) {
+      uses.push(node);
+    }
+  }
+  let exp_text = sml_syntax::trimmed(exp.syntax())?.1.to_string();
+  let scope_end = uses.iter().map(|node| node.text_range().end()).max();
+  let scope = TextRange::new(dec.text_range().end(), scope_end.unwrap_or(dec.text_range().end()));
+  if cx.shadowed(&exp, scope) {
+    return None;
+  }
+  let fix_env = if file.fix_env.is_empty() { &*sml_fixity::STD_BASIS } else { &file.fix_env };
+  let mut edits = Vec::<(TextRange, String)>::with_capacity(uses.len() + 1);
+  // delete the declaration, and the whitespace before it, or after it if it is first.
+  let (dec_range, _) = sml_syntax::trimmed(&dec)?;
+  let mut start = dec_range.start();
+  let mut end = dec_range.end();
+  let mut token = root.token_at_offset(start).right_biased()?;
+  while let Some(prev) = token.prev_token().filter(|tok| tok.kind() == SyntaxKind::Whitespace) {
+    start = prev.text_range().start();
+    token = prev;
+  }
+  if token.prev_token().is_none() {
+    start = dec_range.start();
+    let mut token = root.token_at_offset(end).left_biased()?;
+    while let Some(next) = token.next_token().filter(|tok| tok.kind() == SyntaxKind::Whitespace) {
+      end = next.text_range().end();
+      token = next;
+    }
+  }
+  edits.push((TextRange::new(start, end), String::new()));
+  for node in uses {
+    let text =
+      if needs_parens(fix_env, &exp, &node) { format!("({exp_text})") } else { exp_text.clone() };
+    edits.push((node.text_range(), text));
+  }
+  let edits: Option<Vec<_>> = edits
+    .into_iter()
+    .map(|(range, text)| Some((file.syntax.pos_db.range_utf16(range)?, text)))
+    .collect();
+  Some((name, edits?))
+}
+
+/// Returns whether the expression is non-expansive, and so may be evaluated any number of times, at
+/// any time, without changing what happens.
+pub(crate) fn nonexpansive(
+  st: &sml_statics_types::St,
+  file: &mlb_statics::SourceFile,
+  exp: &ast::Exp,
+) -> bool {
+  Cx { st, file }.nonexpansive(exp)
+}
+
+/// Returns the name if the path is a single name.
+fn single_name(path: &ast::Path) -> Option<String> {
+  let mut names = path.name_star_eq_dots();
+  let name = names.next()?;
+  if names.next().is_some() {
+    return None;
+  }
+  Some(name.name_star_eq()?.token.text().to_owned())
+}
+
+/// Returns whether the expression needs parentheses in the place of the node.
+fn needs_parens(fix_env: &sml_fixity::Env, exp: &ast::Exp, node: &SyntaxNode) -> bool {
+  let exp_prec = prec(exp);
+  if exp_prec == Prec::Atomic {
+    return false;
+  }
+  let Some(parent) = node.parent() else { return true };
+  if let Some(app) = ast::AppExp::cast(parent.clone()) {
+    let is_func = app.func().map_or(false, |func| func.syntax() == node);
+    let max = if is_func { Prec::App } else { Prec::Atomic };
+    return exp_prec > max;
+  }
+  let Some(infix) = ast::InfixExp::cast(parent) else { return exp_prec > allowed(node) };
+  let outer = infix.name_star_eq().and_then(|x| fix_env.get(x.token.text()).copied());
+  match exp {
+    ast::Exp::AppExp(_) => false,
+    ast::Exp::InfixExp(exp) => {
+      let inner = exp.name_star_eq().and_then(|x| fix_env.get(x.token.text()).copied());
+      let (Some(outer), Some(inner)) = (outer, inner) else { return true };
+      let is_lhs = infix.lhs().map_or(false, |lhs| lhs.syntax() == node);
+      let assoc = if is_lhs { sml_fixity::Assoc::Left } else { sml_fixity::Assoc::Right };
+      match inner.prec.cmp(&outer.prec) {
+        std::cmp::Ordering::Greater => false,
+        std::cmp::Ordering::Equal => inner.assoc != assoc || outer.assoc != assoc,
+        std::cmp::Ordering::Less => true,
+      }
+    }
+    _ => true,
+  }
+}
+
+struct Cx<'a> {
+  st: &'a sml_statics_types::St,
+  file: &'a mlb_statics::SourceFile,
+}
+
+impl Cx<'_> {
+  /// Returns whether the expression is non-expansive, and so may be evaluated any number of times
+  /// without changing what happens.
+  fn nonexpansive(&self, exp: &ast::Exp) -> bool {
+    let all = |exps: &mut dyn Iterator<Item = Option<ast::Exp>>| {
+      exps.all(|exp| exp.map_or(false, |exp| self.nonexpansive(&exp)))
+    };
+    match exp {
+      ast::Exp::SConExp(_)
+      | ast::Exp::PathExp(_)
+      | ast::Exp::SelectorExp(_)
+      | ast::Exp::OpAndalsoExp(_)
+      | ast::Exp::OpOrelseExp(_)
+      | ast::Exp::FnExp(_) => true,
+      ast::Exp::ParenExp(exp) => all(&mut std::iter::once(exp.exp())),
+      ast::Exp::TypedExp(exp) => all(&mut std::iter::once(exp.exp())),
+      ast::Exp::TupleExp(exp) => all(&mut exp.exp_args().map(|x| x.exp())),
+      ast::Exp::ListExp(exp) => all(&mut exp.exp_args().map(|x| x.exp())),
+      ast::Exp::VectorExp(exp) => {
+        all(&mut exp.list_exp().into_iter().flat_map(|x| x.exp_args()).map(|x| x.exp()))
+      }
+      ast::Exp::RecordExp(exp) => {
+        // punned rows are variables.
+        all(&mut exp.exp_rows().filter_map(|x| x.eq_exp()).map(|x| x.exp()))
+      }
+      ast::Exp::AppExp(exp) => {
+        exp.func().map_or(false, |func| self.constructor(func.syntax()))
+          && all(&mut std::iter::once(exp.arg()))
+      }
+      ast::Exp::InfixExp(exp) => {
+        self.constructor(exp.syntax()) && all(&mut [exp.lhs(), exp.rhs()].into_iter())
+      }
+      _ => false,
+    }
+  }
+
+  /// Returns whether the node is a constructor other than `ref`, or an infix application of one.
+  fn constructor(&self, node: &SyntaxNode) -> bool {
+    if let Some(path_exp) = ast::PathExp::cast(node.clone()) {
+      let is_ref = path_exp.path().and_then(|x| single_name(&x)).map_or(false, |x| x == "ref");
+      if is_ref {
+        return false;
+      }
+    } else if !ast::InfixExp::can_cast(node.kind()) {
+      return false;
+    }
+    let ptr = SyntaxNodePtr::new(node);
+    let idxs = self.file.syntax.lower.ptrs.ast_to_hir_all(&ptr).unwrap_or_default();
+    idxs.iter().any(|&idx| {
+      matches!(
+        self.file.info.get_val_symbol_kind(self.st, idx),
+        Some(SymbolKind::Constructor | SymbolKind::Exception)
+      )
+    })
+  }
+
+  /// Returns whether any of the variables the expression uses may be shadowed in the scope, because
+  /// their names appear there other than as uses of the same variables.
+  fn shadowed(&self, exp: &ast::Exp, scope: TextRange) -> bool {
+    let ptrs = &self.file.syntax.lower.ptrs;
+    let get_defs = |path_exp: &ast::PathExp| {
+      let idx = ptrs.ast_to_hir(&SyntaxNodePtr::new(path_exp.syntax()))?;
+      Some(self.file.info.get_defs(idx))
+    };
+    let mut vars = Vec::<(String, FxHashSet<def::Def>)>::new();
+    for path_exp in exp.syntax().descendants().filter_map(ast::PathExp::cast) {
+      let Some(name) = path_exp.path().and_then(|x| single_name(&x)) else { continue };
+      let Some(defs) = get_defs(&path_exp) else { return true };
+      vars.push((name, defs));
+    }
+    let root = self.file.syntax.parse.syntax();
+    let mut tokens = root.descendants_with_tokens().filter_map(NodeOrToken::into_token);
+    tokens.any(|tok| {
+      if !scope.contains_range(tok.text_range()) {
+        return false;
+      }
+      let Some((_, defs)) = vars.iter().find(|(name, _)| name == tok.text()) else { return false };
+      let Some(path_exp) = tok.parent_ancestors().find_map(ast::PathExp::cast) else { return true };
+      match path_exp.path() {
+        Some(path) if single_name(&path).is_some() => get_defs(&path_exp).as_ref() != Some(defs),
+        // a name in a qualified path is not the variable.
+        Some(_) => false,
+        None => true,
+      }
+    })
+  }
+}
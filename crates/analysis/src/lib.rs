@@ -2,9 +2,26 @@
 
 #![deny(clippy::pedantic, missing_debug_implementations, missing_docs, rust_2018_idioms)]
 
+mod call_hierarchy;
 mod diagnostic;
+mod document_highlight;
+mod extract_val;
+mod fix;
+mod folding_range;
+mod generate_sig;
+mod group_file;
+mod implementation;
+mod inline_val;
 mod matcher;
+mod missing_arms;
+mod missing_specs;
+mod rename;
+mod selection_range;
+mod semantic_tokens;
+mod signature_help;
 mod source_files;
+mod undefined;
+mod workspace_symbols;
 
 use fast_hash::FxHashSet;
 use paths::{PathId, PathMap, WithPath};
@@ -15,7 +32,8 @@ use std::{error::Error, fmt, io::Write as _};
 use text_pos::{PositionDb, PositionUtf16, RangeUtf16};
 use text_size_util::TextRange;
 
-pub use crate::diagnostic::{Diagnostic, Options};
+pub use crate::diagnostic::{Diagnostic, Fix, Options};
+pub use crate::rename::RenameError;
 pub use mlb_statics::StdBasis;
 
 /// The url to go to for information about diagnostics.
@@ -42,6 +60,13 @@ impl Analysis {
     }
   }
 
+  /// Sets the options.
+  ///
+  /// The new options take effect the next time diagnostics are computed.
+  pub fn set_options(&mut self, diagnostics_options: diagnostic::Options) {
+    self.diagnostics_options = diagnostics_options;
+  }
+
   /// Given information about many interdependent source files and their groupings, returns a
   /// mapping from source paths to diagnostics.
   pub fn get_many(
@@ -95,6 +120,7 @@ impl Analysis {
           message: err.to_string(),
           code: err.code(),
           severity: err.severity(),
+          fix: None,
         };
         Some((path, vec![err]))
       }))
@@ -119,11 +145,13 @@ impl Analysis {
   /// Update only the give path to have the new text, not recalculating diagnostics or anything in
   /// any other paths.
   ///
+  /// Does nothing if there is no source file for this path, as when this analyzed older input.
+  ///
   /// # Panics
   ///
-  /// If we couldn't find the source file for this path.
+  /// If the input has no contents for this path.
   pub fn update_one(&mut self, input: &input::Input, path: paths::PathId) {
-    let source_file = self.source_files.get_mut(&path).expect("no source file");
+    let Some(source_file) = self.source_files.get_mut(&path) else { return };
     let contents = input.sources.get(&path).expect("no contents");
     mlb_statics::update_one(&mut self.syms_tys, &input.lang, source_file, path, contents);
   }
@@ -200,6 +228,36 @@ impl Analysis {
     )
   }
 
+  /// Returns the ranges of the things that implement the specification or signature at this
+  /// position, via ascription.
+  #[must_use]
+  pub fn get_impls(&self, pos: WithPath<PositionUtf16>) -> Option<Vec<WithPath<RangeUtf16>>> {
+    implementation::get(&self.source_files, pos)
+  }
+
+  /// Returns the ranges of the definitions of the path, basis, or exported name at this position in
+  /// the group file.
+  #[must_use]
+  pub fn group_defs(
+    &self,
+    input: &input::Input,
+    pos: WithPath<PositionUtf16>,
+  ) -> Option<Vec<WithPath<RangeUtf16>>> {
+    group_file::defs(&self.source_files, input, pos)
+  }
+
+  /// Returns a Markdown string with the absolute path and exports of the file whose path is at this
+  /// position in the group file.
+  #[must_use]
+  pub fn group_md(
+    &self,
+    input: &input::Input,
+    paths: &paths::Store,
+    pos: WithPath<PositionUtf16>,
+  ) -> Option<(String, RangeUtf16)> {
+    group_file::hover(&self.source_files, input, paths, pos)
+  }
+
   /// Given a position on a `case` expression, return the code and its range to fill the case with
   /// all of the variants of the head's type.
   #[must_use]
@@ -207,7 +265,7 @@ impl Analysis {
     let ft = source_files::file_and_token(&self.source_files, pos)?;
     let (ptr, _) = ft.get_ptr_and_idx()?;
     let ptr = ptr.cast::<ast::CaseExp>()?;
-    let case = ptr.to_node(ft.file.syntax.parse.root.syntax());
+    let case = ptr.to_node(&ft.file.syntax.parse.syntax());
     let range = TextRange::empty(case.syntax().text_range().end());
     let range = ft.file.syntax.pos_db.range_utf16(range)?;
     let head_ast = case.exp()?;
@@ -235,6 +293,73 @@ impl Analysis {
     Some((range, case.to_string()))
   }
 
+  /// Given a position on a non-exhaustive `case`, `fn`, or `fun`, return the code and its range to
+  /// add arms for the patterns that are not yet matched.
+  #[must_use]
+  pub fn add_missing_arms(&self, pos: WithPath<PositionUtf16>) -> Option<(RangeUtf16, String)> {
+    missing_arms::get(&self.syms_tys, &self.source_files, pos)
+  }
+
+  /// Given a position on a structure ascribed to a signature that it is missing specifications
+  /// from, return the ranges and code to add stubs for those specifications to the structure, and to
+  /// the structures nested in it.
+  #[must_use]
+  pub fn add_missing_specs(
+    &self,
+    pos: WithPath<PositionUtf16>,
+  ) -> Option<Vec<(RangeUtf16, String)>> {
+    missing_specs::get(&self.syms_tys, &self.source_files, pos)
+  }
+
+  /// Given a position on an undefined name, return the ranges and replacement texts for the names
+  /// that may have been meant instead, most likely first.
+  #[must_use]
+  pub fn did_you_mean(&self, pos: WithPath<PositionUtf16>) -> Vec<(RangeUtf16, String)> {
+    undefined::similar(&self.source_files, pos)
+  }
+
+  /// Given a position on an undefined name, return the ranges and replacement texts to qualify the
+  /// name with the structures that define it, most likely first.
+  #[must_use]
+  pub fn qualify(&self, pos: WithPath<PositionUtf16>) -> Vec<(RangeUtf16, String)> {
+    undefined::qualified(&self.syms_tys, &self.source_files, pos)
+  }
+
+  /// Given a range selecting an expression, return the ranges and texts to extract it into a `val`
+  /// in a `let` around it, or if `top_level`, into a declaration before the enclosing structure-level
+  /// declaration, with the variables it uses that are bound in that declaration as parameters.
+  #[must_use]
+  pub fn extract_val(
+    &self,
+    range: WithPath<RangeUtf16>,
+    top_level: bool,
+  ) -> Option<Vec<(RangeUtf16, String)>> {
+    extract_val::get(&self.syms_tys, &self.source_files, range, top_level)
+  }
+
+  /// Given a position on the name of a `val` binding a variable to a non-expansive expression,
+  /// return the name, and the ranges and texts to replace each use of the variable with the
+  /// expression and delete the binding.
+  #[must_use]
+  pub fn inline_val(
+    &self,
+    pos: WithPath<PositionUtf16>,
+  ) -> Option<(String, Vec<(RangeUtf16, String)>)> {
+    inline_val::get(&self.syms_tys, &self.source_files, pos)
+  }
+
+  /// Given a position on the head of a `structure` binding to a `struct ... end`, return the name
+  /// of a signature for it, and the ranges and texts to add that signature and, if `ascribe`, to
+  /// ascribe the structure to it.
+  #[must_use]
+  pub fn generate_sig(
+    &self,
+    pos: WithPath<PositionUtf16>,
+    ascribe: bool,
+  ) -> Option<(String, Vec<(RangeUtf16, String)>)> {
+    generate_sig::get(&self.syms_tys, &self.source_files, pos, ascribe)
+  }
+
   /// Format the given file, and return the end position of the file.
   ///
   /// # Errors
@@ -253,10 +378,10 @@ impl Analysis {
     let buf = match self.diagnostics_options.format {
       config::init::FormatEngine::None => return Err(FormatError::Disabled),
       config::init::FormatEngine::Naive => {
-        sml_naive_fmt::get(&file.syntax.parse.root, tab_size).map_err(FormatError::NaiveFmt)?
+        sml_naive_fmt::get(&file.syntax.parse.root(), tab_size).map_err(FormatError::NaiveFmt)?
       }
       config::init::FormatEngine::Smlfmt => {
-        let contents = file.syntax.parse.root.syntax().to_string();
+        let contents = file.syntax.parse.syntax().to_string();
         let mut prog = Command::new("smlfmt")
           .stdin(Stdio::piped())
           .stdout(Stdio::piped())
@@ -295,6 +420,15 @@ impl Analysis {
     Some(ret)
   }
 
+  /// Returns the symbols in all files whose names match the query, best matches first, up to a
+  /// fixed number of them.
+  ///
+  /// If the query contains a `.`, it is matched against qualified names, like `Foo.Bar.baz`.
+  #[must_use]
+  pub fn workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+    workspace_symbols::get(std::iter::once((&self.syms_tys, &self.source_files)), query)
+  }
+
   /// Returns all references to the position.
   #[must_use]
   pub fn find_all_references(
@@ -315,6 +449,99 @@ impl Analysis {
     Some(ret.collect())
   }
 
+  /// Returns information about the signature of the function or functor being applied at the
+  /// position.
+  #[must_use]
+  pub fn signature_help(&self, pos: WithPath<PositionUtf16>) -> Option<SignatureHelp> {
+    let (mut ret, defs) = signature_help::get(&self.syms_tys, &self.source_files, pos)?;
+    let docs: Vec<_> = defs.into_iter().filter_map(|def| self.get_doc(def)).collect();
+    if !docs.is_empty() {
+      ret.documentation = Some(docs.join("\n\n---\n\n"));
+    }
+    Some(ret)
+  }
+
+  /// Returns the semantic tokens for the file, or only the ones in the range if given.
+  #[must_use]
+  pub fn semantic_tokens(
+    &self,
+    path: PathId,
+    range: Option<RangeUtf16>,
+  ) -> Option<Vec<SemanticToken>> {
+    let file = self.source_files.get(&path)?;
+    let range = match range {
+      Some(range) => Some(file.syntax.pos_db.text_range_utf16(range)?),
+      None => None,
+    };
+    Some(semantic_tokens::get(&self.syms_tys, file, range))
+  }
+
+  /// Returns the call hierarchy item for the function at the position.
+  #[must_use]
+  pub fn prepare_call_hierarchy(&self, pos: WithPath<PositionUtf16>) -> Option<CallHierarchyItem> {
+    call_hierarchy::prepare(&self.syms_tys, &self.source_files, pos)
+  }
+
+  /// Returns the calls to the function whose name is at the position.
+  #[must_use]
+  pub fn incoming_calls(&self, pos: WithPath<PositionUtf16>) -> Option<Vec<CallHierarchyCall>> {
+    call_hierarchy::incoming(&self.syms_tys, &self.source_files, pos)
+  }
+
+  /// Returns the calls made by the function whose name is at the position.
+  #[must_use]
+  pub fn outgoing_calls(&self, pos: WithPath<PositionUtf16>) -> Option<Vec<CallHierarchyCall>> {
+    call_hierarchy::outgoing(&self.syms_tys, &self.source_files, pos)
+  }
+
+  /// Returns the ranges that may be folded in the SML file.
+  #[must_use]
+  pub fn folding_ranges(&self, path: PathId) -> Option<Vec<FoldingRange>> {
+    let file = self.source_files.get(&path)?;
+    Some(folding_range::sml(file))
+  }
+
+  /// Returns the ranges to select when expanding the selection from the position, from innermost
+  /// (the token at the position) to outermost (the whole file).
+  #[must_use]
+  pub fn selection_ranges(&self, pos: WithPath<PositionUtf16>) -> Option<Vec<RangeUtf16>> {
+    selection_range::get(&self.source_files, pos)
+  }
+
+  /// Returns the occurrences in the file of the item at the position.
+  #[must_use]
+  pub fn document_highlights(
+    &self,
+    pos: WithPath<PositionUtf16>,
+  ) -> Option<Vec<DocumentHighlight>> {
+    document_highlight::get(&self.source_files, pos)
+  }
+
+  /// Returns the range of the name of the item to rename at the position.
+  ///
+  /// # Errors
+  ///
+  /// If there was no item that can be renamed at the position.
+  pub fn prepare_rename(&self, pos: WithPath<PositionUtf16>) -> Result<RangeUtf16, RenameError> {
+    let target = rename::target(&self.source_files, pos)?;
+    let file = self.source_files.get(&pos.path).ok_or(RenameError::NoItem)?;
+    file.syntax.pos_db.range_utf16(target.token.text_range()).ok_or(RenameError::NoItem)
+  }
+
+  /// Returns the edits, grouped by path, to rename the item at the position to the new name.
+  ///
+  /// # Errors
+  ///
+  /// If there was no item that can be renamed at the position, or if the new name is not valid
+  /// for the item.
+  pub fn rename(
+    &self,
+    pos: WithPath<PositionUtf16>,
+    new_name: &str,
+  ) -> Result<PathMap<Vec<(RangeUtf16, String)>>, RenameError> {
+    rename::get(&self.source_files, pos, new_name)
+  }
+
   /// Returns all completions for the position.
   #[must_use]
   pub fn completions(&self, pos: WithPath<PositionUtf16>) -> Option<Vec<CompletionItem>> {
@@ -448,7 +675,7 @@ impl Analysis {
     let fun_return_ty_hints = fun_case_bodies.filter_map(|(ptr_exp, exp)| {
       let ptr = file.syntax.lower.ptrs.hir_to_ast(ptr_exp.into())?;
       let fun_bind_ptr = ptr.cast::<sml_syntax::ast::FunBind>()?;
-      let fun_bind = fun_bind_ptr.to_node(file.syntax.parse.root.syntax());
+      let fun_bind = fun_bind_ptr.to_node(&file.syntax.parse.syntax());
       let case = fun_bind.fun_bind_cases().next()?;
       if case.ty_annotation().is_some() {
         return None;
@@ -580,6 +807,133 @@ fn symbol(
   })
 }
 
+/// Like [`Analysis::workspace_symbols`], but for the files in all the analyses together, so the
+/// matches are ranked and limited once across all of them.
+#[must_use]
+pub fn workspace_symbols<'a, I>(analyses: I, query: &str) -> Vec<WorkspaceSymbol>
+where
+  I: IntoIterator<Item = &'a Analysis>,
+{
+  workspace_symbols::get(analyses.into_iter().map(|an| (&an.syms_tys, &an.source_files)), query)
+}
+
+/// Returns the ranges that may be folded in the group file with the path and contents, or `None` if
+/// the path is not a group file path or the contents could not be processed.
+#[must_use]
+pub fn group_folding_ranges(path: &std::path::Path, contents: &str) -> Option<Vec<FoldingRange>> {
+  folding_range::group(path, contents)
+}
+
+/// Returns the links for the paths in the group file with the path, or `None` if there is no such
+/// group file in the input.
+#[must_use]
+pub fn group_links(input: &input::Input, path: PathId) -> Option<Vec<DocumentLink>> {
+  group_file::links(input, path)
+}
+
+/// Returns the completions for the position in the group file with the path and contents, or
+/// `None` if the path is not a group file path.
+///
+/// Path variables are completed inside `$(...)`. Otherwise, the completions are keywords and the
+/// files in the directory of the path being written that the group file may contain. Workspace
+/// paths in the path variables are relative to the directory of the root group file.
+#[must_use]
+pub fn group_completions<F>(
+  fs: &F,
+  path: &std::path::Path,
+  contents: &str,
+  pos: PositionUtf16,
+  path_vars: &slash_var_path::UnresolvedEnv,
+  root_group_dir: &std::path::Path,
+) -> Option<Vec<GroupCompletionItem>>
+where
+  F: paths::FileSystem,
+{
+  group_file::completions(fs, path, contents, pos, path_vars, root_group_dir)
+}
+
+/// A range in a file that links to another file.
+#[derive(Debug)]
+pub struct DocumentLink {
+  /// The range.
+  pub range: RangeUtf16,
+  /// The path of the other file.
+  pub target: PathId,
+}
+
+/// A range that may be folded.
+#[derive(Debug)]
+pub struct FoldingRange {
+  /// The range.
+  pub range: RangeUtf16,
+  /// The kind.
+  pub kind: FoldingRangeKind,
+}
+
+/// A kind of folding range.
+#[derive(Debug, Clone, Copy)]
+pub enum FoldingRangeKind {
+  /// A block comment.
+  Comment,
+  /// Some other block, like `struct ... end`.
+  Region,
+}
+
+/// A function or value in a call hierarchy.
+#[derive(Debug)]
+pub struct CallHierarchyItem {
+  /// The name.
+  pub name: String,
+  /// The kind.
+  pub kind: sml_namespace::SymbolKind,
+  /// The type.
+  pub detail: Option<String>,
+  /// The range of the whole binding.
+  pub range: WithPath<RangeUtf16>,
+  /// The range of the name, in the same file as the whole binding.
+  pub selection_range: RangeUtf16,
+}
+
+/// A call in a call hierarchy.
+#[derive(Debug)]
+pub struct CallHierarchyCall {
+  /// For incoming calls, the caller. For outgoing calls, the callee.
+  pub item: CallHierarchyItem,
+  /// The ranges of the calls, in the file of the caller.
+  pub ranges: Vec<RangeUtf16>,
+}
+
+/// An occurrence of an item in a file.
+#[derive(Debug)]
+pub struct DocumentHighlight {
+  /// The range.
+  pub range: text_pos::RangeUtf16,
+  /// The kind.
+  pub kind: DocumentHighlightKind,
+}
+
+/// A kind of occurrence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentHighlightKind {
+  /// The item is used.
+  Read,
+  /// The item is defined, or assigned to with `:=`.
+  Write,
+}
+
+/// A symbol in some file.
+#[derive(Debug)]
+pub struct WorkspaceSymbol {
+  /// The name of the symbol.
+  pub name: String,
+  /// What kind of symbol this is.
+  pub kind: sml_namespace::SymbolKind,
+  /// The qualified name of the structure, signature, or functor containing this symbol, if any.
+  pub container_name: Option<String>,
+  /// The range of the whole symbol.
+  pub range: WithPath<RangeUtf16>,
+}
+
 /// An inlay hint.
 #[derive(Debug)]
 pub struct InlayHint {
@@ -600,6 +954,41 @@ pub enum InlayHintKind {
   Ty,
 }
 
+/// Information about the signature of a function or functor being applied.
+#[derive(Debug)]
+pub struct SignatureHelp {
+  /// The label, like `f : int -> string -> unit`.
+  pub label: String,
+  /// The start and end of each parameter in the label, as UTF-16 offsets.
+  pub params: Vec<[u32; 2]>,
+  /// The index of the parameter the cursor is on.
+  pub active_param: Option<u32>,
+  /// Markdown documentation.
+  pub documentation: Option<String>,
+}
+
+/// A semantic token.
+#[derive(Debug)]
+pub struct SemanticToken {
+  /// The range.
+  pub range: text_pos::RangeUtf16,
+  /// The kind.
+  pub kind: SemanticTokenKind,
+  /// Whether it was defined in the std basis.
+  pub std_basis: bool,
+  /// Whether it has a `ref` type.
+  pub is_ref: bool,
+}
+
+/// A semantic token kind.
+#[derive(Debug, Clone, Copy)]
+pub enum SemanticTokenKind {
+  /// A symbol, like a structure or value.
+  Symbol(sml_namespace::SymbolKind),
+  /// A type variable.
+  TyVar,
+}
+
 /// A completion item.
 #[derive(Debug)]
 pub struct CompletionItem {
@@ -613,6 +1002,32 @@ pub struct CompletionItem {
   pub documentation: Option<String>,
 }
 
+/// A completion item in a group file.
+#[derive(Debug)]
+pub struct GroupCompletionItem {
+  /// The label, which is also the text to insert.
+  pub label: String,
+  /// The kind.
+  pub kind: GroupCompletionKind,
+  /// Detail about it.
+  pub detail: Option<String>,
+  /// The range to replace with the label.
+  pub range: RangeUtf16,
+}
+
+/// A kind of completion item in a group file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupCompletionKind {
+  /// A keyword.
+  Keyword,
+  /// A path variable.
+  PathVar,
+  /// A file.
+  File,
+  /// A directory.
+  Folder,
+}
+
 fn get_env<'e>(mut env: &'e Env, path: &sml_syntax::ast::Path) -> Option<&'e Env> {
   for part in path.name_star_eq_dots() {
     if part.dot().is_none() {
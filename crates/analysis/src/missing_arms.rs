@@ -0,0 +1,124 @@
+//! Adding the arms missing from a non-exhaustive `case`, `fn`, or `fun`.
+
+use crate::source_files;
+use paths::{PathMap, WithPath};
+use sml_syntax::ast::{self, AstNode as _};
+use sml_syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+use text_pos::{PositionUtf16, RangeUtf16};
+use text_size_util::TextRange;
+
+pub(crate) fn get(
+  st: &sml_statics_types::St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<(RangeUtf16, String)> {
+  let file = source_files.get(&pos.path)?;
+  let offset = file.syntax.pos_db.text_size_utf16(pos.val)?;
+  // the innermost node with a non-exhaustive error whose diagnostic range contains the offset.
+  //
+  // a non-exhaustive binding, like `val SOME x = y`, has no arms to add to. rewriting it into a
+  // `case` would mean choosing what its variables are in each new arm, so we leave it alone.
+  let (node, err) = source_files::statics_errors_at(file, offset)
+    .filter(|(_, node, _)| {
+      matches!(node.kind(), SyntaxKind::CaseExp | SyntaxKind::FnExp | SyntaxKind::FunBind)
+    })
+    .min_by_key(|(range, _, _)| range.len())
+    .map(|(_, node, err)| (node, err))?;
+  let (range, text) = if let Some(fun_bind) = ast::FunBind::cast(node.clone()) {
+    fun(st, &fun_bind, err)?
+  } else {
+    let matcher = ast::CaseExp::cast(node.clone())
+      .and_then(|x| x.matcher())
+      .or_else(|| ast::FnExp::cast(node).and_then(|x| x.matcher()))?;
+    case(st, &matcher, err)?
+  };
+  let range = file.syntax.pos_db.range_utf16(range)?;
+  Some((range, text))
+}
+
+fn case(
+  st: &sml_statics_types::St,
+  matcher: &ast::Matcher,
+  err: &sml_statics::Error,
+) -> Option<(TextRange, String)> {
+  let pats = err.missing_pats(st, None)?;
+  let last = matcher.arms().last()?;
+  let indent = arm_indent(&last.syntax().first_token()?);
+  let text: String = pats.iter().map(|pat| format!("\n{indent}| {pat} => _")).collect();
+  append(last.syntax(), last.exp(), text)
+}
+
+fn fun(
+  st: &sml_statics_types::St,
+  fun_bind: &ast::FunBind,
+  err: &sml_statics::Error,
+) -> Option<(TextRange, String)> {
+  let first = fun_bind.fun_bind_cases().next()?;
+  let (name, num_args) = match first.fun_bind_case_head()? {
+    ast::FunBindCaseHead::PrefixFunBindCaseHead(head) => {
+      (head.name_star_eq()?.token.text().to_owned(), first.pats().count())
+    }
+    ast::FunBindCaseHead::InfixFunBindCaseHead(head) => {
+      (format!("op {}", head.name_star_eq()?.token.text()), first.pats().count() + 1)
+    }
+  };
+  let pats = err.missing_pats(st, Some(num_args))?;
+  let last = fun_bind.fun_bind_cases().last()?;
+  let indent = arm_indent(&last.syntax().first_token()?);
+  let text: String = pats.iter().map(|pat| format!("\n{indent}| {name} {pat} = _")).collect();
+  append(last.syntax(), last.eq_exp().and_then(|x| x.exp()), text)
+}
+
+/// Returns the edit that adds the new arms after the last arm, whose body is the expression.
+///
+/// If the body ends with arms of its own, like an unparenthesized `case`, the new arms would belong
+/// to those, so this also wraps the body in parentheses.
+fn append(last: &SyntaxNode, body: Option<ast::Exp>, arms: String) -> Option<(TextRange, String)> {
+  match body {
+    Some(body) if ends_with_arms(&body) => {
+      let (range, text) = sml_syntax::trimmed(body.syntax())?;
+      Some((range, format!("({text}){arms}")))
+    }
+    _ => Some((TextRange::empty(sml_syntax::trimmed(last)?.0.end()), arms)),
+  }
+}
+
+/// Returns whether the expression ends with a matcher, so that a `|` after it would continue that
+/// matcher.
+fn ends_with_arms(exp: &ast::Exp) -> bool {
+  let last = match exp {
+    ast::Exp::CaseExp(_) | ast::Exp::FnExp(_) | ast::Exp::HandleExp(_) => return true,
+    ast::Exp::AndalsoExp(exp) => exp.rhs(),
+    ast::Exp::OrelseExp(exp) => exp.rhs(),
+    ast::Exp::RaiseExp(exp) => exp.exp(),
+    ast::Exp::IfExp(exp) => exp.no(),
+    ast::Exp::WhileExp(exp) => exp.body(),
+    _ => return false,
+  };
+  last.map_or(false, |x| ends_with_arms(&x))
+}
+
+/// Returns the indentation for new arms after the arm that starts with the token.
+///
+/// If the arm's line starts with a `|`, new arms line up with it. If the arm starts its line, the
+/// `|` of each new arm goes just before where the arm starts. Otherwise, new arms are indented more than
+/// the line.
+fn arm_indent(token: &SyntaxToken) -> String {
+  let mut first = token.clone();
+  let indent = loop {
+    let Some(prev) = first.prev_token() else { break "" };
+    if prev.kind() == SyntaxKind::Whitespace {
+      if let Some((_, indent)) = prev.text().rsplit_once('\n') {
+        break indent;
+      }
+    }
+    first = prev;
+  };
+  if first.kind() == SyntaxKind::Bar {
+    indent.to_owned()
+  } else if first == *token {
+    indent.get(2..).unwrap_or_default().to_owned()
+  } else {
+    format!("{indent}  ")
+  }
+}
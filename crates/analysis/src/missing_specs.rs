@@ -0,0 +1,279 @@
+//! Adding stubs for the specifications missing from a structure ascribed to a signature.
+
+use crate::source_files;
+use paths::{PathMap, WithPath};
+use sml_statics_types::info::{IdStatus, TyInfo};
+use sml_statics_types::ty::{TyData, TyScheme};
+use sml_statics_types::{def, env::Env};
+use sml_syntax::ast::{self, AstNode as _};
+use sml_syntax::{SyntaxKind, SyntaxToken};
+use str_util::Name;
+use text_pos::{PositionUtf16, RangeUtf16};
+use text_size_util::TextRange;
+
+const INDENT: &str = "  ";
+const LINES: config::DiagnosticLines = config::DiagnosticLines::One;
+const BODY: &str = "raise Fail \"unimplemented\"";
+
+pub(crate) fn get(
+  st: &sml_statics_types::St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<Vec<(RangeUtf16, String)>> {
+  let file = source_files.get(&pos.path)?;
+  let offset = file.syntax.pos_db.text_size_utf16(pos.val)?;
+  // the innermost ascribed structure with missing specs whose diagnostic range contains the offset.
+  let (node, missing) = source_files::statics_errors_at(file, offset)
+    .filter_map(|(range, node, err)| Some((range, node, file.info.get_missing(err.idx())?)))
+    .min_by_key(|(range, _, _)| range.len())
+    .map(|(_, node, missing)| (node, missing))?;
+  let str_exp = match ast::StrExp::cast(node)? {
+    ast::StrExp::AscriptionStrExp(str_exp) => str_exp.str_exp()?,
+    str_exp => str_exp,
+  };
+  let ast::StrExp::StructStrExp(str_exp) = str_exp else { return None };
+  let mut prefix: Vec<_> = str_exp
+    .syntax()
+    .ancestors()
+    .filter_map(|node| ast::StrBind::cast(node)?.name())
+    .map(|tok| Name::new(tok.text()))
+    .collect();
+  prefix.reverse();
+  let cx = Cx { st, source_files };
+  let mut edits = Vec::<(TextRange, String)>::new();
+  cx.stubs(&str_exp, missing, &mut prefix, &mut edits)?;
+  if edits.is_empty() {
+    return None;
+  }
+  edits
+    .into_iter()
+    .map(|(range, text)| Some((file.syntax.pos_db.range_utf16(range)?, text)))
+    .collect()
+}
+
+/// Returns the last binding of the name directly in the structure.
+fn str_bind(str_exp: &ast::StructStrExp, name: &Name) -> Option<ast::StrBind> {
+  str_exp
+    .syntax()
+    .descendants()
+    .filter_map(ast::StrBind::cast)
+    .filter(|str_bind| str_bind.name().map_or(false, |tok| tok.text() == name.as_str()))
+    .filter(|str_bind| {
+      let parent = str_bind.syntax().ancestors().skip(1).find_map(ast::StructStrExp::cast);
+      parent.as_ref() == Some(str_exp)
+    })
+    .last()
+}
+
+/// Returns the indentation of the line the token is on.
+fn line_indent(token: &SyntaxToken) -> String {
+  let mut token = token.clone();
+  while let Some(prev) = token.prev_token() {
+    if prev.kind() == SyntaxKind::Whitespace {
+      if let Some((_, indent)) = prev.text().rsplit_once('\n') {
+        return indent.to_owned();
+      }
+    }
+    token = prev;
+  }
+  String::new()
+}
+
+struct Cx<'a> {
+  st: &'a sml_statics_types::St,
+  source_files: &'a PathMap<mlb_statics::SourceFile>,
+}
+
+struct Item {
+  /// where the spec was, for putting the stubs in the same order as the signature.
+  pos: u32,
+  name: Name,
+  kind: Kind,
+}
+
+enum Kind {
+  Lines(Vec<String>),
+  /// datatypes specified together must be declared together, since they may refer to each other.
+  Datatype(Option<def::Def>, String),
+}
+
+impl Cx<'_> {
+  /// Returns the position of the name in the node for the def, since many things may share a def.
+  fn pos(&self, def: Option<def::Def>, name: &Name) -> u32 {
+    let Some(idx) = def.and_then(def::Def::to_regular_idx) else { return u32::MAX };
+    let Some(file) = self.source_files.get(&idx.path) else { return u32::MAX };
+    let Some(ptr) = file.syntax.lower.ptrs.hir_to_ast(idx.val) else { return u32::MAX };
+    let node = ptr.to_node(&file.syntax.parse.syntax());
+    let tok = node
+      .descendants_with_tokens()
+      .filter_map(sml_syntax::rowan::NodeOrToken::into_token)
+      .find(|tok| tok.text() == name.as_str());
+    let pos = tok.map_or_else(|| node.text_range().start(), |tok| tok.text_range().start());
+    pos.into()
+  }
+
+  /// Adds the edits to put stubs for everything in the env at the end of the structure at the
+  /// `prefix`, or at the end of the structures nested in it for the ones it already declares.
+  fn stubs(
+    &self,
+    str_exp: &ast::StructStrExp,
+    missing: &Env,
+    prefix: &mut Vec<Name>,
+    edits: &mut Vec<(TextRange, String)>,
+  ) -> Option<()> {
+    let mut declared = Vec::<Name>::new();
+    for (name, nested) in missing.str_env.iter() {
+      let Some(str_bind) = str_bind(str_exp, name) else { continue };
+      declared.push(name.clone());
+      // the stubs would not help if a signature hides them, or if the structure is not a `struct`.
+      if str_bind.ascription_tail().is_some() {
+        continue;
+      }
+      let Some(ast::StrExp::StructStrExp(nested_str_exp)) =
+        str_bind.eq_str_exp().and_then(|x| x.str_exp())
+      else {
+        continue;
+      };
+      prefix.push(name.clone());
+      self.stubs(&nested_str_exp, nested, prefix, edits)?;
+      prefix.pop();
+    }
+    let lines = self.decs(missing, prefix, &declared);
+    if lines.is_empty() {
+      return Some(());
+    }
+    let indent = line_indent(&str_exp.struct_kw()?);
+    let end_kw = str_exp.end_kw()?;
+    let mut last = end_kw.prev_token()?;
+    let mut newline = false;
+    while matches!(last.kind(), SyntaxKind::Whitespace | SyntaxKind::BlockComment) {
+      newline = newline || last.text().contains('\n');
+      last = last.prev_token()?;
+    }
+    let mut text = String::new();
+    for line in lines {
+      text.push_str(&format!("\n{indent}{INDENT}{line}"));
+    }
+    if !newline {
+      text.push('\n');
+      text.push_str(&indent);
+    }
+    edits.push((TextRange::empty(last.text_range().end()), text));
+    Some(())
+  }
+
+  /// Returns the lines of the stub declarations for everything in the env, in the structure at the
+  /// `prefix`, except for the structures it already declares.
+  fn decs(&self, env: &Env, prefix: &[Name], declared: &[Name]) -> Vec<String> {
+    let mut items = Vec::<Item>::new();
+    for (name, str_env) in env.str_env.iter() {
+      if declared.contains(name) {
+        continue;
+      }
+      let mut str_prefix = prefix.to_vec();
+      str_prefix.push(name.clone());
+      let mut lines = vec![format!("structure {name} = struct")];
+      let decs = self.decs(str_env, &str_prefix, &[]);
+      lines.extend(decs.into_iter().map(|line| format!("{INDENT}{line}")));
+      lines.push("end".to_owned());
+      let pos = self.pos(str_env.def, name);
+      items.push(Item { pos, name: name.clone(), kind: Kind::Lines(lines) });
+    }
+    for (name, ty_info) in env.ty_env.iter() {
+      let pos = self.pos(ty_info.def, name);
+      items.push(Item { pos, name: name.clone(), kind: self.ty_dec(name, ty_info, prefix) });
+    }
+    for (name, val_info) in env.val_env.iter() {
+      let ty = self.st.tys.data(val_info.ty_scheme.ty);
+      let line = match val_info.id_status {
+        // declared along with their datatypes.
+        IdStatus::Con => continue,
+        IdStatus::Exn(_) => match ty {
+          TyData::Fn(data) => {
+            let param = TyScheme::zero(data.param);
+            format!("exception {name} of {}", param.display_relative(self.st, LINES, prefix))
+          }
+          _ => format!("exception {name}"),
+        },
+        IdStatus::Val => {
+          let op =
+            if name.as_str().starts_with(|c: char| c.is_ascii_alphabetic()) { "" } else { "op " };
+          if matches!(ty, TyData::Fn(_)) {
+            format!("fun {op}{name} _ = {BODY}")
+          } else {
+            let ty_scheme = val_info.ty_scheme.display_relative(self.st, LINES, prefix);
+            format!("val {op}{name} : {ty_scheme} = {BODY}")
+          }
+        }
+      };
+      let pos = val_info.defs.iter().map(|&def| self.pos(Some(def), name)).min();
+      let pos = pos.unwrap_or(u32::MAX);
+      items.push(Item { pos, name: name.clone(), kind: Kind::Lines(vec![line]) });
+    }
+    items.sort_by(|a, b| a.pos.cmp(&b.pos).then_with(|| a.name.as_str().cmp(b.name.as_str())));
+    let mut ret = Vec::<String>::new();
+    let mut prev_datatype = None::<def::Def>;
+    for item in items {
+      match item.kind {
+        Kind::Lines(lines) => {
+          prev_datatype = None;
+          ret.extend(lines);
+        }
+        Kind::Datatype(def, line) => {
+          let kw = if def.is_some() && def == prev_datatype { "and" } else { "datatype" };
+          prev_datatype = def;
+          ret.push(format!("{kw} {line}"));
+        }
+      }
+    }
+    ret
+  }
+
+  fn ty_dec(&self, name: &Name, ty_info: &TyInfo, prefix: &[Name]) -> Kind {
+    let head = ty_info.ty_scheme.display_head(name);
+    let sym_info = match self.st.tys.data(ty_info.ty_scheme.ty) {
+      TyData::Con(data) => self.st.syms.get(data.sym),
+      _ => None,
+    };
+    match sym_info {
+      // a type the signature declares, which the stub must declare too. the statics gives these the
+      // path of the ascribed structure, even in the structures nested in it.
+      Some(sym_info)
+        if prefix.starts_with(sym_info.path.prefix()) && sym_info.path.last() == name =>
+      {
+        if ty_info.val_env.is_empty() {
+          return Kind::Lines(vec![format!("type {head} = unit")]);
+        }
+        let cons: Vec<_> = sym_info
+          .ty_info
+          .val_env
+          .iter()
+          .map(|(con, val_info)| match self.st.tys.data(val_info.ty_scheme.ty) {
+            TyData::Fn(data) => {
+              let bound_vars = val_info.ty_scheme.bound_vars.clone();
+              let param = TyScheme { bound_vars, ty: data.param };
+              format!("{con} of {}", param.display_relative(self.st, LINES, prefix))
+            }
+            _ => con.to_string(),
+          })
+          .collect();
+        Kind::Datatype(ty_info.def, format!("{head} = {}", cons.join(" | ")))
+      }
+      Some(sym_info) if !ty_info.val_env.is_empty() => {
+        let path = &sym_info.path;
+        let common = path.prefix().iter().zip(prefix).take_while(|(a, b)| a == b).count();
+        let mut other = String::new();
+        for name in &path.prefix()[common..] {
+          other.push_str(name.as_str());
+          other.push('.');
+        }
+        other.push_str(path.last().as_str());
+        Kind::Lines(vec![format!("datatype {name} = datatype {other}")])
+      }
+      _ => {
+        let ty_scheme = ty_info.ty_scheme.display_relative(self.st, LINES, prefix);
+        Kind::Lines(vec![format!("type {head} = {ty_scheme}")])
+      }
+    }
+  }
+}
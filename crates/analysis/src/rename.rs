@@ -0,0 +1,248 @@
+//! Renaming an item across all files.
+
+use crate::source_files;
+use fast_hash::FxHashSet;
+use paths::{PathId, PathMap, WithPath};
+use sml_namespace::SymbolKind;
+use sml_statics_types::{def, info::IdStatus};
+use sml_syntax::{SyntaxKind, SyntaxToken};
+use std::fmt;
+use text_pos::{PositionUtf16, RangeUtf16};
+
+/// An error when renaming.
+#[derive(Debug)]
+pub enum RenameError {
+  /// There was no item to rename at the position.
+  NoItem,
+  /// The item was defined in the std basis.
+  StdBasis,
+  /// The new name was not a valid name for the item.
+  InvalidName(String, SymbolKind),
+  /// The new name would change the identifier status of some other names.
+  ChangesIdStatus(String),
+}
+
+impl fmt::Display for RenameError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      RenameError::NoItem => f.write_str("no item to rename at this position"),
+      RenameError::StdBasis => f.write_str("cannot rename an item defined in the std basis"),
+      RenameError::InvalidName(name, kind) => {
+        write!(f, "`{name}` is not a valid name for a {}", kind_desc(*kind))
+      }
+      RenameError::ChangesIdStatus(name) => {
+        write!(f, "renaming to `{name}` would change the identifier status of some names")
+      }
+    }
+  }
+}
+
+impl std::error::Error for RenameError {}
+
+fn kind_desc(kind: SymbolKind) -> &'static str {
+  match kind {
+    SymbolKind::Structure => "structure",
+    SymbolKind::Signature => "signature",
+    SymbolKind::Functor => "functor",
+    SymbolKind::Type => "type",
+    SymbolKind::Constructor => "constructor",
+    SymbolKind::Exception => "exception",
+    SymbolKind::Function | SymbolKind::Value => "value",
+  }
+}
+
+/// The thing being renamed.
+pub(crate) struct Target {
+  /// The token at the position that was asked to be renamed.
+  pub(crate) token: SyntaxToken,
+  name: String,
+  kind: SymbolKind,
+  defs: FxHashSet<def::Def>,
+}
+
+pub(crate) fn target(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Result<Target, RenameError> {
+  let ft = source_files::file_and_token(source_files, pos).ok_or(RenameError::NoItem)?;
+  if !matches!(ft.token.kind(), SyntaxKind::Name | SyntaxKind::Star) {
+    return Err(RenameError::NoItem);
+  }
+  let name = ft.token.text().to_owned();
+  let defs = ft.get_defs(pos.path).ok_or(RenameError::NoItem)?;
+  let mut kind = None::<SymbolKind>;
+  let mut target_defs = FxHashSet::<def::Def>::default();
+  for def in defs {
+    let idx = def.to_regular_idx().ok_or(RenameError::StdBasis)?;
+    let Some(file) = source_files.get(&idx.path) else { continue };
+    let Some(k) = def_kind(&file.syntax.lower.arenas, idx.val, &name) else { continue };
+    // only keep the defs that actually mention the name.
+    if source_files::name_tokens(file, idx.val, &name).is_empty() {
+      continue;
+    }
+    kind.get_or_insert(k);
+    target_defs.insert(def);
+  }
+  let kind = kind.ok_or(RenameError::NoItem)?;
+  Ok(Target { token: ft.token, name, kind, defs: target_defs })
+}
+
+pub(crate) fn get(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+  new_name: &str,
+) -> Result<PathMap<Vec<(RangeUtf16, String)>>, RenameError> {
+  let target = target(source_files, pos)?;
+  if !is_valid_name(new_name, target.kind) {
+    return Err(RenameError::InvalidName(new_name.to_owned(), target.kind));
+  }
+  let mut tokens = FxHashSet::<(PathId, SyntaxToken)>::default();
+  for &def in &target.defs {
+    let Some(idx) = def.to_regular_idx() else { continue };
+    let file = &source_files[&idx.path];
+    tokens.extend(
+      source_files::name_tokens(file, idx.val, &target.name).into_iter().map(|tok| (idx.path, tok)),
+    );
+    for (&path, file) in source_files {
+      for idx in file.info.get_with_def(def) {
+        tokens.extend(
+          source_files::name_tokens(file, idx, &target.name).into_iter().map(|tok| (path, tok)),
+        );
+      }
+    }
+  }
+  if matches!(target.kind, SymbolKind::Structure) {
+    for (&path, file) in source_files {
+      tokens.extend(
+        source_files::str_path_tokens(file, &target.name, &target.defs)
+          .into_iter()
+          .map(|tok| (path, tok)),
+      );
+    }
+  }
+  let mut ret = PathMap::<Vec<(RangeUtf16, String)>>::default();
+  for (path, tok) in tokens {
+    let file = &source_files[&path];
+    let Some(range) = file.syntax.pos_db.range_utf16(tok.text_range()) else { continue };
+    let is_pun = tok
+      .parent()
+      .map_or(false, |p| matches!(p.kind(), SyntaxKind::LabPatRow | SyntaxKind::ExpRow));
+    let new_text =
+      if is_pun { format!("{} = {new_name}", target.name) } else { new_name.to_owned() };
+    ret.entry(path).or_default().push((range, new_text));
+  }
+  check_id_status(source_files, &target, new_name, &ret)?;
+  for edits in ret.values_mut() {
+    edits.sort_unstable_by_key(|(range, _)| (range.start.line, range.start.col));
+  }
+  Ok(ret)
+}
+
+/// Returns whether the name is lexically valid for an item of the given kind.
+fn is_valid_name(name: &str, kind: SymbolKind) -> bool {
+  let lexed = sml_lex::get(name);
+  if !lexed.errors.is_empty() {
+    return false;
+  }
+  let [tok] = lexed.tokens.as_slice() else { return false };
+  let alpha = name.starts_with(|c: char| c.is_ascii_alphabetic());
+  match kind {
+    SymbolKind::Structure | SymbolKind::Signature | SymbolKind::Functor | SymbolKind::Type => {
+      matches!(tok.kind, SyntaxKind::Name) && alpha
+    }
+    SymbolKind::Constructor | SymbolKind::Exception | SymbolKind::Function | SymbolKind::Value => {
+      matches!(tok.kind, SyntaxKind::Name | SyntaxKind::Star)
+    }
+  }
+}
+
+/// Errors if the new name, in the files we're about to change, is already bound as a value with a
+/// different identifier status than the target, or if the target is a constructor or exception and
+/// the new name is used as a variable pattern.
+fn check_id_status(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  target: &Target,
+  new_name: &str,
+  edits: &PathMap<Vec<(RangeUtf16, String)>>,
+) -> Result<(), RenameError> {
+  let want: fn(IdStatus) -> bool = match target.kind {
+    SymbolKind::Function | SymbolKind::Value => |x| matches!(x, IdStatus::Val),
+    SymbolKind::Constructor => |x| matches!(x, IdStatus::Con),
+    SymbolKind::Exception => |x| matches!(x, IdStatus::Exn(_)),
+    SymbolKind::Structure | SymbolKind::Signature | SymbolKind::Functor | SymbolKind::Type => {
+      return Ok(())
+    }
+  };
+  let is_con = matches!(target.kind, SymbolKind::Constructor | SymbolKind::Exception);
+  for path in edits.keys() {
+    let file = &source_files[path];
+    let envs = [&file.scope.env, &file.info.basis().env];
+    let other_status = envs
+      .into_iter()
+      .filter_map(|env| env.val_env.get(new_name))
+      .any(|val_info| !want(val_info.id_status));
+    let var_pat = is_con
+      && file.syntax.lower.arenas.pat.iter().any(|(idx, pat)| match pat {
+        sml_hir::Pat::Con(path, None) => {
+          path.prefix().is_empty()
+            && path.last().as_str() == new_name
+            && file.info.get_defs(idx.into()).is_empty()
+        }
+        _ => false,
+      });
+    if other_status || var_pat {
+      return Err(RenameError::ChangesIdStatus(new_name.to_owned()));
+    }
+  }
+  Ok(())
+}
+
+/// Returns what kind of item with this name is defined at this index.
+fn def_kind(arenas: &sml_hir::Arenas, idx: sml_hir::Idx, name: &str) -> Option<SymbolKind> {
+  let has_con = |dat_binds: &[sml_hir::DatBind]| {
+    dat_binds.iter().flat_map(|x| &x.cons).any(|x| x.name.as_str() == name)
+  };
+  let ret = match idx {
+    sml_hir::Idx::Pat(_) => SymbolKind::Value,
+    sml_hir::Idx::Dec(idx) => match &arenas.dec[idx] {
+      sml_hir::Dec::Val(..) => SymbolKind::Value,
+      sml_hir::Dec::Ty(_) | sml_hir::Dec::DatatypeCopy(..) => SymbolKind::Type,
+      sml_hir::Dec::Datatype(dat_binds, _) | sml_hir::Dec::Abstype(dat_binds, _, _) => {
+        if has_con(dat_binds) {
+          SymbolKind::Constructor
+        } else {
+          SymbolKind::Type
+        }
+      }
+      sml_hir::Dec::Exception(_) => SymbolKind::Exception,
+      sml_hir::Dec::Local(..) | sml_hir::Dec::Open(_) => return None,
+    },
+    sml_hir::Idx::StrDec(idx) => match &arenas.str_dec[idx] {
+      sml_hir::StrDec::Structure(_) => SymbolKind::Structure,
+      sml_hir::StrDec::Signature(_) => SymbolKind::Signature,
+      sml_hir::StrDec::Functor(_) => SymbolKind::Functor,
+      sml_hir::StrDec::Dec(_) | sml_hir::StrDec::Local(..) => return None,
+    },
+    sml_hir::Idx::Spec(idx) => match &arenas.spec[idx] {
+      sml_hir::Spec::Val(..) => SymbolKind::Value,
+      sml_hir::Spec::Ty(_) | sml_hir::Spec::EqTy(_) | sml_hir::Spec::DatatypeCopy(..) => {
+        SymbolKind::Type
+      }
+      sml_hir::Spec::Datatype(dat_desc) => {
+        if has_con(std::slice::from_ref(dat_desc)) {
+          SymbolKind::Constructor
+        } else {
+          SymbolKind::Type
+        }
+      }
+      sml_hir::Spec::Exception(_) => SymbolKind::Exception,
+      sml_hir::Spec::Str(_) => SymbolKind::Structure,
+      sml_hir::Spec::Include(_) | sml_hir::Spec::Sharing(..) => return None,
+    },
+    sml_hir::Idx::StrExp(_)
+    | sml_hir::Idx::SigExp(_)
+    | sml_hir::Idx::Exp(_)
+    | sml_hir::Idx::Ty(_) => return None,
+  };
+  Some(ret)
+}
@@ -0,0 +1,24 @@
+//! Expanding the selection along the syntax tree.
+
+use crate::source_files;
+use paths::{PathMap, WithPath};
+use text_pos::{PositionUtf16, RangeUtf16};
+use text_size_util::TextRange;
+
+pub(crate) fn get(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<Vec<RangeUtf16>> {
+  let ft = source_files::file_and_token(source_files, pos)?;
+  let mut ranges = Vec::<TextRange>::new();
+  // nodes may start or end with trivia, like whitespace and comments, which is not worth selecting.
+  let iter = std::iter::once(ft.token.text_range())
+    .chain(ft.token.parent_ancestors().filter_map(|node| Some(sml_syntax::trimmed(&node)?.0)));
+  for range in iter {
+    // many nodes have the same range as their only child, e.g. a `PathExp` and its `Path`.
+    if ranges.last().map_or(true, |&last| last != range) {
+      ranges.push(range);
+    }
+  }
+  ranges.into_iter().map(|range| ft.file.syntax.pos_db.range_utf16(range)).collect()
+}
@@ -0,0 +1,139 @@
+//! Classifying the names in a source file, for semantic highlighting.
+
+use crate::{source_files, SemanticToken, SemanticTokenKind};
+use sml_namespace::SymbolKind;
+use sml_statics_types::def;
+use sml_syntax::ast::{AstNode as _, SyntaxNodePtr};
+use sml_syntax::{rowan::NodeOrToken, SyntaxKind, SyntaxNode, SyntaxToken};
+use text_size_util::TextRange;
+
+pub(crate) fn get(
+  st: &sml_statics_types::St,
+  file: &mlb_statics::SourceFile,
+  range: Option<TextRange>,
+) -> Vec<SemanticToken> {
+  let root = file.syntax.parse.syntax();
+  let range = range.unwrap_or_else(|| root.text_range());
+  root
+    .descendants_with_tokens()
+    .filter_map(NodeOrToken::into_token)
+    .filter(|token| range.contains_range(token.text_range()))
+    .filter_map(|token| {
+      let (kind, std_basis, is_ref) = classify(st, file, &token)?;
+      let range = file.syntax.pos_db.range_utf16(token.text_range())?;
+      Some(SemanticToken { range, kind, std_basis, is_ref })
+    })
+    .collect()
+}
+
+/// How we know what kind of symbol a name is.
+enum Class {
+  /// We know from the syntax alone.
+  Syntax(SymbolKind),
+  /// It is a value, and we need the statics to know what kind of value.
+  Val,
+}
+
+/// Returns the kind of the token, whether it is defined in the std basis, and whether it has a
+/// `ref` type.
+fn classify(
+  st: &sml_statics_types::St,
+  file: &mlb_statics::SourceFile,
+  token: &SyntaxToken,
+) -> Option<(SemanticTokenKind, bool, bool)> {
+  match token.kind() {
+    SyntaxKind::TyVar => return Some((SemanticTokenKind::TyVar, false, false)),
+    SyntaxKind::Name | SyntaxKind::Star | SyntaxKind::Eq => {}
+    _ => return None,
+  }
+  let (class, node) = syntactic_class(token)?;
+  let indices = node
+    .and_then(|node| file.syntax.lower.ptrs.ast_to_hir_all(&SyntaxNodePtr::new(&node)))
+    .unwrap_or_default();
+  let std_basis = match (&class, source_files::str_path_prefix(token)) {
+    (Class::Syntax(SymbolKind::Structure), Some(names)) => {
+      is_std_basis(source_files::str_def(file, token, &names))
+    }
+    _ => is_std_basis(indices.iter().flat_map(|&idx| file.info.get_defs(idx))),
+  };
+  let (kind, is_ref) = match class {
+    Class::Syntax(kind) => (kind, false),
+    Class::Val => indices
+      .iter()
+      .find_map(|&idx| {
+        let kind = file.info.get_val_symbol_kind(st, idx)?;
+        Some((kind, file.info.is_ref_ty(st, idx)))
+      })
+      .unwrap_or((SymbolKind::Value, false)),
+  };
+  Some((SemanticTokenKind::Symbol(kind), std_basis, is_ref))
+}
+
+/// Returns what we know about the name token from the syntax, and the node whose HIR indices have
+/// more information about it, if any.
+fn syntactic_class(token: &SyntaxToken) -> Option<(Class, Option<SyntaxNode>)> {
+  let parent = token.parent()?;
+  let is_name = token.kind() == SyntaxKind::Name;
+  let ret = match parent.kind() {
+    SyntaxKind::StrBind | SyntaxKind::FunctorArgNameSigExp if is_name => {
+      (Class::Syntax(SymbolKind::Structure), None)
+    }
+    SyntaxKind::FunctorBind if is_name => (Class::Syntax(SymbolKind::Functor), None),
+    SyntaxKind::AppStrExp if is_name => (Class::Syntax(SymbolKind::Functor), Some(parent)),
+    SyntaxKind::SigBind if is_name => (Class::Syntax(SymbolKind::Signature), None),
+    SyntaxKind::NameSigExp if is_name => (Class::Syntax(SymbolKind::Signature), Some(parent)),
+    SyntaxKind::TyBind | SyntaxKind::DatBind | SyntaxKind::DatCopyDec if is_name => {
+      (Class::Syntax(SymbolKind::Type), None)
+    }
+    // the rest hold a `NameStarEq`, which is a name, `*`, or `=` token directly in the node.
+    SyntaxKind::ConBind => (Class::Syntax(SymbolKind::Constructor), None),
+    SyntaxKind::ExBind => (Class::Syntax(SymbolKind::Exception), None),
+    SyntaxKind::PrefixFunBindCaseHead | SyntaxKind::InfixFunBindCaseHead => {
+      (Class::Syntax(SymbolKind::Function), None)
+    }
+    SyntaxKind::InfixExp | SyntaxKind::InfixPat | SyntaxKind::LabPatRow => {
+      (Class::Val, Some(parent))
+    }
+    SyntaxKind::NameStarEqDot => path_class(token, &parent)?,
+    _ => return None,
+  };
+  Some(ret)
+}
+
+/// Returns the class of a name in a path.
+fn path_class(
+  token: &SyntaxToken,
+  name_star_eq_dot: &SyntaxNode,
+) -> Option<(Class, Option<SyntaxNode>)> {
+  if source_files::str_path_prefix(token).is_some() {
+    return Some((Class::Syntax(SymbolKind::Structure), None));
+  }
+  let path = name_star_eq_dot.parent()?;
+  let node = path.parent()?;
+  let ret = match node.kind() {
+    SyntaxKind::PathExp | SyntaxKind::ConPat => (Class::Val, Some(node)),
+    SyntaxKind::ConTy
+    | SyntaxKind::OneArgConTy
+    | SyntaxKind::WhereTypeSigExp
+    | SyntaxKind::DatCopyDec => (Class::Syntax(SymbolKind::Type), Some(node)),
+    SyntaxKind::PathStrExp => (Class::Syntax(SymbolKind::Structure), Some(node)),
+    SyntaxKind::WhereSigExp => (Class::Syntax(SymbolKind::Structure), None),
+    SyntaxKind::PathEq => {
+      let sharing = node.parent()?;
+      let is_ty = sharing.children_with_tokens().any(|x| x.kind() == SyntaxKind::TypeKw);
+      let kind = if is_ty { SymbolKind::Type } else { SymbolKind::Structure };
+      (Class::Syntax(kind), None)
+    }
+    _ => return None,
+  };
+  Some(ret)
+}
+
+/// Returns whether there is at least one def, and all defs are from the std basis.
+fn is_std_basis<I>(defs: I) -> bool
+where
+  I: IntoIterator<Item = def::Def>,
+{
+  let mut defs = defs.into_iter().peekable();
+  defs.peek().is_some() && defs.all(|def| def.to_regular_idx().is_none())
+}
@@ -0,0 +1,171 @@
+//! Signature help for function and functor application.
+
+use crate::SignatureHelp;
+use paths::{PathMap, WithPath};
+use sml_statics_types::ty::{Ty, TyData, TyScheme};
+use sml_statics_types::{def, St};
+use sml_syntax::ast::{self, AstNode as _, SyntaxNodePtr};
+use sml_syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+use text_pos::PositionUtf16;
+use text_size_util::TextSize;
+
+/// Returns the signature help at the position, and the defs of the item being applied, so the
+/// caller can find documentation for it.
+pub(crate) fn get(
+  st: &St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Option<(SignatureHelp, Vec<def::Def>)> {
+  let file = source_files.get(&pos.path)?;
+  let offset = file.syntax.pos_db.text_size_utf16(pos.val)?;
+  let token = token_before(&file.syntax.parse.syntax(), offset)?;
+  for node in token.parent_ancestors() {
+    if let Some(app) = ast::AppStrExp::cast(node.clone()) {
+      return functor(source_files, file, &app);
+    }
+    if ast::AppExp::can_cast(node.kind()) {
+      let (head, args) = spine(node);
+      return fun(st, file, &head, &args, offset);
+    }
+  }
+  let head = token.parent_ancestors().find(|node| ast::PathExp::can_cast(node.kind()))?;
+  fun(st, file, &head, &[], offset)
+}
+
+/// Returns the last non-trivia token that starts before the offset.
+fn token_before(root: &SyntaxNode, offset: TextSize) -> Option<SyntaxToken> {
+  let mut token = root.token_at_offset(offset).left_biased()?;
+  while matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::BlockComment)
+    || token.text_range().start() >= offset
+  {
+    token = token.prev_token()?;
+  }
+  Some(token)
+}
+
+/// Returns the head and arguments of the whole application that `node`, an `AppExp`, is a part of.
+fn spine(mut node: SyntaxNode) -> (SyntaxNode, Vec<SyntaxNode>) {
+  while let Some(parent) = node.parent().and_then(ast::AppExp::cast) {
+    if parent.func().map_or(true, |func| *func.syntax() != node) {
+      break;
+    }
+    node = parent.syntax().clone();
+  }
+  let mut args = Vec::<SyntaxNode>::new();
+  let mut head = node;
+  while let Some(app) = ast::AppExp::cast(head.clone()) {
+    let (Some(func), Some(arg)) = (app.func(), app.arg()) else { break };
+    args.push(arg.syntax().clone());
+    head = func.syntax().clone();
+  }
+  args.reverse();
+  (head, args)
+}
+
+fn fun(
+  st: &St,
+  file: &mlb_statics::SourceFile,
+  head: &SyntaxNode,
+  args: &[SyntaxNode],
+  offset: TextSize,
+) -> Option<(SignatureHelp, Vec<def::Def>)> {
+  let idx = file.syntax.lower.ptrs.ast_to_hir(&SyntaxNodePtr::new(head))?;
+  let ty_scheme = file.info.get_ty_scheme(idx)?;
+  let mut params = Vec::<Vec<TyScheme>>::new();
+  let mut ty = ty_scheme.ty;
+  let sub = |ty: Ty| TyScheme { bound_vars: ty_scheme.bound_vars.clone(), ty };
+  while let TyData::Fn(data) = st.tys.data(ty) {
+    let param = match st.tys.data(data.param) {
+      TyData::Record(rows) if is_tuple(&rows) => rows.values().map(|&ty| sub(ty)).collect(),
+      _ => vec![sub(data.param)],
+    };
+    params.push(param);
+    ty = data.res;
+  }
+  if params.is_empty() {
+    return None;
+  }
+  // the curried param the cursor is on, and the tuple component in that param.
+  let curried = args.iter().position(|arg| offset <= arg.text_range().end()).unwrap_or(args.len());
+  let component = args.get(curried).cloned().and_then(ast::TupleExp::cast).map_or(0, |tuple| {
+    tuple.exp_args().filter_map(|x| x.comma()).filter(|c| c.text_range().end() <= offset).count()
+  });
+  let mut label = format!("{} : ", head.text().to_string().trim());
+  let mut ret_params = Vec::<[u32; 2]>::new();
+  let mut active_param = None::<u32>;
+  for (i, param) in params.iter().enumerate() {
+    if i != 0 {
+      label.push_str(" -> ");
+    }
+    for (j, ty_scheme) in param.iter().enumerate() {
+      if j != 0 {
+        label.push_str(" * ");
+      }
+      let needs_parens = match st.tys.data(ty_scheme.ty) {
+        TyData::Fn(_) => true,
+        TyData::Record(rows) => is_tuple(&rows),
+        _ => false,
+      };
+      if i == curried && j == component.min(param.len() - 1) {
+        active_param = Some(u32::try_from(ret_params.len()).ok()?);
+      }
+      let start = utf16_len(&label);
+      let ty = ty_scheme.display(st, config::DiagnosticLines::One).to_string();
+      if needs_parens {
+        label.push('(');
+        label.push_str(&ty);
+        label.push(')');
+      } else {
+        label.push_str(&ty);
+      }
+      ret_params.push([start, utf16_len(&label)]);
+    }
+  }
+  let res = sub(ty);
+  label.push_str(" -> ");
+  label.push_str(&res.display(st, config::DiagnosticLines::One).to_string());
+  let defs = file.info.get_defs(idx).into_iter().collect();
+  Some((SignatureHelp { label, params: ret_params, active_param, documentation: None }, defs))
+}
+
+fn functor(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  file: &mlb_statics::SourceFile,
+  app: &ast::AppStrExp,
+) -> Option<(SignatureHelp, Vec<def::Def>)> {
+  let name = app.name()?;
+  let name = name.text();
+  let fun_sig = [&file.info.basis().fun_env, &file.scope.fun_env]
+    .into_iter()
+    .find_map(|fun_env| fun_env.get(name))?;
+  let def = fun_sig.body_env.def?;
+  let idx = def.to_regular_idx()?;
+  let def_file = source_files.get(&idx.path)?;
+  let ptr = def_file.syntax.lower.ptrs.hir_to_ast(idx.val)?;
+  let fun_bind = ptr
+    .to_node(&def_file.syntax.parse.syntax())
+    .descendants()
+    .filter_map(ast::FunctorBind::cast)
+    .find(|x| x.functor_name().map_or(false, |tok| tok.text() == name))?;
+  let param: Vec<_> = fun_bind.functor_args().map(|x| x.syntax().to_string()).collect();
+  let param = param.join(" ");
+  let param: Vec<_> = param.split_whitespace().collect();
+  let param = param.join(" ");
+  let mut label = format!("{name} (");
+  let start = utf16_len(&label);
+  label.push_str(&param);
+  let end = utf16_len(&label);
+  label.push(')');
+  Some((
+    SignatureHelp { label, params: vec![[start, end]], active_param: Some(0), documentation: None },
+    vec![def],
+  ))
+}
+
+fn is_tuple(rows: &sml_statics_types::ty::RecordData) -> bool {
+  rows.len() > 1 && rows.keys().enumerate().all(|(idx, lab)| sml_hir::Lab::tuple(idx) == *lab)
+}
+
+fn utf16_len(s: &str) -> u32 {
+  u32::try_from(s.encode_utf16().count()).expect("label too long")
+}
@@ -1,9 +1,13 @@
 //! Helpers for working with source files.
 
-use paths::{PathMap, WithPath};
-use sml_syntax::ast::{AstNode as _, SyntaxNodePtr};
-use sml_syntax::{rowan::TokenAtOffset, SyntaxKind, SyntaxToken};
+use fast_hash::FxHashSet;
+use paths::{PathId, PathMap, WithPath};
+use sml_statics_types::def;
+use sml_syntax::ast::{self, AstNode as _, SyntaxNodePtr};
+use sml_syntax::rowan::{NodeOrToken, TokenAtOffset};
+use sml_syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
 use text_pos::{PositionUtf16, RangeUtf16};
+use text_size_util::{TextRange, TextSize};
 
 pub(crate) fn path_and_range(
   source_files: &PathMap<mlb_statics::SourceFile>,
@@ -20,7 +24,7 @@ pub(crate) fn file_and_token(
 ) -> Option<FileAndToken<'_>> {
   let file = source_files.get(&pos.path)?;
   let offset = file.syntax.pos_db.text_size_utf16(pos.val)?;
-  let syntax = file.syntax.parse.root.syntax();
+  let syntax = file.syntax.parse.syntax();
   let tr = syntax.text_range();
   if !tr.contains_inclusive(offset) {
     return None;
@@ -39,6 +43,21 @@ pub(crate) fn file_and_token(
   Some(FileAndToken { file, token })
 }
 
+/// Returns the statics errors in the file whose diagnostic ranges contain the offset, with those
+/// ranges and the nodes they are for.
+pub(crate) fn statics_errors_at(
+  file: &mlb_statics::SourceFile,
+  offset: TextSize,
+) -> impl Iterator<Item = (TextRange, SyntaxNode, &sml_statics::Error)> + '_ {
+  let root = file.syntax.parse.syntax();
+  file.statics_errors.iter().filter_map(move |err| {
+    let ptr = file.syntax.lower.ptrs.hir_to_ast(err.idx())?;
+    let node = ptr.to_node(&root);
+    let range = sml_syntax::node_range(&node);
+    range.contains_inclusive(offset).then_some((range, node, err))
+  })
+}
+
 pub(crate) struct FileAndToken<'a> {
   pub(crate) file: &'a mlb_statics::SourceFile,
   pub(crate) token: SyntaxToken,
@@ -56,6 +75,27 @@ impl FileAndToken<'_> {
     }
   }
 
+  /// Returns the defs of the name token, treating indices with no defs as def sites themselves.
+  pub(crate) fn get_defs(&self, path: PathId) -> Option<FxHashSet<def::Def>> {
+    let mut ret = FxHashSet::<def::Def>::default();
+    match str_path_prefix(&self.token) {
+      // the token is a structure name in a path, like the `A` in `A.x`.
+      Some(names) => ret.extend(str_def(self.file, &self.token, &names)),
+      None => {
+        let (_, indices) = self.get_ptr_and_indices()?;
+        for &idx in indices {
+          let got = self.file.info.get_defs(idx);
+          if got.is_empty() {
+            ret.insert(def::Def::Path(def::Path::Regular(path), idx));
+          } else {
+            ret.extend(got);
+          }
+        }
+      }
+    }
+    Some(ret)
+  }
+
   pub(crate) fn get_ptr_and_indices(&self) -> Option<(SyntaxNodePtr, &[sml_hir::Idx])> {
     let mut node = self.token.parent()?;
     loop {
@@ -68,6 +108,100 @@ impl FileAndToken<'_> {
   }
 }
 
+/// If the token is a structure name in a path, returns the names of the path up to and including
+/// the token.
+pub(crate) fn str_path_prefix(token: &SyntaxToken) -> Option<Vec<str_util::Name>> {
+  let path = token.parent_ancestors().find_map(ast::Path::cast)?;
+  let in_open = path.syntax().parent().map_or(false, |p| p.kind() == SyntaxKind::OpenDec);
+  let parts: Vec<_> = path.name_star_eq_dots().filter_map(|x| x.name_star_eq()).collect();
+  let idx = parts.iter().position(|x| x.token == *token)?;
+  if idx + 1 == parts.len() && !in_open {
+    return None;
+  }
+  Some(parts[..=idx].iter().map(|x| str_util::Name::new(x.token.text())).collect())
+}
+
+/// Returns the def of the structure with the given names, which start the path with the token, as
+/// the statics looked it up there.
+pub(crate) fn str_def(
+  file: &mlb_statics::SourceFile,
+  token: &SyntaxToken,
+  names: &[str_util::Name],
+) -> Option<def::Def> {
+  let ft = FileAndToken { file, token: token.clone() };
+  let (_, indices) = ft.get_ptr_and_indices()?;
+  indices.iter().find_map(|&idx| file.info.get_str_def(idx, names))
+}
+
+/// Returns the tokens for the name in the syntax for the index.
+///
+/// Many indices point at syntax that contains more than just the name, e.g. a whole `datatype`
+/// declaration, so we look for the name inside the syntax.
+pub(crate) fn name_tokens(
+  file: &mlb_statics::SourceFile,
+  idx: sml_hir::Idx,
+  name: &str,
+) -> Vec<SyntaxToken> {
+  let Some(ptr) = file.syntax.lower.ptrs.hir_to_ast(idx) else { return Vec::new() };
+  let node = ptr.to_node(&file.syntax.parse.syntax());
+  node_name_tokens(&node, name)
+}
+
+fn node_name_tokens(node: &SyntaxNode, name: &str) -> Vec<SyntaxToken> {
+  if let Some(fun_bind) = ast::FunBind::cast(node.clone()) {
+    return fun_bind
+      .fun_bind_cases()
+      .filter_map(|case| {
+        let tok = match case.fun_bind_case_head()? {
+          ast::FunBindCaseHead::PrefixFunBindCaseHead(head) => head.name_star_eq()?.token,
+          ast::FunBindCaseHead::InfixFunBindCaseHead(head) => head.name_star_eq()?.token,
+        };
+        (tok.text() == name).then_some(tok)
+      })
+      .collect();
+  }
+  let is_name = |tok: &SyntaxToken| {
+    matches!(tok.kind(), SyntaxKind::Name | SyntaxKind::Star) && tok.text() == name
+  };
+  // e.g. the operator in an infix expression.
+  if let Some(tok) = node.children_with_tokens().filter_map(NodeOrToken::into_token).find(is_name) {
+    return vec![tok];
+  }
+  if let Some(path) = node.children().find_map(ast::Path::cast) {
+    let tok = path.name_star_eq_dots().last().and_then(|x| x.name_star_eq()).map(|x| x.token);
+    return tok.into_iter().filter(|tok| tok.text() == name).collect();
+  }
+  node
+    .descendants_with_tokens()
+    .filter_map(NodeOrToken::into_token)
+    .find(is_name)
+    .into_iter()
+    .collect()
+}
+
+/// Returns the structure name tokens in paths in the file that refer to the structure with the name
+/// and one of the defs.
+pub(crate) fn str_path_tokens(
+  file: &mlb_statics::SourceFile,
+  name: &str,
+  defs: &FxHashSet<def::Def>,
+) -> Vec<SyntaxToken> {
+  file
+    .syntax
+    .parse
+    .syntax()
+    .descendants()
+    .filter_map(ast::Path::cast)
+    .flat_map(|path| path.name_star_eq_dots().filter_map(|x| x.name_star_eq()))
+    .map(|x| x.token)
+    .filter(|tok| tok.text() == name)
+    .filter(|tok| {
+      let Some(names) = str_path_prefix(tok) else { return false };
+      str_def(file, tok, &names).map_or(false, |def| defs.contains(&def))
+    })
+    .collect()
+}
+
 fn priority(kind: SyntaxKind) -> u8 {
   match kind {
     SyntaxKind::Name => 5,
@@ -0,0 +1,112 @@
+//! Suggesting replacements for undefined names.
+
+use crate::source_files;
+use paths::{PathMap, WithPath};
+use sml_statics_types::ty::{Generalizable, TyScheme};
+use sml_statics_types::util::instantiate;
+use sml_syntax::ast::{self, AstNode as _, SyntaxNodePtr};
+use sml_syntax::rowan::NodeOrToken;
+use sml_syntax::{SyntaxNode, SyntaxToken};
+use text_pos::{PositionUtf16, RangeUtf16};
+
+/// Returns edits to change the undefined names at the position to similar defined names.
+pub(crate) fn similar(
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Vec<(RangeUtf16, String)> {
+  get(source_files, pos, |_, _, err| {
+    let (name, suggestions) = err.undefined_suggestions()?;
+    Some((name.as_str(), suggestions.into_iter().map(ToOwned::to_owned).collect()))
+  })
+}
+
+/// How many of the shallowest qualified names to check the types of. Each check copies all the
+/// types in the analysis.
+const MAX_TY_CHECKED: usize = 5;
+
+/// Returns edits to qualify the undefined names at the position with structures that define them,
+/// shallowest first. For a function applied to an argument, of the shallowest few, ones whose types
+/// fit the argument come first.
+pub(crate) fn qualified(
+  st: &sml_statics_types::St,
+  source_files: &PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+) -> Vec<(RangeUtf16, String)> {
+  get(source_files, pos, |file, node, err| {
+    let qualified = err.undefined_qualified()?;
+    let name = qualified.first()?.0.last().as_str();
+    let arg_ty_scheme = ast::PathExp::cast(node.clone())
+      .and_then(|path_exp| {
+        let app = ast::AppExp::cast(path_exp.syntax().parent()?)?;
+        if app.func()?.syntax() != path_exp.syntax() {
+          return None;
+        }
+        let arg = app.arg()?;
+        file.syntax.lower.ptrs.ast_to_hir(&SyntaxNodePtr::new(arg.syntax()))
+      })
+      .and_then(|idx| file.info.get_ty_scheme(idx));
+    let mut qualified: Vec<_> = qualified.iter().collect();
+    if let Some(arg_ty_scheme) = arg_ty_scheme {
+      let n = qualified.len().min(MAX_TY_CHECKED);
+      qualified[..n].sort_by_cached_key(|(_, ty_scheme)| {
+        !ty_scheme.as_ref().map_or(false, |x| fits(st, x, &arg_ty_scheme))
+      });
+    }
+    Some((name, qualified.into_iter().map(|(path, _)| path.to_string()).collect()))
+  })
+}
+
+/// Returns whether a function of the type scheme could be applied to an argument of the other.
+fn fits(st: &sml_statics_types::St, func_ty_scheme: &TyScheme, arg_ty_scheme: &TyScheme) -> bool {
+  // check on a copy, so as to not add constraints to the types in the analysis.
+  let mut tys = st.tys.clone();
+  let func_ty = instantiate(&mut tys, Generalizable::Always, func_ty_scheme);
+  let arg_ty = instantiate(&mut tys, Generalizable::Always, arg_ty_scheme);
+  let res_ty = tys.meta_var(Generalizable::Always);
+  let want = tys.fun(arg_ty, res_ty);
+  sml_statics_types::unify::unify(&mut tys, &st.syms, want, func_ty).is_ok()
+}
+
+/// Returns edits to replace the undefined names at the position, for each error that `f` returns
+/// the name and the replacements for, given the file and the node for the error.
+fn get<'a, F>(
+  source_files: &'a PathMap<mlb_statics::SourceFile>,
+  pos: WithPath<PositionUtf16>,
+  f: F,
+) -> Vec<(RangeUtf16, String)>
+where
+  F: Fn(
+    &'a mlb_statics::SourceFile,
+    &SyntaxNode,
+    &'a sml_statics::Error,
+  ) -> Option<(&'a str, Vec<String>)>,
+{
+  let Some(file) = source_files.get(&pos.path) else { return Vec::new() };
+  let Some(offset) = file.syntax.pos_db.text_size_utf16(pos.val) else { return Vec::new() };
+  let mut ret = Vec::<(RangeUtf16, String)>::new();
+  for (_, node, err) in source_files::statics_errors_at(file, offset) {
+    let Some((name, replacements)) = f(file, &node, err) else { continue };
+    let Some(token) = name_token(&node, name) else { continue };
+    let Some(range) = file.syntax.pos_db.range_utf16(token.text_range()) else { continue };
+    ret.extend(replacements.into_iter().map(|new_text| (range, new_text)));
+  }
+  ret
+}
+
+/// Returns the token for the undefined name in the node for the error.
+///
+/// This is usually the last part of the node's path, but it may be a structure before that, like
+/// `A` in `A.x`. Only the node's own path counts, not the paths in the nodes in it, like the type
+/// argument in `foo foo`.
+fn name_token(node: &SyntaxNode, name: &str) -> Option<SyntaxToken> {
+  let tokens: Vec<_> = match node.children().find_map(ast::Path::cast) {
+    Some(path) => path.name_star_eq_dots().filter_map(|x| Some(x.name_star_eq()?.token)).collect(),
+    // the name, `*`, or `=` is directly in the node, like for an infix application.
+    None => node.children_with_tokens().filter_map(NodeOrToken::into_token).collect(),
+  };
+  let (last, rest) = tokens.split_last()?;
+  if last.text() == name {
+    return Some(last.clone());
+  }
+  rest.iter().find(|tok| tok.text() == name).cloned()
+}
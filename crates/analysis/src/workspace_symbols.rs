@@ -0,0 +1,80 @@
+//! Searching for symbols across all files.
+
+use crate::{DocumentSymbol, WorkspaceSymbol};
+use paths::{PathId, PathMap};
+
+/// The most symbols to return. Clients usually show only the first few anyway, and will search again
+/// as the query gets longer.
+const MAX: usize = 128;
+
+/// Returns the symbols matching the query in the source files of all the analyses, best first, at
+/// most [`MAX`] of them.
+pub(crate) fn get<'a, I>(analyses: I, query: &str) -> Vec<WorkspaceSymbol>
+where
+  I: IntoIterator<Item = (&'a sml_statics_types::St, &'a PathMap<mlb_statics::SourceFile>)>,
+{
+  let query = query.to_lowercase();
+  let mut ret = Vec::<(Score, WorkspaceSymbol)>::new();
+  for (st, source_files) in analyses {
+    for (&path, file) in source_files {
+      for sym in file.info.document_symbols(st, path) {
+        if let Some(sym) = crate::symbol(&file.syntax, sym) {
+          go(&mut ret, &query, path, None, sym);
+        }
+      }
+    }
+  }
+  ret.sort_by(|(a, x), (b, y)| a.cmp(b).then_with(|| x.name.cmp(&y.name)));
+  ret.truncate(MAX);
+  ret.into_iter().map(|(_, x)| x).collect()
+}
+
+fn go(
+  ac: &mut Vec<(Score, WorkspaceSymbol)>,
+  query: &str,
+  path: PathId,
+  container: Option<&str>,
+  sym: DocumentSymbol,
+) {
+  let qualified = match container {
+    Some(container) => format!("{container}.{}", sym.name),
+    None => sym.name.clone(),
+  };
+  // only match on the qualified name if the query looks like it wants that.
+  let haystack = if query.contains('.') { qualified.as_str() } else { sym.name.as_str() };
+  if let Some(score) = score(query, haystack) {
+    ac.push((
+      score,
+      WorkspaceSymbol {
+        name: sym.name,
+        kind: sym.kind,
+        container_name: container.map(ToOwned::to_owned),
+        range: path.wrap(sym.range),
+      },
+    ));
+  }
+  for child in sym.children {
+    go(ac, query, path, Some(qualified.as_str()), child);
+  }
+}
+
+/// Lower is better. In order: whether the match did not start at the beginning, how many
+/// characters were skipped between matched characters, and the length of the candidate.
+type Score = (bool, usize, usize);
+
+/// Returns the score if all the chars of the lowercase `query` appear in order in `candidate`.
+fn score(query: &str, candidate: &str) -> Option<Score> {
+  let candidate: Vec<_> = candidate.to_lowercase().chars().collect();
+  let mut start = 0usize;
+  let mut first = None::<usize>;
+  let mut gaps = 0usize;
+  for q in query.chars() {
+    let found = start + candidate[start..].iter().position(|&c| c == q)?;
+    match first {
+      None => first = Some(found),
+      Some(_) => gaps += found - start,
+    }
+    start = found + 1;
+  }
+  Some((first.map_or(false, |x| x != 0), gaps, candidate.len()))
+}
@@ -15,6 +15,34 @@ pub use types::{
   Class, CmFile, Error, Export, Namespace, PathKind, PathOrMinus, PathOrStdBasis, Result,
 };
 
+use text_size_util::TextRange;
+use types::Token;
+
+/// The keywords of CM files.
+pub const KEYWORDS: [&str; 10] = [
+  "Group",
+  "Library",
+  "functor",
+  "funsig",
+  "group",
+  "is",
+  "library",
+  "signature",
+  "source",
+  "structure",
+];
+
+/// Returns the kind of the path, or `None` if CM files may not contain it without an explicit
+/// class.
+#[must_use]
+pub fn path_kind(path: &std::path::Path) -> Option<PathKind> {
+  match Class::from_path(path)? {
+    Class::Sml => Some(PathKind::Sml),
+    Class::Cm => Some(PathKind::Cm),
+    Class::Other(_) => None,
+  }
+}
+
 /// Turn the contents of a CM file into exports and members.
 ///
 /// # Errors
@@ -26,3 +54,23 @@ pub fn get(s: &str, env: &slash_var_path::Env) -> Result<CmFile> {
   let file = lower::get(root)?;
   Ok(file)
 }
+
+/// Returns the range of the members of a CM file, from the `is` keyword to the end of the last
+/// member, if there are any members.
+///
+/// # Errors
+///
+/// If the contents of the file could not be lexed.
+pub fn fold_ranges(s: &str) -> Result<Vec<TextRange>> {
+  let tokens = lex::get(s)?;
+  let Some(is) = tokens.iter().position(|x| matches!(x.val, Token::Is)) else {
+    return Ok(Vec::new());
+  };
+  let ret = match tokens.last() {
+    Some(last) if is + 1 < tokens.len() => {
+      vec![TextRange::new(tokens[is].range.start(), last.range.end())]
+    }
+    _ => Vec::new(),
+  };
+  Ok(ret)
+}
@@ -215,7 +215,7 @@ pub enum Class {
 }
 
 impl Class {
-  fn from_path(path: &Path) -> Option<Self> {
+  pub(crate) fn from_path(path: &Path) -> Option<Self> {
     let ret = match path.extension()?.to_str()? {
       "sig" | "sml" | "fun" => Self::Sml,
       "cm" => Self::Cm,
@@ -50,3 +50,81 @@ pub enum FormatEngine {
   Naive,
   Smlfmt,
 }
+
+/// The client settings, which the client sends when they change, and when asked for the
+/// configuration.
+///
+/// These are nested like the `millet.*` settings of the VS Code extension, e.g.
+/// `millet.server.hover.token.enable` is `server.hover.token.enable` here.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+  #[serde(default)]
+  pub format: FormatSettings,
+  #[serde(default)]
+  pub server: ServerSettings,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FormatSettings {
+  #[serde(default)]
+  pub engine: FormatEngine,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSettings {
+  #[serde(default)]
+  pub diagnostics: DiagnosticsSettings,
+  #[serde(default)]
+  pub file_system_watcher: Enable,
+  #[serde(default)]
+  pub hover: HoverSettings,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsSettings {
+  #[serde(default)]
+  pub ignore: DiagnosticsIgnore,
+  #[serde(default)]
+  pub more_info_hint: Enable,
+  #[serde(default)]
+  pub on_change: EnableOff,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct HoverSettings {
+  #[serde(default)]
+  pub token: Enable,
+}
+
+/// A setting that is on by default.
+#[derive(Debug, Default, Deserialize)]
+pub struct Enable {
+  #[serde(default)]
+  pub enable: Tool,
+}
+
+/// A setting that is off by default.
+#[derive(Debug, Default, Deserialize)]
+pub struct EnableOff {
+  #[serde(default)]
+  pub enable: bool,
+}
+
+impl From<Settings> for Options {
+  fn from(settings: Settings) -> Self {
+    let server = settings.server;
+    Options {
+      token_hover: server.hover.token.enable,
+      fs_watcher: server.file_system_watcher.enable,
+      format: settings.format.engine,
+      diagnostics: DiagnosticsOptions {
+        on_change: server.diagnostics.on_change.enable,
+        more_info_hint: server.diagnostics.more_info_hint.enable,
+        ignore: server.diagnostics.ignore,
+      },
+    }
+  }
+}
@@ -29,6 +29,8 @@ pub struct Input {
   pub severities: types::Severities,
   /// The language config.
   pub lang: config::lang::Language,
+  /// The path variables from the config, not yet resolved relative to any group file.
+  pub path_vars: slash_var_path::UnresolvedEnv,
   /// Errors when getting input.
   pub errors: Vec<Error>,
 }
@@ -69,6 +71,7 @@ impl Input {
       f(fs, &mut ret.sources, &mut ret.groups, paths, &path_var_env, group.path, &mut ret.errors);
       ret.root_group_paths.push(group.path);
     }
+    ret.path_vars = root.config.path_vars;
     let bas_decs = ret.groups.iter().map(|(&a, b)| (a, &b.bas_dec));
     if let Err(err) = topo::check(bas_decs) {
       ret.errors.push(Error::new(
@@ -83,4 +86,31 @@ impl Input {
     }
     ret
   }
+
+  /// Adds a source file that is not in any group, as if it were the only file in its own group.
+  ///
+  /// The path of that group is the path of the source file.
+  pub fn add_standalone(&mut self, path: PathId, contents: String) {
+    self.sources.insert(path, contents);
+    let bas_dec = mlb_hir::BasDec::Path(path, mlb_hir::PathKind::Source);
+    self.groups.insert(
+      path,
+      types::Group { bas_dec, pos_db: text_pos::PositionDb::new(""), paths: Vec::new() },
+    );
+    self.root_group_paths.push(path);
+  }
+}
+
+/// Returns the directories in the folder that should each be analyzed as a separate root.
+///
+/// These are every directory under the folder containing a config file, and the folder itself if it
+/// directly contains a config file or group file, or if there were no other roots.
+///
+/// This does not look very deep, or in hidden directories or ones that usually hold build output or
+/// dependencies.
+pub fn roots<F>(fs: &F, folder: &paths::CanonicalPathBuf) -> Vec<paths::CanonicalPathBuf>
+where
+  F: paths::FileSystem,
+{
+  root::find(fs, folder)
 }
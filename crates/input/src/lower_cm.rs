@@ -45,7 +45,8 @@ pub(crate) fn get<F>(
       mlb_hir::BasDec::seq(path_decs).into(),
       mlb_hir::BasDec::seq(exports).into(),
     );
-    let group = Group { bas_dec, pos_db: cm_file.pos_db.expect("no pos db") };
+    let pos_db = cm_file.pos_db.expect("no pos db");
+    let group = Group { bas_dec, pos_db, paths: cm_file.paths };
     groups.insert(path, group);
   }
 }
@@ -68,6 +69,7 @@ struct CmFile {
   cm_paths: Vec<paths::PathId>,
   sml_paths: FxHashSet<paths::PathId>,
   exports: NameExports,
+  paths: Vec<WithRange<paths::PathId>>,
 }
 
 type NameExports = BTreeMap<NameExport, TextRange>;
@@ -124,6 +126,7 @@ fn get_one_cm_file<F>(
         continue;
       }
     };
+    ret.paths.push(WithRange { val: path_id, range: pp.range });
     match pp.val.kind() {
       cm_syntax::PathKind::Sml => {
         let contents = match read_file(st.fs, source, path.as_path()) {
@@ -288,7 +291,7 @@ where
 fn get_top_defs(contents: &str, ac: &mut NameExports, range: TextRange) {
   let mut fix_env = sml_fixity::STD_BASIS.clone();
   let (_, parse) = sml_file_syntax::SourceFileSyntax::lex_and_parse(&mut fix_env, contents);
-  get_top_defs_dec(ac, parse.root.decs(), range);
+  get_top_defs_dec(ac, parse.root().decs(), range);
 }
 
 fn get_top_defs_dec<I>(ac: &mut NameExports, iter: I, range: TextRange)
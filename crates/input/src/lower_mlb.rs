@@ -6,6 +6,7 @@ use crate::util::{
 };
 use fast_hash::FxHashSet;
 use paths::{PathId, PathMap};
+use text_size_util::WithRange;
 
 pub(crate) fn get<F>(
   fs: &F,
@@ -19,7 +20,7 @@ pub(crate) fn get<F>(
   F: paths::FileSystem,
 {
   let init = GroupPathToProcess { parent: path, range: None, path };
-  let mut st = St { fs, paths, sources, stack: vec![init], errors };
+  let mut st = St { fs, paths, sources, stack: vec![init], group_paths: Vec::new(), errors };
   while let Some(cur) = st.stack.pop() {
     if groups.contains_key(&cur.path) {
       continue;
@@ -45,7 +46,8 @@ pub(crate) fn get<F>(
     };
     let cx = Cx { group, path_id: cur.path };
     let bas_dec = get_bas_dec(&mut st, &cx, syntax_dec);
-    groups.insert(cur.path, Group { bas_dec, pos_db: cx.group.pos_db });
+    let paths = std::mem::take(&mut st.group_paths);
+    groups.insert(cur.path, Group { bas_dec, pos_db: cx.group.pos_db, paths });
   }
 }
 
@@ -54,6 +56,8 @@ struct St<'a, F> {
   paths: &'a mut paths::Store,
   sources: &'a mut PathMap<String>,
   stack: Vec<GroupPathToProcess>,
+  /// The paths in the group we're currently lowering.
+  group_paths: Vec<WithRange<PathId>>,
   errors: &'a mut Vec<Error>,
 }
 
@@ -120,6 +124,7 @@ where
           return mlb_hir::BasDec::seq(Vec::new());
         }
       };
+      st.group_paths.push(WithRange { val: path_id, range: pp.range });
       let kind = match pp.val.kind() {
         mlb_syntax::PathKind::Sml => {
           let contents = match read_file(st.fs, source, path.as_path()) {
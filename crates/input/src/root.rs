@@ -89,6 +89,67 @@ impl Root {
   }
 }
 
+/// How many directories deep under the folder to look for nested roots.
+const MAX_DEPTH: usize = 5;
+
+/// Directories that usually hold build output or dependencies, not roots.
+const SKIP_DIRS: [&str; 5] = ["_build", "build", "node_modules", "target", "vendor"];
+
+/// Returns the roots in the folder. See [`crate::roots`].
+pub(crate) fn find<F>(fs: &F, folder: &paths::CanonicalPathBuf) -> Vec<paths::CanonicalPathBuf>
+where
+  F: paths::FileSystem,
+{
+  let entries = fs.read_dir(folder.as_path()).unwrap_or_default();
+  let folder_is_root = entries
+    .iter()
+    .any(|entry| is_config(fs, entry) || GroupPathBuf::new(fs, entry.clone()).is_some());
+  let mut visited = FxHashSet::<PathBuf>::default();
+  visited.insert(folder.as_path().to_owned());
+  let mut nested = Vec::<paths::CanonicalPathBuf>::new();
+  let mut stack: Vec<_> =
+    entries.into_iter().filter(|x| is_dir_to_search(fs, x)).map(|x| (x, 1usize)).collect();
+  while let Some((dir, depth)) = stack.pop() {
+    // guard against symlink cycles.
+    let Ok(dir) = fs.canonicalize(dir.as_path()) else { continue };
+    if !visited.insert(dir.as_path().to_owned()) {
+      continue;
+    }
+    let Ok(entries) = fs.read_dir(dir.as_path()) else { continue };
+    if entries.iter().any(|entry| is_config(fs, entry)) {
+      nested.push(dir);
+    }
+    if depth < MAX_DEPTH {
+      let dirs = entries.into_iter().filter(|x| is_dir_to_search(fs, x));
+      stack.extend(dirs.map(|x| (x, depth + 1)));
+    }
+  }
+  nested.sort_unstable_by(|a, b| a.as_path().cmp(b.as_path()));
+  if folder_is_root || nested.is_empty() {
+    nested.insert(0, folder.clone());
+  }
+  nested
+}
+
+fn is_config<F>(fs: &F, path: &Path) -> bool
+where
+  F: paths::FileSystem,
+{
+  path.file_name().map_or(false, |x| x == config::file::PATH) && fs.is_file(path)
+}
+
+/// Skips hidden directories, like `.git`, and the ones in [`SKIP_DIRS`].
+fn is_dir_to_search<F>(fs: &F, path: &Path) -> bool
+where
+  F: paths::FileSystem,
+{
+  let skip = path
+    .file_name()
+    .and_then(|x| x.to_str())
+    .map_or(true, |x| x.starts_with('.') || SKIP_DIRS.contains(&x));
+  !skip && !fs.is_file(path)
+}
+
 #[derive(Debug)]
 pub(crate) struct RootGroup {
   pub(crate) path: PathId,
@@ -12,4 +12,6 @@ pub struct Group {
   pub bas_dec: mlb_hir::BasDec,
   /// A position DB for the group file that yielded the dec.
   pub pos_db: text_pos::PositionDb,
+  /// The paths in the group file that we could resolve, with their ranges in the group file.
+  pub paths: Vec<text_size_util::WithRange<paths::PathId>>,
 }
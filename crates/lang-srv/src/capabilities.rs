@@ -1,11 +1,13 @@
 //! See [`get`].
 
+use crate::semantic_tokens;
+
 /// Returns the capabilities of the server.
 pub(crate) fn get() -> lsp_types::ServerCapabilities {
   lsp_types::ServerCapabilities {
     text_document_sync: Some(lsp_types::TextDocumentSyncCapability::Options(
       lsp_types::TextDocumentSyncOptions {
-        open_close: Some(false),
+        open_close: Some(true),
         change: Some(lsp_types::TextDocumentSyncKind::INCREMENTAL),
         will_save: Some(false),
         will_save_wait_until: Some(false),
@@ -17,15 +19,60 @@ pub(crate) fn get() -> lsp_types::ServerCapabilities {
     hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
     definition_provider: Some(lsp_types::OneOf::Left(true)),
     type_definition_provider: Some(lsp_types::TypeDefinitionProviderCapability::Simple(true)),
+    implementation_provider: Some(lsp_types::ImplementationProviderCapability::Simple(true)),
     code_action_provider: Some(lsp_types::CodeActionProviderCapability::Simple(true)),
     document_formatting_provider: Some(lsp_types::OneOf::Left(true)),
     document_symbol_provider: Some(lsp_types::OneOf::Left(true)),
+    document_highlight_provider: Some(lsp_types::OneOf::Left(true)),
+    folding_range_provider: Some(lsp_types::FoldingRangeProviderCapability::Simple(true)),
+    document_link_provider: Some(lsp_types::DocumentLinkOptions {
+      resolve_provider: Some(false),
+      work_done_progress_options: lsp_types::WorkDoneProgressOptions::default(),
+    }),
+    selection_range_provider: Some(lsp_types::SelectionRangeProviderCapability::Simple(true)),
+    diagnostic_provider: Some(lsp_types::DiagnosticServerCapabilities::Options(
+      lsp_types::DiagnosticOptions {
+        identifier: Some("millet".to_owned()),
+        inter_file_dependencies: true,
+        workspace_diagnostics: true,
+        work_done_progress_options: lsp_types::WorkDoneProgressOptions::default(),
+      },
+    )),
+    call_hierarchy_provider: Some(lsp_types::CallHierarchyServerCapability::Simple(true)),
+    workspace_symbol_provider: Some(lsp_types::OneOf::Left(true)),
     references_provider: Some(lsp_types::OneOf::Left(true)),
     completion_provider: Some(lsp_types::CompletionOptions {
-      trigger_characters: Some(vec![".".to_owned()]),
+      trigger_characters: Some(vec![".".to_owned(), "/".to_owned(), "(".to_owned()]),
       ..lsp_types::CompletionOptions::default()
     }),
+    signature_help_provider: Some(lsp_types::SignatureHelpOptions {
+      trigger_characters: Some(vec!["(".to_owned(), ",".to_owned()]),
+      // curried arguments are separated by spaces, but a space is too common to start on.
+      retrigger_characters: Some(vec![" ".to_owned()]),
+      work_done_progress_options: lsp_types::WorkDoneProgressOptions::default(),
+    }),
     inlay_hint_provider: Some(lsp_types::OneOf::Left(true)),
+    rename_provider: Some(lsp_types::OneOf::Right(lsp_types::RenameOptions {
+      prepare_provider: Some(true),
+      work_done_progress_options: lsp_types::WorkDoneProgressOptions::default(),
+    })),
+    semantic_tokens_provider: Some(
+      lsp_types::SemanticTokensServerCapabilities::SemanticTokensOptions(
+        lsp_types::SemanticTokensOptions {
+          work_done_progress_options: lsp_types::WorkDoneProgressOptions::default(),
+          legend: semantic_tokens::legend(),
+          range: Some(true),
+          full: Some(lsp_types::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+        },
+      ),
+    ),
+    workspace: Some(lsp_types::WorkspaceServerCapabilities {
+      workspace_folders: Some(lsp_types::WorkspaceFoldersServerCapabilities {
+        supported: Some(true),
+        change_notifications: Some(lsp_types::OneOf::Left(true)),
+      }),
+      file_operations: None,
+    }),
     ..Default::default()
   }
 }
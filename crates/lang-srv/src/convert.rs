@@ -29,10 +29,41 @@ pub(crate) fn diagnostics(
 ) -> Vec<lsp_types::Diagnostic> {
   errors
     .into_iter()
-    .map(|err| diagnostic(err.message, Some(err.range), err.code, err.severity, more_info_hint))
+    .map(|err| {
+      let mut ret =
+        diagnostic(err.message, Some(err.range), err.code, err.severity, more_info_hint);
+      ret.data = err.fix.and_then(|fix| {
+        let edits = fix
+          .edits
+          .into_iter()
+          .map(|(range, new_text)| lsp_types::TextEdit { range: lsp_range(range), new_text })
+          .collect();
+        serde_json::to_value(FixData { title: fix.title, edits }).ok()
+      });
+      ret
+    })
     .collect()
 }
 
+/// The `data` of a diagnostic with a mechanical fix.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FixData {
+  title: String,
+  edits: Vec<lsp_types::TextEdit>,
+}
+
+/// Returns the quick fix for the diagnostic, if it has a mechanical fix.
+pub(crate) fn diagnostic_fix(
+  url: Url,
+  diagnostic: lsp_types::Diagnostic,
+) -> Option<lsp_types::CodeActionOrCommand> {
+  let data: FixData = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+  let mut ret = code_action(data.title, lsp_types::CodeActionKind::QUICKFIX, url, data.edits);
+  ret.diagnostics = Some(vec![diagnostic]);
+  ret.is_preferred = Some(true);
+  Some(lsp_types::CodeActionOrCommand::CodeAction(ret))
+}
+
 pub(crate) fn error_url(code: diagnostic::Code) -> Url {
   Url::parse(&format!("{}/{code}.md", analysis::URL)).expect("couldn't parse diagnostic URL")
 }
@@ -102,7 +133,26 @@ pub(crate) fn lsp_location(
   Some(lsp_types::Location { uri, range: lsp_range(range.val) })
 }
 
-fn analysis_position(pos: lsp_types::Position) -> text_pos::PositionUtf16 {
+pub(crate) fn document_link(
+  paths: &paths::Store,
+  link: analysis::DocumentLink,
+) -> Option<lsp_types::DocumentLink> {
+  let target = match file_url(paths.get_path(link.target).as_path()) {
+    Ok(x) => x,
+    Err(e) => {
+      log::error!("couldn't get path as a file url: {e:#}");
+      return None;
+    }
+  };
+  Some(lsp_types::DocumentLink {
+    range: lsp_range(link.range),
+    target: Some(target),
+    tooltip: None,
+    data: None,
+  })
+}
+
+pub(crate) fn analysis_position(pos: lsp_types::Position) -> text_pos::PositionUtf16 {
   text_pos::PositionUtf16 { line: pos.line, col: pos.character }
 }
 
@@ -134,6 +184,19 @@ where
   Ok(path.wrap(pos))
 }
 
+pub(crate) fn call_hierarchy_item_pos<F>(
+  fs: &F,
+  paths: &mut paths::Store,
+  item: &lsp_types::CallHierarchyItem,
+) -> Result<paths::WithPath<text_pos::PositionUtf16>>
+where
+  F: paths::FileSystem,
+{
+  let path = url_to_path_id(fs, paths, &item.uri)?;
+  let pos = analysis_position(item.selection_range.start);
+  Ok(path.wrap(pos))
+}
+
 pub(crate) fn registration<N, T>(options: T) -> lsp_types::Registration
 where
   N: lsp_types::notification::Notification,
@@ -152,9 +215,45 @@ pub(crate) fn quick_fix(
   range: text_pos::RangeUtf16,
   new_text: String,
 ) -> lsp_types::CodeActionOrCommand {
-  lsp_types::CodeActionOrCommand::CodeAction(lsp_types::CodeAction {
+  let edit = lsp_types::TextEdit { range: lsp_range(range), new_text };
+  let kind = lsp_types::CodeActionKind::QUICKFIX;
+  lsp_types::CodeActionOrCommand::CodeAction(code_action(title, kind, url, vec![edit]))
+}
+
+pub(crate) fn quick_fix_edits(
+  title: String,
+  url: Url,
+  edits: Vec<(text_pos::RangeUtf16, String)>,
+) -> lsp_types::CodeActionOrCommand {
+  let kind = lsp_types::CodeActionKind::QUICKFIX;
+  lsp_types::CodeActionOrCommand::CodeAction(code_action(title, kind, url, text_edits(edits)))
+}
+
+pub(crate) fn refactor(
+  title: String,
+  kind: lsp_types::CodeActionKind,
+  url: Url,
+  edits: Vec<(text_pos::RangeUtf16, String)>,
+) -> lsp_types::CodeActionOrCommand {
+  lsp_types::CodeActionOrCommand::CodeAction(code_action(title, kind, url, text_edits(edits)))
+}
+
+fn text_edits(edits: Vec<(text_pos::RangeUtf16, String)>) -> Vec<lsp_types::TextEdit> {
+  edits
+    .into_iter()
+    .map(|(range, new_text)| lsp_types::TextEdit { range: lsp_range(range), new_text })
+    .collect()
+}
+
+fn code_action(
+  title: String,
+  kind: lsp_types::CodeActionKind,
+  url: Url,
+  edits: Vec<lsp_types::TextEdit>,
+) -> lsp_types::CodeAction {
+  lsp_types::CodeAction {
     title,
-    kind: Some(lsp_types::CodeActionKind::QUICKFIX),
+    kind: Some(kind),
     edit: Some(lsp_types::WorkspaceEdit {
       document_changes: Some(lsp_types::DocumentChanges::Edits(vec![
         lsp_types::TextDocumentEdit {
@@ -162,16 +261,51 @@ pub(crate) fn quick_fix(
             uri: url,
             version: None,
           },
-          edits: vec![lsp_types::OneOf::Left(lsp_types::TextEdit {
-            range: lsp_range(range),
-            new_text,
-          })],
+          edits: edits.into_iter().map(lsp_types::OneOf::Left).collect(),
         },
       ])),
       ..Default::default()
     }),
     ..Default::default()
-  })
+  }
+}
+
+pub(crate) fn workspace_edit(
+  paths: &paths::Store,
+  edits: paths::PathMap<Vec<(text_pos::RangeUtf16, String)>>,
+) -> lsp_types::WorkspaceEdit {
+  let changes = edits
+    .into_iter()
+    .filter_map(|(path, edits)| {
+      let url = match file_url(paths.get_path(path).as_path()) {
+        Ok(x) => x,
+        Err(e) => {
+          log::error!("couldn't get path as a file url: {e:#}");
+          return None;
+        }
+      };
+      let edits = edits
+        .into_iter()
+        .map(|(range, new_text)| lsp_types::TextEdit { range: lsp_range(range), new_text })
+        .collect();
+      Some((url, edits))
+    })
+    .collect();
+  lsp_types::WorkspaceEdit { changes: Some(changes), ..Default::default() }
+}
+
+fn symbol_kind(kind: sml_namespace::SymbolKind) -> lsp_types::SymbolKind {
+  match kind {
+    sml_namespace::SymbolKind::Signature => lsp_types::SymbolKind::INTERFACE,
+    sml_namespace::SymbolKind::Structure => lsp_types::SymbolKind::MODULE,
+    sml_namespace::SymbolKind::Functor | sml_namespace::SymbolKind::Function => {
+      lsp_types::SymbolKind::FUNCTION
+    }
+    sml_namespace::SymbolKind::Value => lsp_types::SymbolKind::VARIABLE,
+    sml_namespace::SymbolKind::Type => lsp_types::SymbolKind::CLASS,
+    sml_namespace::SymbolKind::Constructor => lsp_types::SymbolKind::CONSTRUCTOR,
+    sml_namespace::SymbolKind::Exception => lsp_types::SymbolKind::EVENT,
+  }
 }
 
 pub(crate) fn document_symbol(sym: analysis::DocumentSymbol) -> lsp_types::DocumentSymbol {
@@ -179,17 +313,7 @@ pub(crate) fn document_symbol(sym: analysis::DocumentSymbol) -> lsp_types::Docum
   lsp_types::DocumentSymbol {
     name: sym.name,
     detail: sym.detail,
-    kind: match sym.kind {
-      sml_namespace::SymbolKind::Signature => lsp_types::SymbolKind::INTERFACE,
-      sml_namespace::SymbolKind::Structure => lsp_types::SymbolKind::MODULE,
-      sml_namespace::SymbolKind::Functor | sml_namespace::SymbolKind::Function => {
-        lsp_types::SymbolKind::FUNCTION
-      }
-      sml_namespace::SymbolKind::Value => lsp_types::SymbolKind::VARIABLE,
-      sml_namespace::SymbolKind::Type => lsp_types::SymbolKind::CLASS,
-      sml_namespace::SymbolKind::Constructor => lsp_types::SymbolKind::CONSTRUCTOR,
-      sml_namespace::SymbolKind::Exception => lsp_types::SymbolKind::EVENT,
-    },
+    kind: symbol_kind(sym.kind),
     tags: None,
     deprecated: None,
     range: lsp_range(sym.range),
@@ -198,6 +322,95 @@ pub(crate) fn document_symbol(sym: analysis::DocumentSymbol) -> lsp_types::Docum
   }
 }
 
+pub(crate) fn call_hierarchy_item(
+  paths: &paths::Store,
+  item: analysis::CallHierarchyItem,
+) -> Option<lsp_types::CallHierarchyItem> {
+  let location = lsp_location(paths, item.range)?;
+  Some(lsp_types::CallHierarchyItem {
+    name: item.name,
+    kind: symbol_kind(item.kind),
+    tags: None,
+    detail: item.detail,
+    uri: location.uri,
+    range: location.range,
+    selection_range: lsp_range(item.selection_range),
+    data: None,
+  })
+}
+
+pub(crate) fn folding_range(range: analysis::FoldingRange) -> lsp_types::FoldingRange {
+  lsp_types::FoldingRange {
+    start_line: range.range.start.line,
+    start_character: None,
+    end_line: range.range.end.line,
+    end_character: None,
+    kind: Some(match range.kind {
+      analysis::FoldingRangeKind::Comment => lsp_types::FoldingRangeKind::Comment,
+      analysis::FoldingRangeKind::Region => lsp_types::FoldingRangeKind::Region,
+    }),
+  }
+}
+
+/// The ranges should go from innermost to outermost.
+pub(crate) fn selection_range(
+  ranges: Vec<text_pos::RangeUtf16>,
+) -> Option<lsp_types::SelectionRange> {
+  ranges.into_iter().rev().fold(None, |parent, range| {
+    Some(lsp_types::SelectionRange { range: lsp_range(range), parent: parent.map(Box::new) })
+  })
+}
+
+pub(crate) fn document_highlight(h: analysis::DocumentHighlight) -> lsp_types::DocumentHighlight {
+  lsp_types::DocumentHighlight {
+    range: lsp_range(h.range),
+    kind: Some(match h.kind {
+      analysis::DocumentHighlightKind::Read => lsp_types::DocumentHighlightKind::READ,
+      analysis::DocumentHighlightKind::Write => lsp_types::DocumentHighlightKind::WRITE,
+    }),
+  }
+}
+
+pub(crate) fn workspace_symbol(
+  paths: &paths::Store,
+  sym: analysis::WorkspaceSymbol,
+) -> Option<lsp_types::WorkspaceSymbol> {
+  Some(lsp_types::WorkspaceSymbol {
+    name: sym.name,
+    kind: symbol_kind(sym.kind),
+    tags: None,
+    container_name: sym.container_name,
+    location: lsp_types::OneOf::Left(lsp_location(paths, sym.range)?),
+    data: None,
+  })
+}
+
+pub(crate) fn signature_help(help: analysis::SignatureHelp) -> lsp_types::SignatureHelp {
+  let parameters = help
+    .params
+    .into_iter()
+    .map(|offsets| lsp_types::ParameterInformation {
+      label: lsp_types::ParameterLabel::LabelOffsets(offsets),
+      documentation: None,
+    })
+    .collect();
+  lsp_types::SignatureHelp {
+    signatures: vec![lsp_types::SignatureInformation {
+      label: help.label,
+      documentation: help.documentation.map(|value| {
+        lsp_types::Documentation::MarkupContent(lsp_types::MarkupContent {
+          kind: lsp_types::MarkupKind::Markdown,
+          value,
+        })
+      }),
+      parameters: Some(parameters),
+      active_parameter: help.active_param,
+    }],
+    active_signature: Some(0),
+    active_parameter: help.active_param,
+  }
+}
+
 pub(crate) fn completion_item(item: analysis::CompletionItem) -> lsp_types::CompletionItem {
   lsp_types::CompletionItem {
     label: item.label,
@@ -236,6 +449,26 @@ pub(crate) fn completion_item(item: analysis::CompletionItem) -> lsp_types::Comp
   }
 }
 
+pub(crate) fn group_completion_item(
+  item: analysis::GroupCompletionItem,
+) -> lsp_types::CompletionItem {
+  let kind = match item.kind {
+    analysis::GroupCompletionKind::Keyword => lsp_types::CompletionItemKind::KEYWORD,
+    analysis::GroupCompletionKind::PathVar => lsp_types::CompletionItemKind::VARIABLE,
+    analysis::GroupCompletionKind::File => lsp_types::CompletionItemKind::FILE,
+    analysis::GroupCompletionKind::Folder => lsp_types::CompletionItemKind::FOLDER,
+  };
+  let text_edit =
+    lsp_types::TextEdit { range: lsp_range(item.range), new_text: item.label.clone() };
+  lsp_types::CompletionItem {
+    label: item.label,
+    kind: Some(kind),
+    detail: item.detail,
+    text_edit: Some(lsp_types::CompletionTextEdit::Edit(text_edit)),
+    ..lsp_types::CompletionItem::default()
+  }
+}
+
 pub(crate) fn inlay_hint(hint: analysis::InlayHint) -> lsp_types::InlayHint {
   lsp_types::InlayHint {
     position: lsp_position(hint.position),
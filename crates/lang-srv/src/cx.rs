@@ -1,12 +1,18 @@
 //! See [`Cx`].
 
+use crate::progress::{self, Progress};
+use crate::shared::Shared;
 use crossbeam_channel::Sender;
 use diagnostic::Code;
-use lsp_server::{Message, Notification, ReqQueue, Response};
+use lsp_server::{Message, Notification, ReqQueue, RequestId, Response};
 use lsp_types::Url;
+use std::sync::Arc;
 
 pub(crate) const LEARN_MORE: &str = "Learn more";
 
+/// The section of the client configuration with our options.
+pub(crate) const CONFIGURATION_SECTION: &str = "millet";
+
 /// The context, kind of like "semi-permanent" state.
 ///
 /// Some things on this are totally immutable after initialization. Other things are mutable, but
@@ -14,10 +20,29 @@ pub(crate) const LEARN_MORE: &str = "Learn more";
 /// mutate, it'll only be "tweaked" a bit.
 pub(crate) struct Cx {
   pub(crate) options: config::init::Options,
+  pub(crate) std_basis: analysis::StdBasis,
+  pub(crate) analysis_options: analysis::Options,
+  /// Whether we should register for changes to files.
+  pub(crate) watch_files: bool,
   pub(crate) registered_for_watched_files: bool,
+  /// Whether the client pulls diagnostics, so we should not push them.
+  pub(crate) pull_diagnostics: bool,
+  /// Whether the client supports being asked to pull diagnostics again.
+  pub(crate) diagnostic_refresh: bool,
+  /// Whether the client supports progress we start reporting.
+  pub(crate) work_done_progress: bool,
+  /// How many progress tokens we have made, so we can make unique ones.
+  pub(crate) progress_tokens: u32,
+  /// Whether the client supports being asked for the configuration.
+  pub(crate) configuration: bool,
+  /// The id of the request we sent asking for the configuration, if we have yet to get a response.
+  pub(crate) configuration_request: Option<RequestId>,
   pub(crate) paths: paths::Store,
   pub(crate) fs: paths::RealFileSystem,
+  /// The contents of the open documents, which may not be saved to disk.
+  pub(crate) open_files: paths::PathMap<String>,
   pub(crate) sender: Sender<Message>,
+  pub(crate) shared: Arc<Shared>,
   pub(crate) req_queue: ReqQueue<(), Option<Code>>,
 }
 
@@ -27,12 +52,14 @@ impl Cx {
     self.sender.send(msg).unwrap();
   }
 
-  pub(crate) fn send_request<R>(&mut self, params: R::Params, data: Option<Code>)
+  pub(crate) fn send_request<R>(&mut self, params: R::Params, data: Option<Code>) -> RequestId
   where
     R: lsp_types::request::Request,
   {
     let req = self.req_queue.outgoing.register(R::METHOD.to_owned(), params, data);
+    let ret = req.id.clone();
     self.send(req.into());
+    ret
   }
 
   pub(crate) fn send_response(&mut self, res: Response) {
@@ -70,7 +97,54 @@ impl Cx {
     );
   }
 
+  /// Asks the client for the configuration. The response is handled in [`crate::response`].
+  pub(crate) fn request_configuration(&mut self) {
+    let id = self.send_request::<lsp_types::request::WorkspaceConfiguration>(
+      lsp_types::ConfigurationParams {
+        items: vec![lsp_types::ConfigurationItem {
+          scope_uri: None,
+          section: Some(CONFIGURATION_SECTION.to_owned()),
+        }],
+      },
+      None,
+    );
+    self.configuration_request = Some(id);
+  }
+
+  pub(crate) fn new_analysis(&self) -> analysis::Analysis {
+    analysis::Analysis::new(self.std_basis.clone(), self.analysis_options)
+  }
+
   pub(crate) fn get_input(&mut self, root: &paths::CanonicalPathBuf) -> input::Input {
-    elapsed::log("Input::new", || input::Input::new(&self.fs, &mut self.paths, root))
+    let progress = Progress::begin(self, "Loading input");
+    progress.report(self, root.as_path().display().to_string(), None);
+    let mut ret = elapsed::log("Input::new", || input::Input::new(&self.fs, &mut self.paths, root));
+    for &path in self.open_files.keys() {
+      self.add_open_file(&mut ret, path);
+    }
+    progress.report(self, progress::input_message(&ret), None);
+    progress.end(self);
+    ret
+  }
+
+  /// Makes the contents of the open file take precedence over what is in the input, if the input
+  /// has the file. Returns whether the input changed.
+  pub(crate) fn add_open_file(&self, input: &mut input::Input, path: paths::PathId) -> bool {
+    let Some(contents) = self.open_files.get(&path) else { return false };
+    let Some(old) = input.sources.get_mut(&path) else { return false };
+    if old == contents {
+      return false;
+    }
+    old.clone_from(contents);
+    true
+  }
+
+  pub(crate) fn is_sml(&self, path: paths::PathId) -> bool {
+    self
+      .paths
+      .get_path(path)
+      .as_path()
+      .extension()
+      .map_or(false, |ext| ext.to_str().map_or(false, |ext| matches!(ext, "sml" | "sig" | "fun")))
   }
 }
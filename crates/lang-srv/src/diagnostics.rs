@@ -1,71 +1,103 @@
-//! Publish diagnostics.
+//! Publish diagnostics, or compute them to be pulled.
 
-use crate::convert;
-use crate::state::{Mode, St};
+use crate::progress::Progress;
+use crate::state::St;
+use crate::{convert, cx::Cx, request, worker};
 use fast_hash::{FxHashMap, FxHashSet};
+use lsp_server::Request;
+use lsp_types::request::Request as _;
 use lsp_types::Url;
 use paths::FileSystem as _;
+use std::hash::{Hash as _, Hasher as _};
+use std::ops::ControlFlow;
 
-pub(crate) fn try_publish(st: &mut St) -> bool {
-  let root = match &mut st.mode {
-    Mode::Root(x) => x,
-    Mode::NoRoot => return false,
+/// Diagnostics for each file, including files with no diagnostics that are in the input.
+pub(crate) type Diagnostics = FxHashMap<Url, Vec<lsp_types::Diagnostic>>;
+
+/// Sends the worker a snapshot of the input to analyze. We publish the diagnostics when it finishes.
+pub(crate) fn publish(st: &mut St) {
+  // handling the newer edits will send a newer snapshot.
+  if st.is_behind() {
+    st.publish_pending = true;
+    return;
+  }
+  st.publish_pending = false;
+  if st.analyzing.is_none() {
+    st.analyzing = Some(Progress::begin(&mut st.cx, "Analyzing"));
+  }
+  let roots: Vec<_> = st.roots.iter().map(|root| (root.path.clone(), root.input.clone())).collect();
+  let standalone = st.standalone_input();
+  st.worker.send(st.edits, st.cx.analysis_options, roots, standalone);
+}
+
+/// Handles a report of progress from the worker.
+pub(crate) fn report(st: &St, message: String, percentage: Option<u32>) {
+  if let Some(progress) = &st.analyzing {
+    progress.report(&st.cx, message, percentage);
+  }
+}
+
+/// Handles the worker finishing the job with the id.
+///
+/// Requests get the new analyses even if newer edits made them stale, since they are still newer
+/// than the ones we had, unless we updated a file in place since the worker got its snapshot. But
+/// we only publish diagnostics that are up to date.
+pub(crate) fn finish(st: &mut St, id: usize, done: Option<worker::Done>) {
+  let newest = id == st.worker.newest;
+  if newest {
+    if let Some(progress) = st.analyzing.take() {
+      progress.end(&st.cx);
+    }
+  }
+  let Some(done) = done else {
+    if newest {
+      st.publish_pending = true;
+    }
+    return;
   };
-  let mut input_diagnostics = FxHashMap::<Url, Vec<lsp_types::Diagnostic>>::default();
-  for err in &root.input.errors {
-    let did_send_as_diagnostic = if st.cx.fs.is_file(err.abs_path()) {
-      match convert::file_url(err.abs_path()) {
-        Ok(url) => {
-          let d = convert::diagnostic(
-            err.display(root.path.as_path()).to_string(),
-            err.range(),
-            err.code(),
-            err.severity(),
-            st.cx.options.diagnostics.more_info_hint.0,
-          );
-          input_diagnostics.entry(url).or_default().push(d);
-          true
-        }
-        Err(e) => {
-          log::error!("couldn't get path as a file url: {e:#}");
-          false
-        }
-      }
-    } else {
-      false
+  let is_current = done.edits == st.edits && !st.is_behind();
+  let mut got = Vec::with_capacity(done.roots.len());
+  for (path, analyzed) in done.roots {
+    // the roots may have changed since the worker got the snapshot.
+    let Some(idx) = st.roots.iter().position(|root| root.path.as_path() == path.as_path()) else {
+      continue;
     };
-    if !did_send_as_diagnostic {
-      st.cx.show_error(
-        format!(
-          "{}: {}",
-          err.maybe_rel_path(root.path.as_path()).display(),
-          err.display(root.path.as_path())
-        ),
-        err.code(),
-      );
+    let root = &mut st.roots[idx];
+    // the analysis may have a file that is newer than the snapshot.
+    if root.updated > done.edits {
+      continue;
+    }
+    root.analysis = analyzed.analysis;
+    got.push((idx, analyzed.diagnostics));
+  }
+  if st.standalone_updated <= done.edits {
+    st.standalone = done.standalone.analysis;
+  }
+  if !is_current {
+    log::info!("dropping stale diagnostics");
+    if newest {
+      st.publish_pending = true;
+    }
+    return;
+  }
+  let diagnostics = get(st, got, done.standalone.diagnostics);
+  if st.cx.pull_diagnostics {
+    st.diagnostics = Some(diagnostics);
+    for req in std::mem::take(&mut st.pulls) {
+      request::handle(st, req);
     }
+    if st.cx.diagnostic_refresh {
+      st.cx.send_request::<lsp_types::request::WorkspaceDiagnosticRefresh>((), None);
+    }
+    return;
   }
   let mut has_diagnostics = FxHashSet::<Url>::default();
-  for (url, ds) in input_diagnostics {
-    has_diagnostics.insert(url.clone());
-    st.cx.send_diagnostics(url, ds);
-  }
-  let got_many = st.analysis.get_many(&root.input);
-  for (path_id, errors) in got_many {
-    let path = st.cx.paths.get_path(path_id);
-    let url = match convert::file_url(path.as_path()) {
-      Ok(x) => x,
-      Err(e) => {
-        log::error!("couldn't get path as a file url: {e:#}");
-        continue;
-      }
-    };
-    let ds = convert::diagnostics(errors, st.cx.options.diagnostics.more_info_hint.0);
+  for (url, ds) in &diagnostics {
     if ds.is_empty() {
       continue;
     }
     has_diagnostics.insert(url.clone());
-    st.cx.send_diagnostics(url, ds);
+    st.cx.send_diagnostics(url.clone(), ds.clone());
   }
   // iter over the old list of urls with diagnostics.
   for url in std::mem::take(&mut st.has_diagnostics) {
@@ -77,5 +109,105 @@ pub(crate) fn try_publish(st: &mut St) -> bool {
     st.cx.send_diagnostics(url, Vec::new());
   }
   st.has_diagnostics = has_diagnostics;
-  true
+  st.diagnostics = Some(diagnostics);
+}
+
+/// Defers the request if it is to pull diagnostics that are out of date. We answer it when the
+/// worker finishes analyzing.
+pub(crate) fn defer_pull(st: &mut St, req: Request) -> ControlFlow<(), Request> {
+  let is_pull = [
+    lsp_types::request::DocumentDiagnosticRequest::METHOD,
+    lsp_types::request::WorkspaceDiagnosticRequest::METHOD,
+  ]
+  .contains(&req.method.as_str());
+  if !is_pull || st.diagnostics.is_some() {
+    return ControlFlow::Continue(req);
+  }
+  st.pulls.push(req);
+  if st.analyzing.is_none() {
+    publish(st);
+  }
+  ControlFlow::Break(())
+}
+
+/// Returns an id that changes when the diagnostics change.
+pub(crate) fn result_id(ds: &[lsp_types::Diagnostic]) -> String {
+  let mut hasher = std::collections::hash_map::DefaultHasher::new();
+  match serde_json::to_string(ds) {
+    Ok(s) => s.hash(&mut hasher),
+    Err(e) => log::error!("couldn't serialize diagnostics: {e}"),
+  }
+  format!("{:x}", hasher.finish())
+}
+
+type Got = paths::PathMap<Vec<analysis::Diagnostic<text_pos::RangeUtf16>>>;
+
+fn get(st: &mut St, got: Vec<(usize, Got)>, standalone: Got) -> Diagnostics {
+  let mut ret = Diagnostics::default();
+  for root in &st.roots {
+    for err in &root.input.errors {
+      let did_send_as_diagnostic = if st.cx.fs.is_file(err.abs_path()) {
+        match convert::file_url(err.abs_path()) {
+          Ok(url) => {
+            let d = convert::diagnostic(
+              err.display(root.path.as_path()).to_string(),
+              err.range(),
+              err.code(),
+              err.severity(),
+              st.cx.options.diagnostics.more_info_hint.0,
+            );
+            ret.entry(url).or_default().push(d);
+            true
+          }
+          Err(e) => {
+            log::error!("couldn't get path as a file url: {e:#}");
+            false
+          }
+        }
+      } else {
+        false
+      };
+      if !did_send_as_diagnostic {
+        st.cx.show_error(
+          format!(
+            "{}: {}",
+            err.maybe_rel_path(root.path.as_path()).display(),
+            err.display(root.path.as_path())
+          ),
+          err.code(),
+        );
+      }
+    }
+  }
+  for (idx, got_many) in got {
+    for (path_id, errors) in got_many {
+      // a source in more than one root only gets diagnostics from the root that owns it.
+      if st.owner(path_id) == Some(idx) {
+        add(&st.cx, &mut ret, path_id, errors);
+      }
+    }
+  }
+  // each open file in no root was analyzed by itself.
+  for (path_id, errors) in standalone {
+    add(&st.cx, &mut ret, path_id, errors);
+  }
+  ret
+}
+
+fn add(
+  cx: &Cx,
+  ret: &mut Diagnostics,
+  path_id: paths::PathId,
+  errors: Vec<analysis::Diagnostic<text_pos::RangeUtf16>>,
+) {
+  let path = cx.paths.get_path(path_id);
+  let url = match convert::file_url(path.as_path()) {
+    Ok(x) => x,
+    Err(e) => {
+      log::error!("couldn't get path as a file url: {e:#}");
+      return;
+    }
+  };
+  let ds = convert::diagnostics(errors, cx.options.diagnostics.more_info_hint.0);
+  ret.entry(url).or_default().extend(ds);
 }
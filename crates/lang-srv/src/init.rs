@@ -1,13 +1,21 @@
 //! Initialize a new server.
 
-use crate::state::{Mode, Root, St};
-use crate::{convert, cx::Cx, diagnostics};
+use crate::progress::Progress;
+use crate::shared::Shared;
+use crate::state::St;
+use crate::{convert, cx::Cx, diagnostics, semantic_tokens, worker::Worker};
 use crossbeam_channel::Sender;
 use diagnostic::Code;
 use fast_hash::FxHashSet;
 use lsp_server::{Message, ReqQueue};
+use lsp_types::notification::{DidChangeWatchedFiles, Notification as _};
+use std::sync::Arc;
 
-pub(crate) fn init(init: lsp_types::InitializeParams, sender: Sender<Message>) -> St {
+pub(crate) fn init(
+  init: lsp_types::InitializeParams,
+  sender: Sender<Message>,
+  shared: Arc<Shared>,
+) -> std::io::Result<St> {
   let options: config::init::Options = init
     .initialization_options
     .and_then(|v| match serde_json::from_value(v) {
@@ -18,74 +26,140 @@ pub(crate) fn init(init: lsp_types::InitializeParams, sender: Sender<Message>) -
       }
     })
     .unwrap_or_default();
-  let analysis_options = analysis::Options {
-    lines: config::DiagnosticLines::Many,
-    ignore: options.diagnostics.ignore,
-    format: options.format,
-  };
-  let analysis = analysis::Analysis::new(analysis::StdBasis::full(), analysis_options);
+  let analysis_options = analysis_options(&options);
+  let pull_diagnostics =
+    init.capabilities.text_document.as_ref().map_or(false, |x| x.diagnostic.is_some());
+  let diagnostic_refresh = init
+    .capabilities
+    .workspace
+    .as_ref()
+    .and_then(|x| x.diagnostic.as_ref()?.refresh_support)
+    .unwrap_or_default();
+  let configuration =
+    init.capabilities.workspace.as_ref().and_then(|x| x.configuration).unwrap_or_default();
+  let work_done_progress =
+    init.capabilities.window.as_ref().and_then(|x| x.work_done_progress).unwrap_or_default();
+  let watch_files = options.fs_watcher.0
+    && init
+      .capabilities
+      .workspace
+      .as_ref()
+      .and_then(|x| x.file_operations.as_ref()?.dynamic_registration)
+      .unwrap_or_default();
   let mut cx = Cx {
     options,
+    // replaced with the full std basis below, once we can report progress on loading it.
+    std_basis: analysis::StdBasis::minimal(),
+    analysis_options,
+    watch_files,
     registered_for_watched_files: false,
+    pull_diagnostics,
+    diagnostic_refresh,
+    work_done_progress,
+    progress_tokens: 0,
+    configuration,
+    configuration_request: None,
     paths: paths::Store::new(),
     fs: paths::RealFileSystem::default(),
+    open_files: paths::PathMap::default(),
     sender,
+    shared,
     req_queue: ReqQueue::default(),
   };
-  let mut root = init
-    .root_uri
-    .map(|url| convert::canonical_path_buf(&cx.fs, &url).map_err(|e| (e, url)))
-    .transpose();
-  let mut ret = St {
-    // do this convoluted incantation because we need `ret` to show the error in the `Err` case.
-    mode: match root.as_mut().ok().and_then(Option::take) {
-      Some(path) => {
-        let input = cx.get_input(&path);
-        Mode::Root(Box::new(Root { path, input }))
+  let progress = Progress::begin(&mut cx, "Loading std basis");
+  cx.std_basis = elapsed::log("StdBasis::full", analysis::StdBasis::full);
+  progress.end(&cx);
+  let worker = Worker::spawn(cx.std_basis.clone(), Arc::clone(&cx.shared))?;
+  let urls: Vec<_> = match init.workspace_folders {
+    Some(folders) => folders.into_iter().map(|x| x.uri).collect(),
+    None => init.root_uri.into_iter().collect(),
+  };
+  let mut folders = Vec::<paths::CanonicalPathBuf>::with_capacity(urls.len());
+  for url in urls {
+    match convert::canonical_path_buf(&cx.fs, &url) {
+      Ok(path) => folders.push(path),
+      Err(e) => {
+        cx.show_error(format!("cannot initialize workspace root {url}: {e:#}"), Code::n(1018))
       }
-      None => Mode::NoRoot,
-    },
+    }
+  }
+  let standalone = cx.new_analysis();
+  let mut ret = St {
+    folders,
+    roots: Vec::new(),
     cx,
-    analysis,
+    standalone,
+    standalone_updated: 0,
     has_diagnostics: FxHashSet::default(),
+    diagnostics: None,
+    edits: 0,
+    publish_pending: false,
+    worker,
+    analyzing: None,
+    pulls: Vec::new(),
+    semantic_tokens: semantic_tokens::Cache::default(),
   };
-  if let Err((e, url)) = root {
-    ret.cx.show_error(format!("cannot initialize workspace root {url}: {e:#}"), Code::n(1018));
-  }
-  let want_file_ops = ret.cx.options.fs_watcher.0
-    && init
-      .capabilities
-      .workspace
-      .and_then(|x| x.file_operations?.dynamic_registration)
-      .unwrap_or_default();
-  if want_file_ops {
-    if let Mode::Root(root) = &ret.mode {
-      // we'd like to only listen to millet.toml, not all toml, but "nested alternate groups are
-      // not allowed" at time of writing.
-      let watchers = vec![lsp_types::FileSystemWatcher {
-        glob_pattern: lsp_types::GlobPattern::Relative(lsp_types::RelativePattern {
-          base_uri: lsp_types::OneOf::Right(convert::file_url(root.path.as_path()).unwrap()),
-          pattern: "**/*.{sml,sig,fun,cm,mlb,toml}".to_owned(),
-        }),
-        kind: None,
-      }];
-      let did_changed_registration =
-        convert::registration::<lsp_types::notification::DidChangeWatchedFiles, _>(
-          lsp_types::DidChangeWatchedFilesRegistrationOptions { watchers },
-        );
-      ret.cx.send_request::<lsp_types::request::RegisterCapability>(
-        lsp_types::RegistrationParams { registrations: vec![did_changed_registration] },
-        None,
-      );
-      ret.cx.registered_for_watched_files = true;
-    };
-  }
-  diagnostics::try_publish(&mut ret);
+  ret.update_roots();
+  register_for_watched_files(&mut ret);
+  diagnostics::publish(&mut ret);
   if !ret.cx.registered_for_watched_files {
     log::warn!("millet will not necessarily receive notifications when files change on-disk.");
     log::warn!("this means the internal state of millet can get out of sync with what is");
     log::warn!("actually on disk, e.g. when using `git checkout` or other means of modifying");
     log::warn!("files not via the language client (i.e. the editor millet is attached to).");
   }
-  ret
+  Ok(ret)
+}
+
+pub(crate) fn analysis_options(options: &config::init::Options) -> analysis::Options {
+  analysis::Options {
+    lines: config::DiagnosticLines::Many,
+    ignore: options.diagnostics.ignore,
+    format: options.format,
+  }
+}
+
+/// Registers for changes to the files in the workspace folders, replacing any earlier registration.
+pub(crate) fn register_for_watched_files(st: &mut St) {
+  if !st.cx.watch_files {
+    return;
+  }
+  if st.cx.registered_for_watched_files {
+    st.cx.send_request::<lsp_types::request::UnregisterCapability>(
+      lsp_types::UnregistrationParams {
+        unregisterations: vec![lsp_types::Unregistration {
+          id: DidChangeWatchedFiles::METHOD.to_owned(),
+          method: DidChangeWatchedFiles::METHOD.to_owned(),
+        }],
+      },
+      None,
+    );
+    st.cx.registered_for_watched_files = false;
+  }
+  if st.folders.is_empty() {
+    return;
+  }
+  // we'd like to only listen to millet.toml, not all toml, but "nested alternate groups are not
+  // allowed" at time of writing.
+  let watchers: Vec<_> = st
+    .folders
+    .iter()
+    .filter_map(|folder| {
+      Some(lsp_types::FileSystemWatcher {
+        glob_pattern: lsp_types::GlobPattern::Relative(lsp_types::RelativePattern {
+          base_uri: lsp_types::OneOf::Right(convert::file_url(folder.as_path()).ok()?),
+          pattern: "**/*.{sml,sig,fun,cm,mlb,toml}".to_owned(),
+        }),
+        kind: None,
+      })
+    })
+    .collect();
+  let did_changed_registration = convert::registration::<DidChangeWatchedFiles, _>(
+    lsp_types::DidChangeWatchedFilesRegistrationOptions { watchers },
+  );
+  st.cx.send_request::<lsp_types::request::RegisterCapability>(
+    lsp_types::RegistrationParams { registrations: vec![did_changed_registration] },
+    None,
+  );
+  st.cx.registered_for_watched_files = true;
 }
@@ -10,32 +10,109 @@ mod diagnostics;
 mod helpers;
 mod init;
 mod notification;
+mod progress;
 mod request;
 mod response;
+mod semantic_tokens;
+mod shared;
 mod state;
+mod worker;
+
+use std::sync::Arc;
 
 fn run_inner(
   conn: &lsp_server::Connection,
   init: lsp_types::InitializeParams,
 ) -> anyhow::Result<()> {
   log::info!("start up main loop: {init:#?}");
-  let mut st = init::init(init, conn.sender.clone());
+  let shared = Arc::new(shared::Shared::default());
+  let (sender, receiver) = crossbeam_channel::unbounded::<lsp_server::Message>();
+  let handler = {
+    let shared = Arc::clone(&shared);
+    let conn_sender = conn.sender.clone();
+    std::thread::Builder::new()
+      .name("millet-handler".to_owned())
+      .spawn(move || handle_all(init, conn_sender, shared, &receiver))?
+  };
   for msg in &conn.receiver {
-    match msg {
+    match &msg {
       lsp_server::Message::Request(req) => {
-        if conn.handle_shutdown(&req)? {
+        if conn.handle_shutdown(req)? {
           log::info!("shut down main loop");
-          return Ok(());
+          break;
+        }
+        shared.start(req.id.clone());
+      }
+      lsp_server::Message::Response(_) => {}
+      lsp_server::Message::Notification(notif) => {
+        if let Some(id) = shared::cancel_id(notif) {
+          shared.cancel(&id);
+          continue;
+        }
+        if shared::is_edit(&notif.method) {
+          shared.add_edit();
+        }
+      }
+    }
+    if sender.send(msg).is_err() {
+      log::error!("the handler thread stopped");
+      break;
+    }
+  }
+  drop(sender);
+  handler.join().map_err(|_| anyhow::anyhow!("the handler thread panicked"))?
+}
+
+fn handle_all(
+  init: lsp_types::InitializeParams,
+  sender: crossbeam_channel::Sender<lsp_server::Message>,
+  shared: Arc<shared::Shared>,
+  receiver: &crossbeam_channel::Receiver<lsp_server::Message>,
+) -> anyhow::Result<()> {
+  let mut st = init::init(init, sender, shared)?;
+  let events = st.worker.events.clone();
+  loop {
+    crossbeam_channel::select! {
+      recv(receiver) -> msg => {
+        let Ok(msg) = msg else { break };
+        match msg {
+          lsp_server::Message::Request(req) => request::handle(&mut st, req),
+          lsp_server::Message::Response(res) => response::handle(&mut st, res),
+          lsp_server::Message::Notification(notif) => {
+            if shared::is_edit(&notif.method) {
+              st.edits += 1;
+            }
+            notification::handle(&mut st, notif);
+          }
+        }
+      }
+      recv(events) -> event => {
+        let Ok(event) = event else { anyhow::bail!("the analysis thread stopped") };
+        match event {
+          worker::Event::Report(message, percentage) => {
+            diagnostics::report(&st, message, percentage);
+          }
+          worker::Event::Finished(id, done) => diagnostics::finish(&mut st, id, done),
         }
-        request::handle(&mut st, req);
       }
-      lsp_server::Message::Response(res) => response::handle(&mut st, res),
-      lsp_server::Message::Notification(notif) => notification::handle(&mut st, notif),
+    }
+    if st.publish_pending {
+      diagnostics::publish(&mut st);
     }
   }
   Ok(())
 }
 
+/// Runs the language server over the connection, starting with the initialization handshake.
+///
+/// # Errors
+///
+/// If the language server encountered an error.
+pub fn run(connection: &lsp_server::Connection) -> anyhow::Result<()> {
+  let params = connection.initialize(serde_json::to_value(capabilities::get())?)?;
+  run_inner(connection, serde_json::from_value(params)?)
+}
+
 /// Runs the language server over stdio.
 ///
 /// # Errors
@@ -43,8 +120,7 @@ fn run_inner(
 /// If the language server encountered an error.
 pub fn run_stdio() -> anyhow::Result<()> {
   let (connection, io_threads) = lsp_server::Connection::stdio();
-  let params = connection.initialize(serde_json::to_value(capabilities::get())?)?;
-  run_inner(&connection, serde_json::from_value(params)?)?;
+  run(&connection)?;
   // if we don't drop this, then the join hangs
   drop(connection);
   io_threads.join()?;
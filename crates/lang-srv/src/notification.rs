@@ -1,9 +1,10 @@
 //! Handle notifications.
 
-use crate::cx::Cx;
-use crate::state::{Mode, St};
-use crate::{convert, diagnostics, helpers};
+use crate::cx::{Cx, CONFIGURATION_SECTION};
+use crate::state::St;
+use crate::{convert, diagnostics, helpers, init};
 use anyhow::{bail, Result};
+use diagnostic::Code;
 use lsp_server::Notification;
 use paths::FileSystem as _;
 use std::collections::hash_map::Entry;
@@ -53,45 +54,160 @@ fn try_update_input(
 #[allow(clippy::too_many_lines)]
 fn go(st: &mut St, mut n: Notification) -> ControlFlow<Result<()>, Notification> {
   n = helpers::try_notif::<lsp_types::notification::DidChangeWatchedFiles, _>(n, |params| {
-    match &mut st.mode {
-      Mode::Root(root) => {
-        match try_update_input(&mut st.cx, &mut root.input, params.changes) {
-          Ok(_) => {
-            // TODO use path ids
+    // a config file appearing or disappearing may add or remove a nested root.
+    let roots_changed = params.changes.iter().any(|change| {
+      let is_config = change.uri.path().ends_with(&format!("/{}", config::file::PATH));
+      is_config && change.typ != lsp_types::FileChangeType::CHANGED
+    });
+    let mut by_root = vec![Vec::<lsp_types::FileEvent>::new(); st.roots.len()];
+    for change in params.changes {
+      let Ok(path) = change.uri.to_file_path() else { continue };
+      for (root, changes) in st.roots.iter().zip(by_root.iter_mut()) {
+        if path.starts_with(root.path.as_path()) {
+          changes.push(change.clone());
+        }
+      }
+    }
+    for (root, changes) in st.roots.iter_mut().zip(by_root) {
+      if changes.is_empty() {
+        continue;
+      }
+      match try_update_input(&mut st.cx, &mut root.input, changes) {
+        Ok(paths) => {
+          // the contents of open files take precedence over what is on disk.
+          for path in paths {
+            st.cx.add_open_file(&mut root.input, path);
           }
-          Err(_) => root.input = st.cx.get_input(&root.path),
         }
-        diagnostics::try_publish(st);
+        Err(_) => root.input = st.cx.get_input(&root.path),
+      }
+    }
+    if roots_changed {
+      st.update_roots();
+    }
+    diagnostics::publish(st);
+    Ok(())
+  })?;
+  n = helpers::try_notif::<lsp_types::notification::DidChangeWorkspaceFolders, _>(n, |params| {
+    for folder in params.event.removed {
+      st.folders.retain(|path| convert::file_url(path.as_path()).map_or(true, |x| x != folder.uri));
+    }
+    for folder in params.event.added {
+      match convert::canonical_path_buf(&st.cx.fs, &folder.uri) {
+        Ok(path) => st.folders.push(path),
+        Err(e) => st.cx.show_error(
+          format!("cannot initialize workspace root {}: {e:#}", folder.uri),
+          Code::n(1018),
+        ),
       }
-      Mode::NoRoot => bail!("unexpected DidChangeWatchedFiles with NoRoot"),
     }
+    st.update_roots();
+    init::register_for_watched_files(st);
+    diagnostics::publish(st);
+    Ok(())
+  })?;
+  n = helpers::try_notif::<lsp_types::notification::DidChangeConfiguration, _>(n, |params| {
+    let mut settings = params.settings;
+    if let Some(x) = settings.get_mut(CONFIGURATION_SECTION).map(serde_json::Value::take) {
+      settings = x;
+    }
+    // clients that support pulling the configuration may send no settings.
+    if settings.is_null() {
+      if st.cx.configuration {
+        st.cx.request_configuration();
+      }
+      return Ok(());
+    }
+    let settings: config::init::Settings = serde_json::from_value(settings)?;
+    st.set_options(settings.into());
+    diagnostics::publish(st);
+    Ok(())
+  })?;
+  n = helpers::try_notif::<lsp_types::notification::DidOpenTextDocument, _>(n, |params| {
+    let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &params.text_document.uri)?;
+    st.cx.open_files.insert(path, params.text_document.text);
+    let changed = match st.owner(path) {
+      Some(idx) => st.cx.add_open_file(&mut st.roots[idx].input, path),
+      None => st.cx.is_sml(path),
+    };
+    if changed {
+      diagnostics::publish(st);
+    }
+    Ok(())
+  })?;
+  n = helpers::try_notif::<lsp_types::notification::DidCloseTextDocument, _>(n, |params| {
+    let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &params.text_document.uri)?;
+    st.cx.open_files.remove(&path);
+    match st.owner(path) {
+      Some(idx) => {
+        let sources = &mut st.roots[idx].input.sources;
+        match st.cx.fs.read_to_string(st.cx.paths.get_path(path).as_path()) {
+          // go back to the contents on disk, if the buffer had other contents.
+          Ok(on_disk) => {
+            let Some(contents) = sources.get_mut(&path) else { return Ok(()) };
+            if *contents == on_disk {
+              return Ok(());
+            }
+            *contents = on_disk;
+          }
+          // the file was deleted while open, so it is no longer a source.
+          Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if sources.remove(&path).is_none() {
+              return Ok(());
+            }
+          }
+          Err(e) => return Err(e.into()),
+        }
+      }
+      // stop analyzing the file if it was standalone.
+      None => {
+        if !st.cx.is_sml(path) {
+          return Ok(());
+        }
+      }
+    }
+    diagnostics::publish(st);
     Ok(())
   })?;
   n = helpers::try_notif::<lsp_types::notification::DidChangeTextDocument, _>(n, |params| {
     let url = params.text_document.uri;
     let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &url)?;
-    if let Mode::Root(root) = &mut st.mode {
-      let Some(text) = root.input.sources.get_mut(&path) else {
-        bail!("no source in the input for DidChangeTextDocument")
-      };
-      helpers::apply_changes(text, params.content_changes);
-      if st.cx.options.diagnostics.on_change {
-        diagnostics::try_publish(st);
-      } else {
-        st.analysis.update_one(&root.input, path);
-      }
+    let Some(text) = st.cx.open_files.get_mut(&path) else {
+      bail!("no open file for DidChangeTextDocument")
+    };
+    helpers::apply_changes(text, params.content_changes);
+    let owner = st.owner(path);
+    let changed = match owner {
+      Some(idx) => st.cx.add_open_file(&mut st.roots[idx].input, path),
+      None => st.cx.is_sml(path),
+    };
+    if !changed {
+      return Ok(());
+    }
+    if st.cx.options.diagnostics.on_change {
+      diagnostics::publish(st);
+    } else {
+      st.update_one(path);
+      st.diagnostics = None;
     }
     Ok(())
   })?;
-  n = helpers::try_notif::<lsp_types::notification::DidSaveTextDocument, _>(n, |_| {
-    if let Mode::Root(root) = &mut st.mode {
-      if st.cx.registered_for_watched_files {
-        log::warn!("ignoring DidSaveTextDocument since we registered for watched file events");
-      } else {
+  n = helpers::try_notif::<lsp_types::notification::DidSaveTextDocument, _>(n, |params| {
+    if st.cx.registered_for_watched_files {
+      log::warn!("ignoring DidSaveTextDocument since we registered for watched file events");
+      return Ok(());
+    }
+    let path = convert::canonical_path_buf(&st.cx.fs, &params.text_document.uri)?;
+    let mut changed = false;
+    for root in &mut st.roots {
+      if path.as_path().starts_with(root.path.as_path()) {
         root.input = st.cx.get_input(&root.path);
-        diagnostics::try_publish(st);
+        changed = true;
       }
     }
+    if changed {
+      diagnostics::publish(st);
+    }
     Ok(())
   })?;
   ControlFlow::Continue(n)
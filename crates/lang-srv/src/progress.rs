@@ -0,0 +1,69 @@
+//! See [`Progress`].
+
+use crate::cx::Cx;
+use lsp_types::{NumberOrString, ProgressParamsValue, WorkDoneProgress};
+
+/// Reports progress on some work to the client, if the client supports it.
+pub(crate) struct Progress {
+  token: Option<NumberOrString>,
+}
+
+impl Progress {
+  /// Begins reporting progress on work with the title.
+  pub(crate) fn begin(cx: &mut Cx, title: &str) -> Progress {
+    if !cx.work_done_progress {
+      return Progress { token: None };
+    }
+    let token = NumberOrString::String(format!("millet/{}", cx.progress_tokens));
+    cx.progress_tokens += 1;
+    cx.send_request::<lsp_types::request::WorkDoneProgressCreate>(
+      lsp_types::WorkDoneProgressCreateParams { token: token.clone() },
+      None,
+    );
+    let ret = Progress { token: Some(token) };
+    ret.send(
+      cx,
+      WorkDoneProgress::Begin(lsp_types::WorkDoneProgressBegin {
+        title: title.to_owned(),
+        cancellable: Some(false),
+        message: None,
+        percentage: None,
+      }),
+    );
+    ret
+  }
+
+  /// Reports a message about the work, and optionally a percentage of the work done.
+  pub(crate) fn report(&self, cx: &Cx, message: String, percentage: Option<u32>) {
+    self.send(
+      cx,
+      WorkDoneProgress::Report(lsp_types::WorkDoneProgressReport {
+        cancellable: Some(false),
+        message: Some(message),
+        percentage,
+      }),
+    );
+  }
+
+  /// Ends reporting progress, since the work is done.
+  pub(crate) fn end(self, cx: &Cx) {
+    self.send(cx, WorkDoneProgress::End(lsp_types::WorkDoneProgressEnd { message: None }));
+  }
+
+  fn send(&self, cx: &Cx, value: WorkDoneProgress) {
+    let Some(token) = self.token.clone() else { return };
+    cx.send_notification::<lsp_types::notification::Progress>(lsp_types::ProgressParams {
+      token,
+      value: ProgressParamsValue::WorkDone(value),
+    });
+  }
+}
+
+/// Returns a message with the number of groups and files in the input.
+pub(crate) fn input_message(input: &input::Input) -> String {
+  let groups = input.groups.len();
+  let files = input.sources.len();
+  let groups_s = if groups == 1 { "" } else { "s" };
+  let files_s = if files == 1 { "" } else { "s" };
+  format!("{groups} group{groups_s}, {files} file{files_s}")
+}
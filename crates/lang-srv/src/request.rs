@@ -1,19 +1,33 @@
 //! Handle requests.
 
-use crate::state::{Mode, St};
-use crate::{convert, helpers};
+use crate::state::St;
+use crate::{convert, diagnostics, helpers, semantic_tokens};
 use anyhow::Result;
-use lsp_server::{Request, Response};
+use fast_hash::FxHashMap;
+use lsp_server::{ErrorCode, Request, Response};
+use paths::FileSystem as _;
 use std::ops::ControlFlow;
 
 pub(crate) fn handle(st: &mut St, req: Request) {
   log::info!("got request: {req:?}");
   st.cx.req_queue.incoming.register(req.id.clone(), ());
+  let id = req.id.clone();
+  if st.cx.shared.is_canceled(&id) {
+    st.cx.shared.finish(&id);
+    let res = Response::new_err(id, ErrorCode::RequestCanceled as i32, "canceled".to_owned());
+    st.cx.send_response(res);
+    return;
+  }
+  let req = match diagnostics::defer_pull(st, req) {
+    ControlFlow::Break(()) => return,
+    ControlFlow::Continue(req) => req,
+  };
   match go(st, req) {
     ControlFlow::Break(Ok(())) => {}
     ControlFlow::Break(Err(e)) => log::error!("couldn't handle request: {e}"),
     ControlFlow::Continue(req) => log::warn!("unhandled request: {req:?}"),
   }
+  st.cx.shared.finish(&id);
 }
 
 /// TODO replace with constant from lsp types after it's updated with 3.17
@@ -24,23 +38,28 @@ fn go(st: &mut St, mut r: Request) -> ControlFlow<Result<()>, Request> {
   r = helpers::try_req::<lsp_types::request::HoverRequest, _>(r, |id, params| {
     let params = params.text_document_position_params;
     let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
-    let res =
-      st.analysis.get_md(pos, st.cx.options.token_hover.0).map(|(value, range)| lsp_types::Hover {
-        contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
-          kind: lsp_types::MarkupKind::Markdown,
-          value,
-        }),
-        range: Some(convert::lsp_range(range)),
-      });
+    let md = match st.group_root(pos.path) {
+      Some(root) => root.analysis.group_md(&root.input, &st.cx.paths, pos),
+      None => st.analysis(pos.path).get_md(pos, st.cx.options.token_hover.0),
+    };
+    let res = md.map(|(value, range)| lsp_types::Hover {
+      contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+        kind: lsp_types::MarkupKind::Markdown,
+        value,
+      }),
+      range: Some(convert::lsp_range(range)),
+    });
     st.cx.send_response(Response::new_ok(id, res));
     Ok(())
   })?;
   r = helpers::try_req::<lsp_types::request::GotoDefinition, _>(r, |id, params| {
     let params = params.text_document_position_params;
     let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
-    let res: Vec<_> = st
-      .analysis
-      .get_defs(pos)
+    let defs = match st.group_root(pos.path) {
+      Some(root) => root.analysis.group_defs(&root.input, pos),
+      None => st.analysis(pos.path).get_defs(pos),
+    };
+    let res: Vec<_> = defs
       .into_iter()
       .flatten()
       .filter_map(|range| convert::lsp_location(&st.cx.paths, range))
@@ -52,7 +71,7 @@ fn go(st: &mut St, mut r: Request) -> ControlFlow<Result<()>, Request> {
     let params = params.text_document_position_params;
     let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
     let locs: Vec<_> = st
-      .analysis
+      .analysis(pos.path)
       .get_ty_defs(pos)
       .into_iter()
       .flatten()
@@ -62,13 +81,72 @@ fn go(st: &mut St, mut r: Request) -> ControlFlow<Result<()>, Request> {
     st.cx.send_response(Response::new_ok(id, res));
     Ok(())
   })?;
+  r = helpers::try_req::<lsp_types::request::GotoImplementation, _>(r, |id, params| {
+    let params = params.text_document_position_params;
+    let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
+    let locs: Vec<_> = st
+      .analysis(pos.path)
+      .get_impls(pos)
+      .into_iter()
+      .flatten()
+      .filter_map(|range| convert::lsp_location(&st.cx.paths, range))
+      .collect();
+    let res = (!locs.is_empty()).then_some(lsp_types::GotoDefinitionResponse::Array(locs));
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
   r = helpers::try_req::<lsp_types::request::CodeActionRequest, _>(r, |id, params| {
     let url = params.text_document.uri;
     let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &url)?;
     let range = convert::analysis_range(params.range);
     let mut actions = Vec::<lsp_types::CodeActionOrCommand>::new();
-    if let Some((range, new_text)) = st.analysis.fill_case(path.wrap(range.start)) {
-      actions.push(convert::quick_fix("Fill case".to_owned(), url, range, new_text));
+    let an = st.analysis(path);
+    if let Some((range, new_text)) = an.fill_case(path.wrap(range.start)) {
+      actions.push(convert::quick_fix("Fill case".to_owned(), url.clone(), range, new_text));
+    }
+    if let Some((range, new_text)) = an.add_missing_arms(path.wrap(range.start)) {
+      actions.push(convert::quick_fix("Add missing arms".to_owned(), url.clone(), range, new_text));
+    }
+    if let Some(edits) = an.add_missing_specs(path.wrap(range.start)) {
+      let title = "Add stubs for missing specifications".to_owned();
+      actions.push(convert::quick_fix_edits(title, url.clone(), edits));
+    }
+    for (range, new_text) in an.did_you_mean(path.wrap(range.start)) {
+      let title = format!("Change to `{new_text}`");
+      actions.push(convert::quick_fix(title, url.clone(), range, new_text));
+    }
+    for (range, new_text) in an.qualify(path.wrap(range.start)) {
+      let title = format!("Qualify as `{new_text}`");
+      actions.push(convert::quick_fix(title, url.clone(), range, new_text));
+    }
+    for top_level in [false, true] {
+      if let Some(edits) = an.extract_val(path.wrap(range), top_level) {
+        let title = if top_level {
+          "Extract to `val` before the declaration"
+        } else {
+          "Extract to `val` in `let`"
+        };
+        let kind = lsp_types::CodeActionKind::REFACTOR_EXTRACT;
+        actions.push(convert::refactor(title.to_owned(), kind, url.clone(), edits));
+      }
+    }
+    if let Some((name, edits)) = an.inline_val(path.wrap(range.start)) {
+      let kind = lsp_types::CodeActionKind::REFACTOR_INLINE;
+      actions.push(convert::refactor(format!("Inline `{name}`"), kind, url.clone(), edits));
+    }
+    for ascribe in [false, true] {
+      if let Some((name, edits)) = an.generate_sig(path.wrap(range.start), ascribe) {
+        let title = if ascribe {
+          format!("Generate signature `{name}` and ascribe to it")
+        } else {
+          format!("Generate signature `{name}`")
+        };
+        let kind = lsp_types::CodeActionKind::REFACTOR;
+        actions.push(convert::refactor(title, kind, url.clone(), edits));
+      }
+    }
+    for diagnostic in params.context.diagnostics {
+      actions.extend(convert::diagnostic_fix(url.clone(), diagnostic));
     }
     st.cx.send_response(Response::new_ok(id, actions));
     Ok(())
@@ -76,35 +154,27 @@ fn go(st: &mut St, mut r: Request) -> ControlFlow<Result<()>, Request> {
   r = helpers::try_req::<lsp_types::request::Formatting, _>(r, |id, params| {
     let url = params.text_document.uri;
     let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &url)?;
-    let res = match &st.mode {
-      Mode::Root(root) => {
-        // need to re-compute the internal parse tree etc
-        st.analysis.get_many(&root.input);
-        match st.analysis.format(path, params.options.tab_size) {
-          Ok((new_text, end)) => {
-            let edit = lsp_types::TextEdit {
-              range: lsp_types::Range {
-                start: lsp_types::Position { line: 0, character: 0 },
-                end: convert::lsp_position(end),
-              },
-              new_text,
-            };
-            Response::new_ok(id, vec![edit])
-          }
-          Err(e) => match e {
-            analysis::FormatError::NoFile
-            | analysis::FormatError::Disabled
-            | analysis::FormatError::NaiveFmt(_)
-            | analysis::FormatError::Smlfmt(analysis::SmlfmtError::Unsuccessful(_)) => {
-              Response::new_ok(id, None::<()>)
-            }
-            analysis::FormatError::Smlfmt(e) => {
-              Response::new_err(id, REQUEST_FAILED, format!("{e:#}"))
-            }
+    // the worker may not have analyzed the newest contents yet, so re-parse only this file.
+    let res = match st.update_one(path).format(path, params.options.tab_size) {
+      Ok((new_text, end)) => {
+        let edit = lsp_types::TextEdit {
+          range: lsp_types::Range {
+            start: lsp_types::Position { line: 0, character: 0 },
+            end: convert::lsp_position(end),
           },
-        }
+          new_text,
+        };
+        Response::new_ok(id, vec![edit])
       }
-      Mode::NoRoot => Response::new_ok(id, None::<()>),
+      Err(e) => match e {
+        analysis::FormatError::NoFile
+        | analysis::FormatError::Disabled
+        | analysis::FormatError::NaiveFmt(_)
+        | analysis::FormatError::Smlfmt(analysis::SmlfmtError::Unsuccessful(_)) => {
+          Response::new_ok(id, None::<()>)
+        }
+        analysis::FormatError::Smlfmt(e) => Response::new_err(id, REQUEST_FAILED, format!("{e:#}")),
+      },
     };
     st.cx.send_response(res);
     Ok(())
@@ -113,26 +183,228 @@ fn go(st: &mut St, mut r: Request) -> ControlFlow<Result<()>, Request> {
     let url = params.text_document.uri;
     let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &url)?;
     let res: Option<Vec<_>> = st
-      .analysis
+      .analysis(path)
       .document_symbols(path)
       .map(|xs| xs.into_iter().map(convert::document_symbol).collect());
     st.cx.send_response(Response::new_ok(id, res));
     Ok(())
   })?;
+  r = helpers::try_req::<lsp_types::request::WorkspaceSymbolRequest, _>(r, |id, params| {
+    let analyses =
+      st.roots.iter().map(|root| &root.analysis).chain(std::iter::once(&st.standalone));
+    let res: Vec<_> = analysis::workspace_symbols(analyses, &params.query)
+      .into_iter()
+      .filter_map(|sym| convert::workspace_symbol(&st.cx.paths, sym))
+      .collect();
+    st.cx.send_response(Response::new_ok(id, lsp_types::WorkspaceSymbolResponse::Nested(res)));
+    Ok(())
+  })?;
   r = helpers::try_req::<lsp_types::request::References, _>(r, |id, params| {
     let params = params.text_document_position;
     let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
-    let res: Option<Vec<_>> = st.analysis.find_all_references(pos).map(|locs| {
+    let res: Option<Vec<_>> = st.analysis(pos.path).find_all_references(pos).map(|locs| {
       locs.into_iter().filter_map(|loc| convert::lsp_location(&st.cx.paths, loc)).collect()
     });
     st.cx.send_response(Response::new_ok(id, res));
     Ok(())
   })?;
+  r = helpers::try_req::<lsp_types::request::DocumentHighlightRequest, _>(r, |id, params| {
+    let params = params.text_document_position_params;
+    let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
+    let res: Option<Vec<_>> = st
+      .analysis(pos.path)
+      .document_highlights(pos)
+      .map(|xs| xs.into_iter().map(convert::document_highlight).collect());
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::FoldingRangeRequest, _>(r, |id, params| {
+    let path = convert::canonical_path_buf(&st.cx.fs, &params.text_document.uri)?;
+    let path_id = st.cx.paths.get_id(&path);
+    let ranges = match st.analysis(path_id).folding_ranges(path_id) {
+      Some(ranges) => Some(ranges),
+      None => {
+        let contents = st.cx.fs.read_to_string(path.as_path())?;
+        analysis::group_folding_ranges(path.as_path(), &contents)
+      }
+    };
+    let res: Option<Vec<_>> =
+      ranges.map(|ranges| ranges.into_iter().map(convert::folding_range).collect());
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::DocumentLinkRequest, _>(r, |id, params| {
+    let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &params.text_document.uri)?;
+    let links = st.group_root(path).and_then(|root| analysis::group_links(&root.input, path));
+    let res: Option<Vec<_>> = links.map(|links| {
+      links.into_iter().filter_map(|link| convert::document_link(&st.cx.paths, link)).collect()
+    });
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::SelectionRangeRequest, _>(r, |id, params| {
+    let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &params.text_document.uri)?;
+    // we must return one selection range for each position, so use an empty range if we must.
+    let res: Vec<_> = params
+      .positions
+      .into_iter()
+      .map(|pos| {
+        let ranges = st.analysis(path).selection_ranges(path.wrap(convert::analysis_position(pos)));
+        ranges.and_then(convert::selection_range).unwrap_or_else(|| lsp_types::SelectionRange {
+          range: lsp_types::Range { start: pos, end: pos },
+          parent: None,
+        })
+      })
+      .collect();
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::DocumentDiagnosticRequest, _>(r, |id, params| {
+    let url = params.text_document.uri;
+    let ds = st.diagnostics.as_ref().and_then(|x| x.get(&url)).cloned().unwrap_or_default();
+    let result_id = diagnostics::result_id(&ds);
+    let report = if params.previous_result_id.as_ref() == Some(&result_id) {
+      lsp_types::DocumentDiagnosticReport::Unchanged(
+        lsp_types::RelatedUnchangedDocumentDiagnosticReport {
+          related_documents: None,
+          unchanged_document_diagnostic_report: lsp_types::UnchangedDocumentDiagnosticReport {
+            result_id,
+          },
+        },
+      )
+    } else {
+      lsp_types::DocumentDiagnosticReport::Full(lsp_types::RelatedFullDocumentDiagnosticReport {
+        related_documents: None,
+        full_document_diagnostic_report: lsp_types::FullDocumentDiagnosticReport {
+          result_id: Some(result_id),
+          items: ds,
+        },
+      })
+    };
+    let res = lsp_types::DocumentDiagnosticReportResult::Report(report);
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::WorkspaceDiagnosticRequest, _>(r, |id, params| {
+    let mut all = st.diagnostics.clone().unwrap_or_default();
+    // files that had diagnostics before, but are now gone from the input, have no diagnostics.
+    for prev in &params.previous_result_ids {
+      all.entry(prev.uri.clone()).or_default();
+    }
+    let previous: FxHashMap<_, _> =
+      params.previous_result_ids.into_iter().map(|x| (x.uri, x.value)).collect();
+    let items: Vec<_> = all
+      .into_iter()
+      .map(|(uri, ds)| {
+        let result_id = diagnostics::result_id(&ds);
+        if previous.get(&uri) == Some(&result_id) {
+          lsp_types::WorkspaceDocumentDiagnosticReport::Unchanged(
+            lsp_types::WorkspaceUnchangedDocumentDiagnosticReport {
+              uri,
+              version: None,
+              unchanged_document_diagnostic_report: lsp_types::UnchangedDocumentDiagnosticReport {
+                result_id,
+              },
+            },
+          )
+        } else {
+          lsp_types::WorkspaceDocumentDiagnosticReport::Full(
+            lsp_types::WorkspaceFullDocumentDiagnosticReport {
+              uri,
+              version: None,
+              full_document_diagnostic_report: lsp_types::FullDocumentDiagnosticReport {
+                result_id: Some(result_id),
+                items: ds,
+              },
+            },
+          )
+        }
+      })
+      .collect();
+    let res =
+      lsp_types::WorkspaceDiagnosticReportResult::Report(lsp_types::WorkspaceDiagnosticReport {
+        items,
+      });
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::CallHierarchyPrepare, _>(r, |id, params| {
+    let params = params.text_document_position_params;
+    let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
+    let res: Option<Vec<_>> = st
+      .analysis(pos.path)
+      .prepare_call_hierarchy(pos)
+      .and_then(|item| convert::call_hierarchy_item(&st.cx.paths, item))
+      .map(|item| vec![item]);
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::CallHierarchyIncomingCalls, _>(r, |id, params| {
+    let pos = convert::call_hierarchy_item_pos(&st.cx.fs, &mut st.cx.paths, &params.item)?;
+    let res: Option<Vec<_>> = st.analysis(pos.path).incoming_calls(pos).map(|calls| {
+      calls
+        .into_iter()
+        .filter_map(|call| {
+          Some(lsp_types::CallHierarchyIncomingCall {
+            from: convert::call_hierarchy_item(&st.cx.paths, call.item)?,
+            from_ranges: call.ranges.into_iter().map(convert::lsp_range).collect(),
+          })
+        })
+        .collect()
+    });
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::CallHierarchyOutgoingCalls, _>(r, |id, params| {
+    let pos = convert::call_hierarchy_item_pos(&st.cx.fs, &mut st.cx.paths, &params.item)?;
+    let res: Option<Vec<_>> = st.analysis(pos.path).outgoing_calls(pos).map(|calls| {
+      calls
+        .into_iter()
+        .filter_map(|call| {
+          Some(lsp_types::CallHierarchyOutgoingCall {
+            to: convert::call_hierarchy_item(&st.cx.paths, call.item)?,
+            from_ranges: call.ranges.into_iter().map(convert::lsp_range).collect(),
+          })
+        })
+        .collect()
+    });
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
   r = helpers::try_req::<lsp_types::request::Completion, _>(r, |id, params| {
     let params = params.text_document_position;
     let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
-    let res: Option<Vec<_>> =
-      st.analysis.completions(pos).map(|cs| cs.into_iter().map(convert::completion_item).collect());
+    let res: Option<Vec<_>> = if st.cx.is_sml(pos.path) {
+      st.analysis(pos.path)
+        .completions(pos)
+        .map(|cs| cs.into_iter().map(convert::completion_item).collect())
+    } else {
+      let path = st.cx.paths.get_path(pos.path).as_path();
+      let contents = match st.cx.open_files.get(&pos.path) {
+        Some(contents) => contents.clone(),
+        None => st.cx.fs.read_to_string(path)?,
+      };
+      let root = st.dir_root(path);
+      let path_vars = root.map(|root| root.input.path_vars.clone()).unwrap_or_default();
+      // the innermost root group file whose directory contains the group file.
+      let root_group_dir = root
+        .into_iter()
+        .flat_map(|root| root.input.root_group_paths.iter())
+        .filter_map(|&p| st.cx.paths.get_path(p).as_path().parent())
+        .filter(|dir| path.starts_with(dir))
+        .max_by_key(|dir| dir.components().count())
+        .or_else(|| path.parent())
+        .unwrap_or(path);
+      analysis::group_completions(&st.cx.fs, path, &contents, pos.val, &path_vars, root_group_dir)
+        .map(|cs| cs.into_iter().map(convert::group_completion_item).collect())
+    };
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::SignatureHelpRequest, _>(r, |id, params| {
+    let params = params.text_document_position_params;
+    let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
+    let res = st.analysis(pos.path).signature_help(pos).map(convert::signature_help);
     st.cx.send_response(Response::new_ok(id, res));
     Ok(())
   })?;
@@ -141,7 +413,7 @@ fn go(st: &mut St, mut r: Request) -> ControlFlow<Result<()>, Request> {
     let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &url)?;
     let range = convert::analysis_range(params.range);
     let res: Vec<_> = st
-      .analysis
+      .analysis(path)
       .inlay_hints(path.wrap(range))
       .into_iter()
       .flat_map(|xs| xs.into_iter().map(convert::inlay_hint))
@@ -149,5 +421,60 @@ fn go(st: &mut St, mut r: Request) -> ControlFlow<Result<()>, Request> {
     st.cx.send_response(Response::new_ok(id, res));
     Ok(())
   })?;
+  r = helpers::try_req::<lsp_types::request::PrepareRenameRequest, _>(r, |id, params| {
+    let pos = convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params)?;
+    let res = match st.analysis(pos.path).prepare_rename(pos) {
+      Ok(range) => {
+        Response::new_ok(id, lsp_types::PrepareRenameResponse::Range(convert::lsp_range(range)))
+      }
+      Err(e) => Response::new_err(id, REQUEST_FAILED, e.to_string()),
+    };
+    st.cx.send_response(res);
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::Rename, _>(r, |id, params| {
+    let pos =
+      convert::text_doc_pos_params(&st.cx.fs, &mut st.cx.paths, &params.text_document_position)?;
+    let res = match st.analysis(pos.path).rename(pos, &params.new_name) {
+      Ok(edits) => Response::new_ok(id, convert::workspace_edit(&st.cx.paths, edits)),
+      Err(e) => Response::new_err(id, REQUEST_FAILED, e.to_string()),
+    };
+    st.cx.send_response(res);
+    Ok(())
+  })?;
+  r = helpers::try_req::<lsp_types::request::SemanticTokensFullRequest, _>(r, |id, params| {
+    let url = params.text_document.uri;
+    let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &url)?;
+    let res = st.analysis(path).semantic_tokens(path, None).map(|tokens| {
+      let data = semantic_tokens::encode(tokens);
+      lsp_types::SemanticTokensResult::Tokens(st.semantic_tokens.full(url, data))
+    });
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
+  r =
+    helpers::try_req::<lsp_types::request::SemanticTokensFullDeltaRequest, _>(r, |id, params| {
+      let url = params.text_document.uri;
+      let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &url)?;
+      let res = st.analysis(path).semantic_tokens(path, None).map(|tokens| {
+        let data = semantic_tokens::encode(tokens);
+        st.semantic_tokens.delta(url, &params.previous_result_id, data)
+      });
+      st.cx.send_response(Response::new_ok(id, res));
+      Ok(())
+    })?;
+  r = helpers::try_req::<lsp_types::request::SemanticTokensRangeRequest, _>(r, |id, params| {
+    let url = params.text_document.uri;
+    let path = convert::url_to_path_id(&st.cx.fs, &mut st.cx.paths, &url)?;
+    let range = convert::analysis_range(params.range);
+    let res = st.analysis(path).semantic_tokens(path, Some(range)).map(|tokens| {
+      lsp_types::SemanticTokensRangeResult::Tokens(lsp_types::SemanticTokens {
+        result_id: None,
+        data: semantic_tokens::encode(tokens),
+      })
+    });
+    st.cx.send_response(Response::new_ok(id, res));
+    Ok(())
+  })?;
   ControlFlow::Continue(r)
 }
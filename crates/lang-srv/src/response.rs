@@ -1,6 +1,6 @@
 //! Respond to requests.
 
-use crate::{convert, cx::LEARN_MORE, state::St};
+use crate::{convert, cx::LEARN_MORE, diagnostics, state::St};
 use lsp_server::Response;
 
 pub(crate) fn handle(st: &mut St, res: Response) {
@@ -9,6 +9,11 @@ pub(crate) fn handle(st: &mut St, res: Response) {
     log::warn!("received response for non-queued request: {res:?}");
     return;
   };
+  if st.cx.configuration_request.as_ref() == Some(&res.id) {
+    st.cx.configuration_request = None;
+    configuration(st, res);
+    return;
+  }
   let Some(code) = data else {
     log::info!("no error code associated with this request");
     return;
@@ -38,3 +43,24 @@ pub(crate) fn handle(st: &mut St, res: Response) {
     None,
   );
 }
+
+fn configuration(st: &mut St, res: Response) {
+  if let Some(e) = res.error {
+    log::error!("couldn't get configuration: {e:?}");
+    return;
+  }
+  let Some(val) = res.result else {
+    log::warn!("got no configuration");
+    return;
+  };
+  // we asked for one item.
+  let settings = match serde_json::from_value::<[config::init::Settings; 1]>(val) {
+    Ok([x]) => x,
+    Err(e) => {
+      log::error!("invalid configuration: {e}");
+      return;
+    }
+  };
+  st.set_options(settings.into());
+  diagnostics::publish(st);
+}
@@ -0,0 +1,151 @@
+//! Encode semantic tokens, and compute deltas between them.
+
+use fast_hash::FxHashMap;
+use lsp_types::{SemanticTokenModifier, SemanticTokenType, Url};
+use sml_namespace::SymbolKind;
+
+const TOKEN_TYPES: [SemanticTokenType; 8] = [
+  SemanticTokenType::NAMESPACE,
+  SemanticTokenType::INTERFACE,
+  SemanticTokenType::CLASS,
+  SemanticTokenType::TYPE,
+  SemanticTokenType::TYPE_PARAMETER,
+  SemanticTokenType::ENUM_MEMBER,
+  SemanticTokenType::FUNCTION,
+  SemanticTokenType::VARIABLE,
+];
+
+const DEFAULT_LIBRARY: u32 = 1 << 0;
+const MUTABLE: u32 = 1 << 1;
+const EXCEPTION: u32 = 1 << 2;
+
+/// Must be in the same order as the bits above.
+const TOKEN_MODIFIERS: [SemanticTokenModifier; 3] = [
+  SemanticTokenModifier::DEFAULT_LIBRARY,
+  SemanticTokenModifier::new("mutable"),
+  SemanticTokenModifier::new("exception"),
+];
+
+pub(crate) fn legend() -> lsp_types::SemanticTokensLegend {
+  lsp_types::SemanticTokensLegend {
+    token_types: TOKEN_TYPES.to_vec(),
+    token_modifiers: TOKEN_MODIFIERS.to_vec(),
+  }
+}
+
+fn token_type(kind: analysis::SemanticTokenKind) -> SemanticTokenType {
+  match kind {
+    analysis::SemanticTokenKind::Symbol(kind) => match kind {
+      SymbolKind::Structure => SemanticTokenType::NAMESPACE,
+      SymbolKind::Signature => SemanticTokenType::INTERFACE,
+      SymbolKind::Functor => SemanticTokenType::CLASS,
+      SymbolKind::Type => SemanticTokenType::TYPE,
+      SymbolKind::Constructor | SymbolKind::Exception => SemanticTokenType::ENUM_MEMBER,
+      SymbolKind::Function => SemanticTokenType::FUNCTION,
+      SymbolKind::Value => SemanticTokenType::VARIABLE,
+    },
+    analysis::SemanticTokenKind::TyVar => SemanticTokenType::TYPE_PARAMETER,
+  }
+}
+
+/// Encodes the tokens, which must be sorted by position, into the relative format that LSP uses.
+pub(crate) fn encode(tokens: Vec<analysis::SemanticToken>) -> Vec<lsp_types::SemanticToken> {
+  let mut ret = Vec::with_capacity(tokens.len());
+  let mut line = 0u32;
+  let mut col = 0u32;
+  for token in tokens {
+    let tt = token_type(token.kind);
+    let Some(idx) = TOKEN_TYPES.iter().position(|x| *x == tt) else { continue };
+    let mut modifiers = 0u32;
+    if token.std_basis {
+      modifiers |= DEFAULT_LIBRARY;
+    }
+    if token.is_ref {
+      modifiers |= MUTABLE;
+    }
+    if matches!(token.kind, analysis::SemanticTokenKind::Symbol(SymbolKind::Exception)) {
+      modifiers |= EXCEPTION;
+    }
+    let start = token.range.start;
+    let delta_line = start.line - line;
+    let delta_start = if delta_line == 0 { start.col - col } else { start.col };
+    line = start.line;
+    col = start.col;
+    ret.push(lsp_types::SemanticToken {
+      delta_line,
+      delta_start,
+      length: token.range.end.col.saturating_sub(start.col),
+      token_type: u32::try_from(idx).expect("too many token types"),
+      token_modifiers_bitset: modifiers,
+    });
+  }
+  ret
+}
+
+/// The last semantic tokens we sent for each file, so we can send deltas.
+#[derive(Debug, Default)]
+pub(crate) struct Cache {
+  next_id: u64,
+  tokens: FxHashMap<Url, (String, Vec<lsp_types::SemanticToken>)>,
+}
+
+impl Cache {
+  /// Stores the tokens for the url, and returns them with a new result id.
+  pub(crate) fn full(
+    &mut self,
+    url: Url,
+    data: Vec<lsp_types::SemanticToken>,
+  ) -> lsp_types::SemanticTokens {
+    let result_id = self.next_result_id();
+    self.tokens.insert(url, (result_id.clone(), data.clone()));
+    lsp_types::SemanticTokens { result_id: Some(result_id), data }
+  }
+
+  /// Stores the tokens for the url, and returns the delta from the previous tokens if the previous
+  /// result id matches, else returns all of them.
+  pub(crate) fn delta(
+    &mut self,
+    url: Url,
+    previous_result_id: &str,
+    data: Vec<lsp_types::SemanticToken>,
+  ) -> lsp_types::SemanticTokensFullDeltaResult {
+    let edit = match self.tokens.get(&url) {
+      Some((id, old)) if id == previous_result_id => diff(old, &data),
+      _ => return lsp_types::SemanticTokensFullDeltaResult::Tokens(self.full(url, data)),
+    };
+    let result_id = self.next_result_id();
+    self.tokens.insert(url, (result_id.clone(), data));
+    lsp_types::SemanticTokensFullDeltaResult::TokensDelta(lsp_types::SemanticTokensDelta {
+      result_id: Some(result_id),
+      edits: edit.into_iter().collect(),
+    })
+  }
+
+  fn next_result_id(&mut self) -> String {
+    let ret = self.next_id.to_string();
+    self.next_id += 1;
+    ret
+  }
+}
+
+/// Returns the single edit to turn `old` into `new`, or `None` if they are the same.
+fn diff(
+  old: &[lsp_types::SemanticToken],
+  new: &[lsp_types::SemanticToken],
+) -> Option<lsp_types::SemanticTokensEdit> {
+  let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+  let max_suffix = old.len().min(new.len()) - prefix;
+  let suffix =
+    old.iter().rev().zip(new.iter().rev()).take(max_suffix).take_while(|(a, b)| a == b).count();
+  let delete_count = old.len() - prefix - suffix;
+  let insert = &new[prefix..new.len() - suffix];
+  if delete_count == 0 && insert.is_empty() {
+    return None;
+  }
+  // each token is encoded as 5 integers.
+  Some(lsp_types::SemanticTokensEdit {
+    start: u32::try_from(prefix * 5).expect("too many tokens"),
+    delete_count: u32::try_from(delete_count * 5).expect("too many tokens"),
+    data: Some(insert.to_vec()),
+  })
+}
@@ -0,0 +1,90 @@
+//! State shared between the thread that reads messages, the thread that handles them, and the
+//! worker thread that analyzes the input.
+//!
+//! One thread owns all of the state and handles every message in order. Requests see the analyses
+//! the worker finished last. See [`crate::worker`].
+//!
+//! Meanwhile, the reading thread tracks which requests are in flight, handles cancellation, and
+//! counts the messages that may change diagnostics. This lets the handling thread skip requests
+//! that were canceled while they waited, and lets both the handling thread and the worker skip work
+//! that newer edits would make stale.
+
+use fast_hash::FxHashMap;
+use lsp_server::RequestId;
+use lsp_types::notification::Notification as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[derive(Debug, Default)]
+pub(crate) struct Shared {
+  /// The ids of requests we have read but not yet responded to, and whether the client canceled
+  /// each.
+  in_flight: Mutex<FxHashMap<RequestId, bool>>,
+  /// How many edits we have read.
+  edits: AtomicUsize,
+}
+
+impl Shared {
+  pub(crate) fn start(&self, id: RequestId) {
+    self.in_flight.lock().unwrap().insert(id, false);
+  }
+
+  /// Cancels the request, if it is in flight. Cancellations of requests we already responded to, or
+  /// never read, are ignored.
+  pub(crate) fn cancel(&self, id: &RequestId) {
+    if let Some(canceled) = self.in_flight.lock().unwrap().get_mut(id) {
+      *canceled = true;
+    }
+  }
+
+  pub(crate) fn is_canceled(&self, id: &RequestId) -> bool {
+    self.in_flight.lock().unwrap().get(id).copied().unwrap_or(false)
+  }
+
+  /// Forgets the request, after we responded to it.
+  pub(crate) fn finish(&self, id: &RequestId) {
+    self.in_flight.lock().unwrap().remove(id);
+  }
+
+  pub(crate) fn add_edit(&self) {
+    self.edits.fetch_add(1, Ordering::SeqCst);
+  }
+
+  pub(crate) fn edits(&self) -> usize {
+    self.edits.load(Ordering::SeqCst)
+  }
+}
+
+/// Returns whether the notification with this method is an edit, i.e. whether it may change the
+/// diagnostics.
+pub(crate) fn is_edit(method: &str) -> bool {
+  [
+    lsp_types::notification::DidChangeWatchedFiles::METHOD,
+    lsp_types::notification::DidChangeWorkspaceFolders::METHOD,
+    lsp_types::notification::DidChangeConfiguration::METHOD,
+    lsp_types::notification::DidOpenTextDocument::METHOD,
+    lsp_types::notification::DidCloseTextDocument::METHOD,
+    lsp_types::notification::DidChangeTextDocument::METHOD,
+    lsp_types::notification::DidSaveTextDocument::METHOD,
+  ]
+  .contains(&method)
+}
+
+/// Returns the id of the request to cancel, if this is a cancel notification.
+pub(crate) fn cancel_id(notif: &lsp_server::Notification) -> Option<RequestId> {
+  if notif.method != lsp_types::notification::Cancel::METHOD {
+    return None;
+  }
+  let params = match serde_json::from_value::<lsp_types::CancelParams>(notif.params.clone()) {
+    Ok(x) => x,
+    Err(e) => {
+      log::error!("couldn't deserialize for cancel: {e}");
+      return None;
+    }
+  };
+  let ret = match params.id {
+    lsp_types::NumberOrString::Number(n) => RequestId::from(n),
+    lsp_types::NumberOrString::String(s) => RequestId::from(s),
+  };
+  Some(ret)
+}
@@ -1,24 +1,160 @@
 //! The main mutable state of the language server.
 
-use crate::cx::Cx;
+use crate::progress::Progress;
+use crate::{cx::Cx, diagnostics, init, semantic_tokens, worker::Worker};
 use fast_hash::FxHashSet;
 use lsp_types::Url;
 
-pub(crate) enum Mode {
-  /// We have a workspace root.
-  Root(Box<Root>),
-  /// We have no workspace root.
-  NoRoot,
-}
-
+/// A directory in a workspace folder that is analyzed separately from the others.
 pub(crate) struct Root {
   pub(crate) path: paths::CanonicalPathBuf,
   pub(crate) input: input::Input,
+  pub(crate) analysis: analysis::Analysis,
+  /// How many edits we had handled when we last updated a file in the analysis in place. See
+  /// [`St::update_one`].
+  pub(crate) updated: usize,
+}
+
+impl Root {
+  pub(crate) fn new(cx: &mut Cx, path: paths::CanonicalPathBuf) -> Root {
+    let input = cx.get_input(&path);
+    Root { path, input, analysis: cx.new_analysis(), updated: 0 }
+  }
 }
 
 pub struct St {
-  pub(crate) mode: Mode,
+  /// The workspace folders.
+  pub(crate) folders: Vec<paths::CanonicalPathBuf>,
+  /// The roots in the workspace folders. Empty if we have no workspace folders.
+  pub(crate) roots: Vec<Root>,
   pub(crate) cx: Cx,
-  pub(crate) analysis: analysis::Analysis,
+  /// The analysis for open SML files not in any root.
+  pub(crate) standalone: analysis::Analysis,
+  /// Like [`Root::updated`], for the standalone analysis.
+  pub(crate) standalone_updated: usize,
   pub(crate) has_diagnostics: FxHashSet<Url>,
+  /// The most recently computed diagnostics, or `None` if they may be out of date.
+  pub(crate) diagnostics: Option<diagnostics::Diagnostics>,
+  /// How many edits we have handled. See [`crate::shared`].
+  pub(crate) edits: usize,
+  /// Whether we skipped publishing diagnostics because of newer edits, which we have yet to handle.
+  pub(crate) publish_pending: bool,
+  /// Analyzes the input in the background. See [`crate::worker`].
+  pub(crate) worker: Worker,
+  /// The progress of the newest analysis, if the worker has yet to finish it.
+  pub(crate) analyzing: Option<Progress>,
+  /// Requests to pull diagnostics, which we answer when the worker finishes analyzing.
+  pub(crate) pulls: Vec<lsp_server::Request>,
+  pub(crate) semantic_tokens: semantic_tokens::Cache,
+}
+
+impl St {
+  /// Finds the roots in the workspace folders again, keeping the roots we already had.
+  pub(crate) fn update_roots(&mut self) {
+    let mut old = std::mem::take(&mut self.roots);
+    for folder in &self.folders {
+      for path in input::roots(&self.cx.fs, folder) {
+        // workspace folders may be nested in one another.
+        if self.roots.iter().any(|root| root.path.as_path() == path.as_path()) {
+          continue;
+        }
+        let root = match old.iter().position(|root| root.path.as_path() == path.as_path()) {
+          Some(idx) => old.swap_remove(idx),
+          None => Root::new(&mut self.cx, path),
+        };
+        self.roots.push(root);
+      }
+    }
+  }
+
+  /// Sets the options, which take effect the next time they are used.
+  ///
+  /// Whether to watch files cannot change after initialization.
+  pub(crate) fn set_options(&mut self, options: config::init::Options) {
+    let analysis_options = init::analysis_options(&options);
+    self.cx.options = options;
+    self.cx.analysis_options = analysis_options;
+    for root in &mut self.roots {
+      root.analysis.set_options(analysis_options);
+    }
+    self.standalone.set_options(analysis_options);
+    self.diagnostics = None;
+  }
+
+  /// Returns whether there are edits we have yet to handle.
+  pub(crate) fn is_behind(&self) -> bool {
+    self.cx.shared.edits() != self.edits
+  }
+
+  /// Returns the index of the root that owns the path, which is the innermost root that has the
+  /// path as a source.
+  pub(crate) fn owner(&self, path: paths::PathId) -> Option<usize> {
+    self
+      .roots
+      .iter()
+      .enumerate()
+      .filter(|(_, root)| root.input.sources.contains_key(&path))
+      .max_by_key(|(_, root)| root.path.as_path().components().count())
+      .map(|(idx, _)| idx)
+  }
+
+  /// Returns the innermost root that has the path as a group file.
+  pub(crate) fn group_root(&self, path: paths::PathId) -> Option<&Root> {
+    self
+      .roots
+      .iter()
+      .filter(|root| root.input.groups.contains_key(&path))
+      .max_by_key(|root| root.path.as_path().components().count())
+  }
+
+  /// Returns the innermost root whose directory contains the path.
+  pub(crate) fn dir_root(&self, path: &std::path::Path) -> Option<&Root> {
+    self
+      .roots
+      .iter()
+      .filter(|root| path.starts_with(root.path.as_path()))
+      .max_by_key(|root| root.path.as_path().components().count())
+  }
+
+  /// Returns the analysis for the path.
+  pub(crate) fn analysis(&self, path: paths::PathId) -> &analysis::Analysis {
+    match self.owner(path) {
+      Some(idx) => &self.roots[idx].analysis,
+      None => &self.standalone,
+    }
+  }
+
+  /// Updates only the file at the path, in the analysis for it, without waiting for the worker.
+  ///
+  /// Records how many edits we had handled, so we don't replace the analysis with one from the
+  /// worker that analyzed older input.
+  pub(crate) fn update_one(&mut self, path: paths::PathId) -> &analysis::Analysis {
+    match self.owner(path) {
+      Some(idx) => {
+        let root = &mut self.roots[idx];
+        root.analysis.update_one(&root.input, path);
+        root.updated = self.edits;
+        &root.analysis
+      }
+      None => {
+        let input = self.standalone_input();
+        if input.sources.contains_key(&path) {
+          self.standalone.update_one(&input, path);
+          self.standalone_updated = self.edits;
+        }
+        &self.standalone
+      }
+    }
+  }
+
+  /// Returns input with only the open SML files that no root owns, each in its own group.
+  pub(crate) fn standalone_input(&self) -> input::Input {
+    let mut ret = input::Input::default();
+    for (&path, contents) in &self.cx.open_files {
+      if self.owner(path).is_none() && self.cx.is_sml(path) {
+        ret.add_standalone(path, contents.clone());
+      }
+    }
+    ret
+  }
 }
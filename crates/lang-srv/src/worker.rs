@@ -0,0 +1,133 @@
+//! Analyze snapshots of the input on a background thread.
+//!
+//! Analyzing every root may take a while. So the handling thread sends the worker a snapshot of the
+//! input, and meanwhile keeps answering requests with the analyses the worker last finished. The
+//! worker checks for newer edits between roots, and stops early if there are any, since the
+//! handling thread will send a newer snapshot after handling them.
+
+use crate::progress;
+use crate::shared::Shared;
+use crossbeam_channel::{Receiver, Sender};
+use paths::PathMap;
+use std::sync::Arc;
+
+/// A snapshot of the input to analyze.
+struct Job {
+  id: usize,
+  /// How many edits the handling thread had handled when it made the snapshot.
+  edits: usize,
+  options: analysis::Options,
+  roots: Vec<(paths::CanonicalPathBuf, input::Input)>,
+  standalone: input::Input,
+}
+
+/// Something the worker sends back.
+pub(crate) enum Event {
+  /// A progress message, and optionally a percentage of the work done.
+  Report(String, Option<u32>),
+  /// The worker finished the job with the id, or stopped early if there is nothing.
+  Finished(usize, Option<Done>),
+}
+
+/// The analyses of a snapshot.
+pub(crate) struct Done {
+  /// How many edits the handling thread had handled when it made the snapshot.
+  pub(crate) edits: usize,
+  pub(crate) roots: Vec<(paths::CanonicalPathBuf, Analyzed)>,
+  pub(crate) standalone: Analyzed,
+}
+
+/// An analysis, and the diagnostics it computed.
+pub(crate) struct Analyzed {
+  pub(crate) analysis: analysis::Analysis,
+  pub(crate) diagnostics: PathMap<Vec<analysis::Diagnostic<text_pos::RangeUtf16>>>,
+}
+
+/// The handle the handling thread has on the worker.
+pub(crate) struct Worker {
+  jobs: Sender<Job>,
+  pub(crate) events: Receiver<Event>,
+  /// The id of the newest job.
+  pub(crate) newest: usize,
+}
+
+impl Worker {
+  pub(crate) fn spawn(std_basis: analysis::StdBasis, shared: Arc<Shared>) -> std::io::Result<Self> {
+    let (jobs, job_receiver) = crossbeam_channel::unbounded::<Job>();
+    let (event_sender, events) = crossbeam_channel::unbounded::<Event>();
+    std::thread::Builder::new()
+      .name("millet-analysis".to_owned())
+      .spawn(move || run(&std_basis, &shared, &job_receiver, &event_sender))?;
+    Ok(Self { jobs, events, newest: 0 })
+  }
+
+  /// Sends a snapshot of the input to analyze.
+  pub(crate) fn send(
+    &mut self,
+    edits: usize,
+    options: analysis::Options,
+    roots: Vec<(paths::CanonicalPathBuf, input::Input)>,
+    standalone: input::Input,
+  ) {
+    self.newest += 1;
+    let job = Job { id: self.newest, edits, options, roots, standalone };
+    if self.jobs.send(job).is_err() {
+      log::error!("the analysis thread stopped");
+    }
+  }
+}
+
+fn run(
+  std_basis: &analysis::StdBasis,
+  shared: &Shared,
+  jobs: &Receiver<Job>,
+  events: &Sender<Event>,
+) {
+  while let Ok(mut job) = jobs.recv() {
+    // only the newest snapshot matters.
+    while let Ok(newer) = jobs.try_recv() {
+      job = newer;
+    }
+    let id = job.id;
+    let done = analyze(std_basis, shared, events, job);
+    if done.is_none() {
+      log::info!("stopped analyzing stale input");
+    }
+    if events.send(Event::Finished(id, done)).is_err() {
+      break;
+    }
+  }
+}
+
+fn analyze(
+  std_basis: &analysis::StdBasis,
+  shared: &Shared,
+  events: &Sender<Event>,
+  job: Job,
+) -> Option<Done> {
+  let num_roots = job.roots.len();
+  let mut roots = Vec::with_capacity(num_roots);
+  for (idx, (path, input)) in job.roots.into_iter().enumerate() {
+    if shared.edits() != job.edits {
+      return None;
+    }
+    let message = format!("{}/{num_roots}: {}", idx + 1, progress::input_message(&input));
+    let percentage = u32::try_from(idx * 100 / num_roots).ok();
+    _ = events.send(Event::Report(message, percentage));
+    roots.push((path, Analyzed::new(std_basis, job.options, &input)));
+  }
+  if !job.standalone.sources.is_empty() {
+    let message = format!("standalone: {}", progress::input_message(&job.standalone));
+    _ = events.send(Event::Report(message, None));
+  }
+  let standalone = Analyzed::new(std_basis, job.options, &job.standalone);
+  Some(Done { edits: job.edits, roots, standalone })
+}
+
+impl Analyzed {
+  fn new(std_basis: &analysis::StdBasis, options: analysis::Options, input: &input::Input) -> Self {
+    let mut analysis = analysis::Analysis::new(std_basis.clone(), options);
+    let diagnostics = elapsed::log("get_many", || analysis.get_many(input));
+    Self { analysis, diagnostics }
+  }
+}
@@ -15,6 +15,8 @@ fn usage() {
 options:
   -h, --help
     show this help
+  --fix
+    apply the mechanical fixes for diagnostics to the SML files in place
   --unsafe-format
     WARNING: THE FORMATTER IS HIGHLY EXPERIMENTAL.
     IT MAY IRREVOCABLY DESTROY SOME OR ALL OF YOUR CODE.
@@ -42,6 +44,7 @@ fn run() -> usize {
     usage();
     return 0;
   }
+  let fix = args.contains("--fix");
   let format = args.contains("--unsafe-format");
   let root: std::path::PathBuf = match args.free_from_str() {
     Ok(x) => x,
@@ -59,7 +62,6 @@ fn run() -> usize {
     }
   };
   let mut store = paths::Store::new();
-  let inp = input::Input::new(&fs, &mut store, &root);
   let options = analysis::Options {
     lines: config::DiagnosticLines::One,
     ignore: config::init::DiagnosticsIgnore::AfterSyntax,
@@ -70,6 +72,8 @@ fn run() -> usize {
     },
   };
   let mut an = analysis::Analysis::new(analysis::StdBasis::full(), options);
+  let fix_errors = if fix { apply_fixes(&fs, &mut store, &root, &mut an) } else { 0 };
+  let inp = input::Input::new(&fs, &mut store, &root);
   let got = an.get_many_text_range(&inp);
   for err in &inp.errors {
     show_input_error(root.as_path(), err);
@@ -122,7 +126,60 @@ fn run() -> usize {
       }
     }
   }
-  format_errors + inp.errors.len() + got.values().map(Vec::len).sum::<usize>()
+  fix_errors + format_errors + inp.errors.len() + got.values().map(Vec::len).sum::<usize>()
+}
+
+/// The most times to re-analyze and fix the files. Fixes that overlap or touch are applied in
+/// later rounds, and applying fixes can uncover more.
+const MAX_FIX_ROUNDS: usize = 10;
+
+/// Applies the mechanical fixes for the diagnostics to the source files in place, re-analyzing them
+/// with the analysis each round. Returns the number of errors writing the files.
+fn apply_fixes(
+  fs: &paths::RealFileSystem,
+  store: &mut paths::Store,
+  root: &paths::CanonicalPathBuf,
+  an: &mut analysis::Analysis,
+) -> usize {
+  let mut errors = 0usize;
+  for _ in 0..MAX_FIX_ROUNDS {
+    let inp = input::Input::new(fs, store, root);
+    let got = an.get_many_text_range(&inp);
+    let mut changed = false;
+    for (path, ds) in got {
+      let Some(contents) = inp.sources.get(&path) else { continue };
+      let mut edits = Vec::<(text_size_util::TextRange, String)>::new();
+      for fix in ds.into_iter().filter_map(|d| d.fix) {
+        let overlaps = fix
+          .edits
+          .iter()
+          .any(|(a, _)| edits.iter().any(|(b, _)| a.start() <= b.end() && b.start() <= a.end()));
+        if !overlaps {
+          edits.extend(fix.edits);
+        }
+      }
+      if edits.is_empty() {
+        continue;
+      }
+      edits.sort_unstable_by_key(|(range, _)| std::cmp::Reverse(range.start()));
+      let mut contents = contents.clone();
+      for (range, text) in edits {
+        contents.replace_range(std::ops::Range::<usize>::from(range), text.as_str());
+      }
+      let path = store.get_path(path).as_path();
+      match std::fs::write(path, contents.as_str()) {
+        Ok(()) => changed = true,
+        Err(e) => {
+          show_input_error(path, &input::Error::from_io(path.to_owned(), e));
+          errors += 1;
+        }
+      }
+    }
+    if !changed {
+      break;
+    }
+  }
+  errors
 }
 
 fn show_input_error(root: &std::path::Path, e: &input::Error) {
@@ -315,7 +315,7 @@ fn get_source_file(
     bs: checked.info.basis().clone(),
   });
   let mut info = checked.info;
-  add_all_doc_comments(syntax.parse.root.syntax(), &syntax.lower, &mut info);
+  add_all_doc_comments(&syntax.parse.syntax(), &syntax.lower, &mut info);
   let mut file = SourceFile {
     syntax,
     statics_errors: checked.errors,
@@ -391,5 +391,5 @@ pub fn update_one(
   let checked =
     sml_statics::get(syms_tys, &sf.scope, mode, &sf.syntax.lower.arenas, &sf.syntax.lower.root);
   sf.info = checked.info;
-  add_all_doc_comments(sf.syntax.parse.root.syntax(), &sf.syntax.lower, &mut sf.info);
+  add_all_doc_comments(&sf.syntax.parse.syntax(), &sf.syntax.lower, &mut sf.info);
 }
@@ -120,7 +120,7 @@ where
       panic!("{name}: statics error: {e}");
     }
     let mut info = checked.info;
-    add_all_doc_comments(started.parse.root.syntax(), &low, &mut info);
+    add_all_doc_comments(&started.parse.syntax(), &low, &mut info);
     (name, info)
   });
   let info: FxHashMap<_, _> = iter.collect();
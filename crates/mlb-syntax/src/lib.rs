@@ -10,6 +10,31 @@ mod types;
 
 pub use types::{BasDec, BasExp, Error, NamesSeq, ParsedPath, PathKind, Result};
 
+use text_size_util::TextRange;
+use types::Token;
+
+/// The keywords of ML Basis files.
+pub const KEYWORDS: [&str; 12] = [
+  "and",
+  "ann",
+  "bas",
+  "basis",
+  "end",
+  "functor",
+  "in",
+  "let",
+  "local",
+  "open",
+  "signature",
+  "structure",
+];
+
+/// Returns the kind of the path, or `None` if ML Basis files may not contain it.
+#[must_use]
+pub fn path_kind(path: &std::path::Path) -> Option<PathKind> {
+  parse::path_kind(path)
+}
+
 /// Process the contents of a ML Basis file.
 ///
 /// # Errors
@@ -19,3 +44,43 @@ pub fn get(s: &str, env: &slash_var_path::Env) -> Result<BasDec> {
   let tokens = lex::get(s)?;
   parse::get(&tokens, env)
 }
+
+/// Returns the ranges of the blocks that end with `end` in the contents of a ML Basis file, like
+/// `local ... in ... end`.
+///
+/// A `bas ... end` block that is the right hand side of a `basis` binding starts at the `basis`
+/// keyword (or the `and` keyword).
+///
+/// # Errors
+///
+/// If the contents of the file could not be lexed.
+pub fn fold_ranges(s: &str) -> Result<Vec<TextRange>> {
+  let tokens = lex::get(s)?;
+  let mut starts = Vec::<TextRange>::new();
+  let mut ret = Vec::<TextRange>::new();
+  for (idx, token) in tokens.iter().enumerate() {
+    match token.val {
+      Token::Bas => {
+        let start = match idx.checked_sub(3).map(|start| &tokens[start..idx]) {
+          Some([kw, name, eq])
+            if matches!(kw.val, Token::Basis | Token::And)
+              && matches!(name.val, Token::Name(_))
+              && matches!(eq.val, Token::Eq) =>
+          {
+            kw.range
+          }
+          _ => token.range,
+        };
+        starts.push(start);
+      }
+      Token::Let | Token::Local | Token::Ann => starts.push(token.range),
+      Token::End => {
+        if let Some(start) = starts.pop() {
+          ret.push(TextRange::new(start.start(), token.range.end()));
+        }
+      }
+      _ => {}
+    }
+  }
+  Ok(ret)
+}
@@ -170,7 +170,7 @@ fn bas_dec_one(p: &mut Parser<'_>) -> Result<BasDecOne> {
   Ok(BasDecOne::Ok(ret))
 }
 
-fn path_kind(path: &Path) -> Option<PathKind> {
+pub(crate) fn path_kind(path: &Path) -> Option<PathKind> {
   let ret = match path.extension()?.to_str()? {
     "sml" | "sig" | "fun" => PathKind::Sml,
     "mlb" => PathKind::Mlb,
@@ -12,4 +12,4 @@ mod ty;
 mod util;
 
 pub use root::get;
-pub use util::{Error, Lower, Ptrs};
+pub use util::{Error, Fix, Lower, Ptrs};
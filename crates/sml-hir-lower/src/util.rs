@@ -211,6 +211,19 @@ impl fmt::Display for Disallowed {
   }
 }
 
+/// A mechanical fix for an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fix {
+  /// Remove the parentheses of the expression, pattern, or type at the range.
+  RemoveParens,
+  /// Remove the token at the range.
+  RemoveToken,
+  /// Simplify the `andalso`, `orelse`, or `if` expression at the range.
+  SimplifyBool,
+  /// Rewrite the `case` expression at the range into a `let` expression.
+  CaseToLet,
+}
+
 /// An error when lowering.
 #[derive(Debug)]
 pub struct Error {
@@ -271,6 +284,18 @@ impl Error {
     }
   }
 
+  /// Returns the mechanical fix for this, if there is one.
+  #[must_use]
+  pub fn fix(&self) -> Option<Fix> {
+    match self.kind {
+      ErrorKind::UnnecessaryParens => Some(Fix::RemoveParens),
+      ErrorKind::UnnecessarySemicolon | ErrorKind::Trailing(_) => Some(Fix::RemoveToken),
+      ErrorKind::ComplexBoolExp => Some(Fix::SimplifyBool),
+      ErrorKind::OneArmedCase => Some(Fix::CaseToLet),
+      _ => None,
+    }
+  }
+
   /// Returns the severity for this.
   #[must_use]
   pub fn severity(&self) -> Severity {
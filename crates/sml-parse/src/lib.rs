@@ -13,17 +13,40 @@ mod util;
 pub mod parser;
 
 use sml_syntax::ast::{AstNode as _, Root};
-use sml_syntax::{token::Token, SyntaxKind as SK};
+use sml_syntax::rowan::GreenNode;
+use sml_syntax::{token::Token, SyntaxKind as SK, SyntaxNode};
 
 /// The result of a parse.
+///
+/// This holds the green tree, which may be sent between threads, unlike the syntax tree of the
+/// root.
 #[derive(Debug)]
 pub struct Parse {
-  /// The root.
-  pub root: Root,
+  green: GreenNode,
   /// The errors encountered when parsing.
   pub errors: Vec<parser::Error>,
 }
 
+impl Parse {
+  /// Returns the root.
+  ///
+  /// # Panics
+  ///
+  /// If casting the root node to a Root failed (an internal error).
+  #[must_use]
+  pub fn root(&self) -> Root {
+    Root::cast(self.syntax()).unwrap()
+  }
+
+  /// Returns the syntax tree of the root.
+  ///
+  /// Every call makes a new syntax tree, so prefer to call this once and use the node it returns.
+  #[must_use]
+  pub fn syntax(&self) -> SyntaxNode {
+    SyntaxNode::new_root(self.green.clone())
+  }
+}
+
 /// Returns a parse of the tokens.
 ///
 /// # Panics
@@ -35,5 +58,7 @@ pub fn get(tokens: &[Token<'_, SK>], fe: &mut sml_fixity::Env) -> Parse {
   let mut sink = event_parse::rowan_sink::RowanSink::default();
   p.finish(&mut sink);
   let (node, errors) = sink.finish();
-  Parse { root: Root::cast(node).unwrap(), errors: errors.into_iter().map(parser::Error).collect() }
+  let root = Root::cast(node).unwrap();
+  let green = root.syntax().green().into_owned();
+  Parse { green, errors: errors.into_iter().map(parser::Error).collect() }
 }
@@ -2,8 +2,8 @@
 
 use crate::sym::{Sym, Syms};
 use crate::ty::{
-  BoundTyVarData, BoundTyVars, MetaTyVarDisplayData, RecordData, Ty, TyData, TyScheme, TyVarKind,
-  UnsolvedMetaTyVarKind,
+  BoundTyVar, BoundTyVarData, BoundTyVars, MetaTyVarDisplayData, RecordData, Ty, TyData, TyScheme,
+  TyVarKind, UnsolvedMetaTyVarKind,
 };
 use crate::{unify::Incompatible, St};
 use fmt_util::comma_seq;
@@ -14,7 +14,7 @@ impl Ty {
   #[must_use]
   pub fn display(self, st: &St, lines: config::DiagnosticLines) -> impl fmt::Display + '_ {
     TyDisplay {
-      cx: TyDisplayCx { bound_vars: None, st },
+      cx: TyDisplayCx { bound_vars: None, st, relative: None },
       ty: self,
       prec: TyPrec::Arrow,
       pretty: Pretty::from_diagnostic_lines(lines),
@@ -31,12 +31,63 @@ impl TyScheme {
     lines: config::DiagnosticLines,
   ) -> impl fmt::Display + 'a {
     TyDisplay {
-      cx: TyDisplayCx { bound_vars: Some(&self.bound_vars), st },
+      cx: TyDisplayCx { bound_vars: Some(&self.bound_vars), st, relative: None },
       ty: self.ty,
       prec: TyPrec::Arrow,
       pretty: Pretty::from_diagnostic_lines(lines),
     }
   }
+
+  /// Returns a value that displays this as SML source written inside the structure with the path
+  /// `prefix`, omitting the leading structure names that type names share with that path.
+  #[must_use]
+  pub fn display_relative<'a>(
+    &'a self,
+    st: &'a St,
+    lines: config::DiagnosticLines,
+    prefix: &'a [str_util::Name],
+  ) -> impl fmt::Display + 'a {
+    TyDisplay {
+      cx: TyDisplayCx { bound_vars: Some(&self.bound_vars), st, relative: Some(prefix) },
+      ty: self.ty,
+      prec: TyPrec::Arrow,
+      pretty: Pretty::from_diagnostic_lines(lines),
+    }
+  }
+
+  /// Returns a value that displays the type variables this binds followed by the name, as in the
+  /// head of a type or datatype declaration.
+  #[must_use]
+  pub fn display_head<'a>(&'a self, name: &'a str_util::Name) -> impl fmt::Display + 'a {
+    TyHeadDisplay { bound_vars: &self.bound_vars, name }
+  }
+}
+
+struct TyHeadDisplay<'a> {
+  bound_vars: &'a BoundTyVars,
+  name: &'a str_util::Name,
+}
+
+impl fmt::Display for TyHeadDisplay<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let iter = BoundTyVar::iter_for(self.bound_vars.iter()).map(|(bv, data)| match data {
+      BoundTyVarData::Kind(kind) => bv.src_name(matches!(kind, TyVarKind::Equality)).to_string(),
+      BoundTyVarData::Named(name) => name.to_string(),
+    });
+    match self.bound_vars.len() {
+      0 => {}
+      1 => {
+        comma_seq(f, iter)?;
+        f.write_str(" ")?;
+      }
+      _ => {
+        f.write_str("(")?;
+        comma_seq(f, iter)?;
+        f.write_str(") ")?;
+      }
+    }
+    self.name.fmt(f)
+  }
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -84,6 +135,8 @@ impl Pretty {
 struct TyDisplayCx<'a> {
   bound_vars: Option<&'a BoundTyVars>,
   st: &'a St,
+  /// if this is `Some`, the type is written as source inside the structure with this path.
+  relative: Option<&'a [str_util::Name]>,
 }
 
 struct TyDisplay<'a> {
@@ -108,8 +161,14 @@ impl fmt::Display for TyDisplay<'_> {
         match bv.index_into(vars) {
           BoundTyVarData::Kind(kind) => match kind {
             // NOTE this can clash with explicitly named ty vars, but currently they do not mix
-            TyVarKind::Regular => bv.name(false).fmt(f)?,
-            TyVarKind::Equality => bv.name(true).fmt(f)?,
+            TyVarKind::Regular | TyVarKind::Equality => {
+              let equality = matches!(kind, TyVarKind::Equality);
+              if self.cx.relative.is_some() {
+                bv.src_name(equality).fmt(f)?;
+              } else {
+                bv.name(equality).fmt(f)?;
+              }
+            }
             TyVarKind::Overloaded(ov) => ov.fmt(f)?,
           },
           BoundTyVarData::Named(name) => name.fmt(f)?,
@@ -196,7 +255,8 @@ impl fmt::Display for TyDisplay<'_> {
           }
           f.write_str(" ")?;
         }
-        SymDisplay { sym: data.sym, syms: &self.cx.st.syms }.fmt(f)?;
+        let prefix = self.cx.relative.unwrap_or_default();
+        SymDisplay { sym: data.sym, syms: &self.cx.st.syms, prefix }.fmt(f)?;
       }
       TyData::Fn(data) => {
         let needs_parens = self.prec > TyPrec::Arrow;
@@ -274,20 +334,29 @@ impl fmt::Display for RowDisplay<'_> {
 
 impl Sym {
   pub(crate) fn display(self, syms: &Syms) -> impl fmt::Display + '_ {
-    SymDisplay { sym: self, syms }
+    SymDisplay { sym: self, syms, prefix: &[] }
   }
 }
 
 struct SymDisplay<'a> {
   sym: Sym,
   syms: &'a Syms,
+  prefix: &'a [str_util::Name],
 }
 
 impl fmt::Display for SymDisplay<'_> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self.syms.get(self.sym) {
       None => f.write_str("exn"),
-      Some(sym_info) => sym_info.path.fmt(f),
+      Some(sym_info) => {
+        let path = &sym_info.path;
+        let common = path.prefix().iter().zip(self.prefix).take_while(|(a, b)| a == b).count();
+        for name in &path.prefix()[common..] {
+          name.fmt(f)?;
+          f.write_str(".")?;
+        }
+        path.last().fmt(f)
+      }
     }
   }
 }
@@ -320,7 +389,7 @@ pub fn record_meta_var<'a>(
   lines: config::DiagnosticLines,
 ) -> impl fmt::Display + 'a {
   RecordMetaVarDisplay {
-    cx: TyDisplayCx { bound_vars: None, st },
+    cx: TyDisplayCx { bound_vars: None, st, relative: None },
     rows,
     pretty: Pretty::from_diagnostic_lines(lines),
   }
@@ -40,6 +40,12 @@ impl Env {
     self.val_env.append(&mut other.val_env);
   }
 
+  /// Returns whether this has no structures, types, or values.
+  #[must_use]
+  pub fn is_empty(&self) -> bool {
+    self.str_env.is_empty() && self.ty_env.is_empty() && self.val_env.is_empty()
+  }
+
   /// Consolidates the env, making it use less memory.
   pub fn consolidate(&mut self) {
     self.str_env.consolidate();
@@ -4,13 +4,19 @@ use std::fmt;
 
 /// Returns a char iterator that when collected could be a name for a type variable.
 pub fn ty_var_name(equality: bool, idx: usize) -> TyVarName {
-  TyVarName { equality, idx }
+  TyVarName { tick: '?', equality, idx }
+}
+
+/// Like [`ty_var_name`], but the name is a valid type variable in source code.
+pub fn src_ty_var_name(equality: bool, idx: usize) -> TyVarName {
+  TyVarName { tick: '\'', equality, idx }
 }
 
 /// A char iterator for a type variable name.
 #[derive(Debug)]
 #[must_use]
 pub struct TyVarName {
+  tick: char,
   equality: bool,
   idx: usize,
 }
@@ -18,7 +24,7 @@ pub struct TyVarName {
 impl fmt::Display for TyVarName {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     let ticks = if self.equality { 2 } else { 1 };
-    for c in std::iter::repeat('?').take(ticks).chain(idx_to_name(self.idx)) {
+    for c in std::iter::repeat(self.tick).take(ticks).chain(idx_to_name(self.idx)) {
       write!(f, "{c}")?;
     }
     Ok(())
@@ -278,6 +278,10 @@ impl BoundTyVar {
     fmt_util::ty_var_name(equality, self.0.to_usize())
   }
 
+  pub(crate) fn src_name(self, equality: bool) -> fmt_util::TyVarName {
+    fmt_util::src_ty_var_name(equality, self.0.to_usize())
+  }
+
   pub(crate) fn iter_for<I, T>(xs: I) -> impl Iterator<Item = (Self, T)>
   where
     I: Iterator<Item = T>,
@@ -150,6 +150,7 @@ fn get_one(
     // @def(18)
     sml_hir::Dec::DatatypeCopy(name, path) => {
       let ty_info = get_ty_info(&cx.env, path);
+      st.info.insert_str_defs(dec.into(), path.prefix(), &ty_info.str_defs);
       for e in ty_info.disallow {
         st.err(dec, e.into());
       }
@@ -158,7 +159,7 @@ fn get_one(
           env.ty_env.insert(name.clone(), ty_info.clone());
           env.val_env.append(&mut ty_info.val_env.clone());
         }
-        Err(e) => st.err(dec, e.into()),
+        Err(e) => e.report(st, dec),
       }
     }
     // @def(19)
@@ -189,13 +190,14 @@ fn get_one(
           // @def(31)
           sml_hir::ExBind::Copy(name, path) => {
             let val_info = get_val_info(&cx.env, path);
+            st.info.insert_str_defs(dec.into(), path.prefix(), &val_info.str_defs);
             for e in val_info.disallow {
               st.err(dec, e.into());
             }
             let val_info = match val_info.val {
               Ok(x) => x,
               Err(e) => {
-                st.err(dec, e.into());
+                e.report(st, dec);
                 return;
               }
             };
@@ -232,12 +234,13 @@ fn get_one(
     sml_hir::Dec::Open(paths) => {
       for path in paths {
         let got_env = get_env(&cx.env, path.all_names());
+        st.info.insert_str_defs(dec.into(), path.all_names(), &got_env.str_defs);
         for e in got_env.disallow {
           st.err(dec, e.into());
         }
         match got_env.val {
           Ok(got_env) => env.append(&mut got_env.clone()),
-          Err(e) => st.err(dec, e.into()),
+          Err(e) => e.report(st, dec),
         }
       }
     }
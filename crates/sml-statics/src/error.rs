@@ -3,6 +3,8 @@
 mod non_exhaustive;
 mod suggestion;
 
+pub(crate) use suggestion::similar;
+
 use crate::pat_match::Pat;
 use diagnostic::{Code, Severity};
 use sml_statics_types::display::record_meta_var;
@@ -15,7 +17,7 @@ use std::fmt;
 pub(crate) enum ErrorKind {
   /// must be first here, but have the highest error code
   Unsupported(&'static str),
-  Undefined(Item, str_util::Name),
+  Undefined(Item, str_util::Name, Suggestions),
   Duplicate(Item, str_util::Name),
   Missing(Item, str_util::Name),
   Extra(Item, str_util::Name),
@@ -60,6 +62,17 @@ pub(crate) enum ErrorKind {
   ShadowInCaseWithSameTy(str_util::Name, TyScheme),
 }
 
+/// What may have been meant instead of an undefined name. These may be slow to compute, so we only
+/// do so for errors we report.
+#[derive(Debug, Default)]
+pub(crate) struct Suggestions {
+  /// The similar names in scope, most similar first.
+  pub(crate) similar: Vec<str_util::Name>,
+  /// The paths to the name in structures that define it, shallowest first, with the type schemes
+  /// of values.
+  pub(crate) qualified: Vec<(sml_path::Path, Option<TyScheme>)>,
+}
+
 struct ErrorKindDisplay<'a> {
   kind: &'a ErrorKind,
   st: &'a sml_statics_types::St,
@@ -70,10 +83,19 @@ impl fmt::Display for ErrorKindDisplay<'_> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self.kind {
       ErrorKind::Unsupported(s) => write!(f, "unsupported: {s}"),
-      ErrorKind::Undefined(item, name) => {
+      ErrorKind::Undefined(item, name, suggestions) => {
         write!(f, "undefined {item}: `{name}`")?;
-        if let Some(sug) = suggestion::get(name.as_str()) {
-          write!(f, " (did you mean `{sug}`?)")?;
+        let mut iter = suggestion::get(name.as_str())
+          .into_iter()
+          .chain(suggestions.similar.iter().map(str_util::Name::as_str))
+          .peekable();
+        if let Some(fst) = iter.next() {
+          write!(f, " (did you mean `{fst}`")?;
+          while let Some(sug) = iter.next() {
+            let sep = if iter.peek().is_some() { "," } else { " or" };
+            write!(f, "{sep} `{sug}`")?;
+          }
+          f.write_str("?)")?;
         }
         Ok(())
       }
@@ -224,6 +246,15 @@ impl fmt::Display for AppendArg {
   }
 }
 
+/// A mechanical fix for an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fix {
+  /// Rewrite the `case` on a `bool` into an `if`.
+  CaseToIf,
+  /// Replace the `fn` with the function it calls.
+  EtaReduce,
+}
+
 /// A statics error.
 #[derive(Debug)]
 pub struct Error {
@@ -248,6 +279,48 @@ impl Error {
     ErrorKindDisplay { kind: &self.kind, st, lines }
   }
 
+  /// Returns the patterns missing from a non-exhaustive `case` or binding, written as SML, if this
+  /// is such an error.
+  ///
+  /// If `fun_args` is `Some(n)`, the patterns are for the cases of a `fun` with `n` curried
+  /// arguments, so each is written as those arguments, or if one cannot be, this returns `None`.
+  #[must_use]
+  pub fn missing_pats(
+    &self,
+    st: &sml_statics_types::St,
+    fun_args: Option<usize>,
+  ) -> Option<Vec<String>> {
+    match &self.kind {
+      ErrorKind::NonExhaustiveCase(pats) | ErrorKind::NonExhaustiveBinding(pats) => {
+        non_exhaustive::sml(&st.syms, pats, fun_args)
+      }
+      _ => None,
+    }
+  }
+
+  /// Returns the undefined name and the names that may have been meant instead, most likely first,
+  /// if this is an error about an undefined name.
+  #[must_use]
+  pub fn undefined_suggestions(&self) -> Option<(&str_util::Name, Vec<&str>)> {
+    match &self.kind {
+      ErrorKind::Undefined(_, name, suggestions) => {
+        let iter = suggestions.similar.iter().map(str_util::Name::as_str);
+        Some((name, suggestion::get(name.as_str()).into_iter().chain(iter).collect()))
+      }
+      _ => None,
+    }
+  }
+
+  /// Returns the paths to the undefined name in structures that define it, shallowest first, with
+  /// the type schemes of values, if this is an error about an undefined name.
+  #[must_use]
+  pub fn undefined_qualified(&self) -> Option<&[(sml_path::Path, Option<TyScheme>)]> {
+    match &self.kind {
+      ErrorKind::Undefined(_, _, suggestions) => Some(suggestions.qualified.as_slice()),
+      _ => None,
+    }
+  }
+
   /// Return the code for this.
   ///
   /// No longer used:
@@ -257,7 +330,7 @@ impl Error {
   pub fn code(&self) -> Code {
     match self.kind {
       ErrorKind::Unsupported(_) => Code::n(5999),
-      ErrorKind::Undefined(_, _) => Code::n(5001),
+      ErrorKind::Undefined(_, _, _) => Code::n(5001),
       ErrorKind::Duplicate(_, _) => Code::n(5002),
       ErrorKind::Missing(_, _) => Code::n(5003),
       ErrorKind::Extra(_, _) => Code::n(5004),
@@ -302,6 +375,16 @@ impl Error {
     }
   }
 
+  /// Returns the mechanical fix for this, if there is one.
+  #[must_use]
+  pub fn fix(&self) -> Option<Fix> {
+    match self.kind {
+      ErrorKind::BoolCase => Some(Fix::CaseToIf),
+      ErrorKind::CanEtaReduce(_) => Some(Fix::EtaReduce),
+      _ => None,
+    }
+  }
+
   /// Returns the severity for this.
   #[must_use]
   pub fn severity(&self) -> Severity {
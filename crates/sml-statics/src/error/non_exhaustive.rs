@@ -27,6 +27,36 @@ pub(crate) fn get(
   Ok(())
 }
 
+/// Returns the patterns as SML, or if `fun_args` is `Some(n)`, as `n` curried arguments to a
+/// function. Returns `None` if a pattern for more than one argument cannot be split into them.
+pub(crate) fn sml(syms: &Syms, pats: &[Pat], fun_args: Option<usize>) -> Option<Vec<String>> {
+  pats
+    .iter()
+    .map(|pat| {
+      let n = match fun_args {
+        None => return Some(PatDisplay { pat, syms, prec: PatPrec::Min }.to_string()),
+        Some(1) => return Some(PatDisplay { pat, syms, prec: PatPrec::App }.to_string()),
+        Some(n) => n,
+      };
+      let con_pat = unwrap_non_or(pat);
+      match &con_pat.con {
+        Con::Any => Some(vec!["_"; n].join(" ")),
+        Con::Record { labels, allows_other: false } if labels.len() == n => {
+          let args: Option<Vec<_>> = (0..n)
+            .map(|idx| {
+              let idx = labels.iter().position(|lab| *lab == sml_hir::Lab::tuple(idx))?;
+              let pat = con_pat.args.get(idx)?;
+              Some(PatDisplay { pat, syms, prec: PatPrec::App }.to_string())
+            })
+            .collect();
+          Some(args?.join(" "))
+        }
+        _ => None,
+      }
+    })
+    .collect()
+}
+
 fn unwrap_non_or(pat: &Pat) -> &ConPat<Lang> {
   match &pat.raw {
     RawPat::Con(c) => c,
@@ -1,4 +1,4 @@
-//! See [`get`].
+//! Suggestions for names that may have been meant instead of undefined ones.
 
 /// Suggests a bit of syntax that may be similar to the input.
 pub(crate) fn get(s: &str) -> Option<&'static str> {
@@ -40,3 +40,55 @@ pub(crate) fn get(s: &str) -> Option<&'static str> {
   };
   Some(ret)
 }
+
+/// The most names to suggest.
+const MAX: usize = 3;
+
+/// Returns the names most similar to `name` among the `names`, most similar first.
+///
+/// Short names get no suggestions, since nearly every other short name is similar to them. We also
+/// never suggest symbolic names for alphanumeric ones, or vice versa.
+pub(crate) fn similar<'a, I>(name: &str, names: I) -> Vec<str_util::Name>
+where
+  I: IntoIterator<Item = &'a str_util::Name>,
+{
+  let max_dist = name.chars().count() / 3;
+  if max_dist == 0 {
+    return Vec::new();
+  }
+  let alpha = is_alpha(name);
+  let mut ret: Vec<_> = names
+    .into_iter()
+    .filter_map(|other| {
+      let other = other.as_str();
+      if is_alpha(other) != alpha {
+        return None;
+      }
+      let dist = edit_distance(name, other);
+      (dist <= max_dist).then_some((dist, other))
+    })
+    .collect();
+  ret.sort_unstable();
+  ret.dedup();
+  ret.into_iter().take(MAX).map(|(_, other)| str_util::Name::new(other)).collect()
+}
+
+fn is_alpha(s: &str) -> bool {
+  s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '\'')
+}
+
+/// Returns the Levenshtein distance between the strings, counting in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<_> = b.chars().collect();
+  let mut prev: Vec<_> = (0..=b.len()).collect();
+  let mut cur = vec![0usize; b.len() + 1];
+  for (i, ac) in a.chars().enumerate() {
+    cur[0] = i + 1;
+    for (j, &bc) in b.iter().enumerate() {
+      let sub = prev[j] + usize::from(ac != bc);
+      cur[j + 1] = sub.min(prev[j + 1] + 1).min(cur[j] + 1);
+    }
+    std::mem::swap(&mut prev, &mut cur);
+  }
+  prev[b.len()]
+}
@@ -44,12 +44,13 @@ fn get(st: &mut St<'_>, cfg: Cfg, cx: &Cx, ars: &sml_hir::Arenas, exp: sml_hir::
     // @def(2)
     sml_hir::Exp::Path(path) => {
       let val_info = get_val_info(&cx.env, path);
+      st.info.insert_str_defs(exp.into(), path.prefix(), &val_info.str_defs);
       for e in val_info.disallow {
         st.err(exp, e.into());
       }
       match val_info.val {
         Err(e) => {
-          st.err(exp, e.into());
+          e.report(st, exp);
           Ty::NONE
         }
         Ok(val_info) => {
@@ -59,6 +60,7 @@ fn get(st: &mut St<'_>, cfg: Cfg, cx: &Cx, ars: &sml_hir::Arenas, exp: sml_hir::
           if let Mode::Dynamics = st.info.mode {
             assert!(st.exp_id_statuses.insert(exp, val_info.id_status).is_none());
           }
+          st.info.entries.id_statuses.exp.insert(exp, val_info.id_status);
           ty_scheme = Some(val_info.ty_scheme.clone());
           defs.reserve(val_info.defs.len());
           for &def in &val_info.defs {
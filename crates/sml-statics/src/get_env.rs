@@ -1,21 +1,66 @@
 //! Getting information from environments.
 
-use crate::error::ErrorKind;
+use crate::error::{similar, ErrorKind, Suggestions};
+use crate::st::St;
 use sml_statics_types::info::{TyInfo, ValInfo};
-use sml_statics_types::{disallow::Disallow, env::Env, item::Item};
+use sml_statics_types::{def, disallow::Disallow, env::Env, item::Item};
 
 #[derive(Debug)]
-pub(crate) struct GetEnvResult<T> {
-  pub(crate) val: Result<T, UndefinedError>,
+pub(crate) struct GetEnvResult<'e, T> {
+  pub(crate) val: Result<T, UndefinedError<'e>>,
   pub(crate) disallow: Vec<DisallowError>,
+  /// the defs of the structures in the path, in order, up to where the lookup failed if it did.
+  pub(crate) str_defs: Vec<Option<def::Def>>,
 }
 
+/// An undefined name. This keeps the envs it was looked up in, so we can make suggestions for it if
+/// we report it, but not otherwise.
 #[derive(Debug)]
-pub(crate) struct UndefinedError(Item, str_util::Name);
+pub(crate) struct UndefinedError<'e> {
+  item: Item,
+  name: str_util::Name,
+  /// the env that does not have the name.
+  env: &'e Env,
+  /// the env the lookup started from, if the name was not qualified.
+  root: Option<&'e Env>,
+}
 
-impl From<UndefinedError> for ErrorKind {
-  fn from(val: UndefinedError) -> ErrorKind {
-    ErrorKind::Undefined(val.0, val.1)
+impl UndefinedError<'_> {
+  /// Reports this at the idx.
+  pub(crate) fn report<I>(self, st: &mut St<'_>, idx: I)
+  where
+    I: Into<sml_hir::Idx>,
+  {
+    st.err_undefined(idx, self.item, self.name.clone(), || self.suggestions());
+  }
+
+  fn suggestions(&self) -> Suggestions {
+    let name = &self.name;
+    let similar = match self.item {
+      Item::Struct => similar(name.as_str(), self.env.str_env.iter().map(|(name, _)| name)),
+      Item::Ty => similar(name.as_str(), self.env.ty_env.iter().map(|(name, _)| name)),
+      Item::Val => similar(name.as_str(), self.env.val_env.iter().map(|(name, _)| name)),
+      Item::TyVar | Item::Sig | Item::Functor => Vec::new(),
+    };
+    let qualified = match self.root {
+      None => Vec::new(),
+      Some(root) => qualified(root, name, |env| match self.item {
+        Item::Struct => env.str_env.get(name).is_some(),
+        Item::Ty => env.ty_env.get(name).is_some(),
+        Item::Val => env.val_env.get(name).is_some(),
+        Item::TyVar | Item::Sig | Item::Functor => false,
+      })
+      .into_iter()
+      .map(|path| {
+        let ty_scheme = match self.item {
+          Item::Val => get_val_info(root, &path).val.ok().map(|x| x.ty_scheme.clone()),
+          _ => None,
+        };
+        (path, ty_scheme)
+      })
+      .collect(),
+    };
+    Suggestions { similar, qualified }
   }
 }
 
@@ -29,27 +74,31 @@ impl From<DisallowError> for ErrorKind {
 }
 
 /// uses the `names` to traverse through the `StrEnv`s of successive `env`s.
-pub(crate) fn get_env<'e, 'n, I>(mut env: &'e Env, names: I) -> GetEnvResult<&'e Env>
+pub(crate) fn get_env<'e, 'n, I>(mut env: &'e Env, names: I) -> GetEnvResult<'e, &'e Env>
 where
   I: IntoIterator<Item = &'n str_util::Name>,
 {
   let mut disallow = Vec::<DisallowError>::new();
-  for name in names {
+  let mut str_defs = Vec::<Option<def::Def>>::new();
+  for (idx, name) in names.into_iter().enumerate() {
     env = match env.str_env.get(name) {
       None => {
-        return GetEnvResult { val: Err(UndefinedError(Item::Struct, name.clone())), disallow }
+        let root = (idx == 0).then_some(env);
+        let e = UndefinedError { item: Item::Struct, name: name.clone(), env, root };
+        return GetEnvResult { val: Err(e), disallow, str_defs };
       }
       Some(x) => x,
     };
+    str_defs.push(env.def);
     if let Some(d) = &env.disallow {
       disallow.push(DisallowError(Item::Struct, d.clone(), name.clone()));
     }
   }
-  GetEnvResult { val: Ok(env), disallow }
+  GetEnvResult { val: Ok(env), disallow, str_defs }
 }
 
 /// DOES include [`DisallowError`] from the [`TyInfo`]
-pub(crate) fn get_ty_info<'e>(env: &'e Env, path: &sml_path::Path) -> GetEnvResult<&'e TyInfo> {
+pub(crate) fn get_ty_info<'e>(env: &'e Env, path: &sml_path::Path) -> GetEnvResult<'e, &'e TyInfo> {
   get_ty_info_raw(env, path.prefix().iter(), path.last())
 }
 
@@ -58,18 +107,21 @@ pub(crate) fn get_ty_info_raw<'e, 'n, S>(
   env: &'e Env,
   prefix: S,
   last: &'n str_util::Name,
-) -> GetEnvResult<&'e TyInfo>
+) -> GetEnvResult<'e, &'e TyInfo>
 where
   S: IntoIterator<Item = &'n str_util::Name>,
 {
+  let mut prefix = prefix.into_iter().peekable();
+  let root = prefix.peek().is_none().then_some(env);
   let got_env = get_env(env, prefix);
   let mut disallow = got_env.disallow;
-  let ty_info = match got_env.val {
-    Ok(got_env) => got_env.ty_env.get(last),
-    Err(e) => return GetEnvResult { val: Err(e), disallow },
+  let str_defs = got_env.str_defs;
+  let got_env = match got_env.val {
+    Ok(x) => x,
+    Err(e) => return GetEnvResult { val: Err(e), disallow, str_defs },
   };
-  let val = match ty_info {
-    None => Err(UndefinedError(Item::Ty, last.clone())),
+  let val = match got_env.ty_env.get(last) {
+    None => Err(UndefinedError { item: Item::Ty, name: last.clone(), env: got_env, root }),
     Some(ty_info) => {
       if let Some(d) = &ty_info.disallow {
         disallow.push(DisallowError(Item::Ty, d.clone(), last.clone()));
@@ -77,19 +129,68 @@ where
       Ok(ty_info)
     }
   };
-  GetEnvResult { val, disallow }
+  GetEnvResult { val, disallow, str_defs }
 }
 
 /// DOES NOT include [`DisallowError`] from the [`ValInfo`]
-pub(crate) fn get_val_info<'e>(env: &'e Env, path: &sml_path::Path) -> GetEnvResult<&'e ValInfo> {
+pub(crate) fn get_val_info<'e>(
+  env: &'e Env,
+  path: &sml_path::Path,
+) -> GetEnvResult<'e, &'e ValInfo> {
   let got_env = get_env(env, path.prefix());
   let disallow = got_env.disallow;
-  let val_info = match got_env.val {
-    Ok(got_env) => got_env.val_env.get(path.last()),
-    Err(e) => return GetEnvResult { val: Err(e), disallow },
+  let str_defs = got_env.str_defs;
+  let got_env = match got_env.val {
+    Ok(x) => x,
+    Err(e) => return GetEnvResult { val: Err(e), disallow, str_defs },
   };
-  let val = val_info.ok_or_else(|| UndefinedError(Item::Val, path.last().clone()));
-  GetEnvResult { val, disallow }
+  let last = path.last();
+  let root = path.prefix().is_empty().then_some(env);
+  let val = got_env.val_env.get(last).ok_or_else(|| UndefinedError {
+    item: Item::Val,
+    name: last.clone(),
+    env: got_env,
+    root,
+  });
+  GetEnvResult { val, disallow, str_defs }
+}
+
+/// Returns the paths to the name in the structures nested anywhere in the env that define it per
+/// `has`, shallowest first.
+fn qualified<F>(env: &Env, name: &str_util::Name, has: F) -> Vec<sml_path::Path>
+where
+  F: Fn(&Env) -> bool,
+{
+  let mut ret = Vec::<sml_path::Path>::new();
+  qualified_go(env, name, &has, &mut Vec::new(), &mut ret);
+  ret.sort_by(|a, b| {
+    let a_prefix = a.prefix().iter().map(str_util::Name::as_str);
+    let b_prefix = b.prefix().iter().map(str_util::Name::as_str);
+    a.prefix().len().cmp(&b.prefix().len()).then_with(|| a_prefix.cmp(b_prefix))
+  });
+  ret
+}
+
+fn qualified_go<F>(
+  env: &Env,
+  name: &str_util::Name,
+  has: &F,
+  prefix: &mut Vec<str_util::Name>,
+  ac: &mut Vec<sml_path::Path>,
+) where
+  F: Fn(&Env) -> bool,
+{
+  for (str_name, env) in env.str_env.iter() {
+    if env.disallow.is_some() {
+      continue;
+    }
+    prefix.push(str_name.clone());
+    if has(env) {
+      ac.push(sml_path::Path::new(prefix.iter().cloned(), name.clone()));
+    }
+    qualified_go(env, name, has, prefix, ac);
+    prefix.pop();
+  }
 }
 
 pub(crate) fn get_mut_env<'e, 'n, I>(
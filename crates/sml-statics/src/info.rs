@@ -1,8 +1,10 @@
 //! See [`Info`].
 
 use crate::basis::Bs;
-use fast_hash::FxHashSet;
+use fast_hash::{FxHashMap, FxHashSet};
 use sml_hir::la_arena;
+use sml_statics_types::info::{IdStatus, IdStatusMap};
+use sml_statics_types::sym::Sym;
 use sml_statics_types::ty::{Ty, TyData, TyScheme};
 use sml_statics_types::util::ty_syms;
 use sml_statics_types::{def, env::Env, mode::Mode};
@@ -102,11 +104,43 @@ impl TyEntries {
   }
 }
 
+#[derive(Debug, Default, Clone)]
+pub(crate) struct IdStatuses {
+  pub(crate) exp: IdStatusMap<sml_hir::Exp>,
+  pub(crate) pat: IdStatusMap<sml_hir::Pat>,
+}
+
+impl IdStatuses {
+  fn get(&self, idx: sml_hir::Idx) -> Option<IdStatus> {
+    match idx {
+      sml_hir::Idx::Exp(idx) => self.exp.get(idx).copied(),
+      sml_hir::Idx::Pat(idx) => self.pat.get(idx).copied(),
+      _ => None,
+    }
+  }
+}
+
+/// A map from the def of a specification (or signature) to the defs of the things that implement it
+/// (or ascribe to it).
+pub(crate) type Impls = FxHashMap<def::Def, FxHashSet<def::Def>>;
+
+/// A map from a structure ascribed to a signature to the specifications it is missing, with types
+/// from the instantiated signature.
+pub(crate) type Missing = FxHashMap<sml_hir::Idx, Env>;
+
+/// A map from an index with paths to the defs of the structures in the paths, keyed by the names of
+/// the path up to and including the structure.
+pub(crate) type StrDefs = FxHashMap<sml_hir::Idx, Vec<(Vec<str_util::Name>, def::Def)>>;
+
 #[derive(Debug, Default, Clone)]
 pub(crate) struct Entries {
   pub(crate) defs: Defs,
   pub(crate) docs: Docs,
   pub(crate) tys: TyEntries,
+  pub(crate) id_statuses: IdStatuses,
+  pub(crate) impls: Impls,
+  pub(crate) missing: Missing,
+  pub(crate) str_defs: StrDefs,
 }
 
 /// Information about HIR indices.
@@ -146,18 +180,60 @@ impl Info {
     Some(ty_entry.to_string())
   }
 
+  /// Returns the most general type scheme for this index if it has one, else its type.
+  #[must_use]
+  pub fn get_ty_scheme(&self, idx: sml_hir::Idx) -> Option<TyScheme> {
+    let ty_entry = self.entries.tys.get(idx)?;
+    let ret = match &ty_entry.ty_scheme {
+      Some(ty_scheme) => ty_scheme.clone(),
+      None => TyScheme::zero(ty_entry.ty),
+    };
+    Some(ret)
+  }
+
   /// Returns documentation for this index.
   #[must_use]
   pub fn get_doc(&self, idx: sml_hir::Idx) -> Option<&str> {
     self.entries.docs.get(idx)
   }
 
+  /// Returns the specifications missing from the structure ascribed to a signature at the idx, with
+  /// types from the instantiated signature.
+  #[must_use]
+  pub fn get_missing(&self, idx: sml_hir::Idx) -> Option<&Env> {
+    self.entries.missing.get(&idx)
+  }
+
   /// Returns the definition sites of the idx.
   #[must_use]
   pub fn get_defs(&self, idx: sml_hir::Idx) -> FxHashSet<def::Def> {
     self.entries.defs.get(idx)
   }
 
+  /// Returns the definition site of the structure that the names, which start a path at the idx,
+  /// refer to there.
+  #[must_use]
+  pub fn get_str_def(&self, idx: sml_hir::Idx, names: &[str_util::Name]) -> Option<def::Def> {
+    let str_defs = self.entries.str_defs.get(&idx)?;
+    str_defs.iter().find_map(|(ns, def)| (ns.as_slice() == names).then_some(*def))
+  }
+
+  /// Records the defs of the structures that the names, which start a path at the idx, refer to.
+  pub(crate) fn insert_str_defs<'n, I>(
+    &mut self,
+    idx: sml_hir::Idx,
+    names: I,
+    defs: &[Option<def::Def>],
+  ) where
+    I: IntoIterator<Item = &'n str_util::Name>,
+  {
+    let names: Vec<_> = names.into_iter().take(defs.len()).cloned().collect();
+    for (len, def) in (1..=names.len()).zip(defs) {
+      let Some(def) = *def else { continue };
+      self.entries.str_defs.entry(idx).or_default().push((names[..len].to_vec(), def));
+    }
+  }
+
   /// Returns the definition site of the type for the idx.
   #[must_use]
   pub fn get_ty_defs(
@@ -203,6 +279,27 @@ impl Info {
     Some(ret)
   }
 
+  /// Returns the kind of symbol the value at this index is, if it is a path expression or pattern.
+  #[must_use]
+  pub fn get_val_symbol_kind(
+    &self,
+    st: &sml_statics_types::St,
+    idx: sml_hir::Idx,
+  ) -> Option<sml_namespace::SymbolKind> {
+    let id_status = self.entries.id_statuses.get(idx)?;
+    let ty_entry = self.entries.tys.get(idx)?;
+    Some(sml_symbol_kind::get_with_ty(&st.tys, id_status, ty_entry.ty))
+  }
+
+  /// Returns whether the type of the index is a `ref` type.
+  #[must_use]
+  pub fn is_ref_ty(&self, st: &sml_statics_types::St, idx: sml_hir::Idx) -> bool {
+    self.entries.tys.get(idx).map_or(false, |ty_entry| match st.tys.data(ty_entry.ty) {
+      TyData::Con(data) => data.sym == Sym::REF,
+      _ => false,
+    })
+  }
+
   /// Returns the symbols for this file.
   ///
   /// You also have to pass down the `path` that this `Info` is for. It's slightly odd, but we
@@ -248,6 +345,12 @@ impl Info {
     self.entries.defs.with_def(def)
   }
 
+  /// Returns the defs of the things in this file that implement the specification or signature with
+  /// this def.
+  pub fn get_impls(&self, def: def::Def) -> impl Iterator<Item = def::Def> + '_ {
+    self.entries.impls.get(&def).into_iter().flatten().copied()
+  }
+
   /// Returns a string representation of a type annotation for the pattern.
   #[must_use]
   pub fn show_pat_ty_annot(
@@ -25,7 +25,7 @@ pub mod basis;
 pub mod info;
 pub mod path_order;
 
-pub use error::Error;
+pub use error::{Error, Fix};
 
 /// The result of statics.
 #[derive(Debug)]
@@ -85,6 +85,7 @@ fn get_(
     sml_hir::Pat::Con(path, argument) => {
       let argument = argument.map(|x| get(st, cfg, ars, cx, ve, x));
       let val_info = get_val_info(&cx.env, path);
+      st.info.insert_str_defs(pat_idx.into(), path.prefix(), &val_info.str_defs);
       for e in val_info.disallow {
         st.err(pat_idx, e.into());
       }
@@ -100,12 +101,13 @@ fn get_(
         if let Mode::Dynamics = st.info.mode {
           assert!(st.pat_id_statuses.insert(pat_idx, IdStatus::Val).is_none());
         }
+        st.info.entries.id_statuses.pat.insert(pat_idx, IdStatus::Val);
         return Some(PatRet { pm_pat: Pat::zero(Con::Any, pat), ty, ty_scheme, defs });
       }
       let val_info = match val_info.val {
         Ok(x) => x,
         Err(e) => {
-          st.err(pat_idx, e.into());
+          e.report(st, pat_idx);
           return None;
         }
       };
@@ -115,6 +117,7 @@ fn get_(
       if let Mode::Dynamics = st.info.mode {
         assert!(st.pat_id_statuses.insert(pat_idx, val_info.id_status).is_none());
       }
+      st.info.entries.id_statuses.pat.insert(pat_idx, val_info.id_status);
       let variant_name = match &val_info.id_status {
         IdStatus::Val => {
           st.err(pat_idx, ErrorKind::PatValIdStatus);
@@ -1,7 +1,7 @@
 //! See [`St`].
 
 use crate::compatible;
-use crate::error::{Error, ErrorKind};
+use crate::error::{Error, ErrorKind, Suggestions};
 use crate::info::Info;
 use crate::pat_match::{self, Pat};
 use fast_hash::FxHashSet;
@@ -58,7 +58,7 @@ impl<'a> St<'a> {
     I: Into<sml_hir::Idx>,
   {
     match (self.info.mode, &kind) {
-      (Mode::PathOrder, ErrorKind::Undefined(Item::Struct | Item::Sig | Item::Functor, _))
+      (Mode::PathOrder, ErrorKind::Undefined(Item::Struct | Item::Sig | Item::Functor, _, _))
       | (Mode::Regular(_) | Mode::BuiltinLib(_) | Mode::Dynamics, _) => {
         self.errors.push(Error { idx: idx.into(), kind });
       }
@@ -66,6 +66,20 @@ impl<'a> St<'a> {
     }
   }
 
+  /// Reports an undefined name, with the suggestions from `f` if we will show the error.
+  pub(crate) fn err_undefined<I, F>(&mut self, idx: I, item: Item, name: str_util::Name, f: F)
+  where
+    I: Into<sml_hir::Idx>,
+    F: FnOnce() -> Suggestions,
+  {
+    let suggestions = match self.info.mode {
+      Mode::Regular(_) | Mode::BuiltinLib(_) | Mode::Dynamics => f(),
+      // only used to order the paths.
+      Mode::PathOrder => Suggestions::default(),
+    };
+    self.err(idx, ErrorKind::Undefined(item, name, suggestions));
+  }
+
   pub(crate) fn insert_bind(&mut self, idx: sml_hir::Idx, pat: Pat, want: Ty) {
     if self.info.mode.is_path_order() {
       return;
@@ -8,7 +8,7 @@ mod sharing_ty;
 mod ty_con_paths;
 mod where_ty;
 
-use crate::error::{ErrorKind, FunctorSugarUser};
+use crate::error::{similar, ErrorKind, FunctorSugarUser, Suggestions};
 use crate::get_env::{get_env, get_ty_info};
 use crate::util::{ins_check_name, ins_no_dupe};
 use crate::{basis::Bs, config::Cfg, dec, st::St, ty};
@@ -17,7 +17,7 @@ use sml_statics_types::env::{Env, FunEnv, FunSig, Sig, SigEnv, StrEnv, TyNameSet
 use sml_statics_types::info::{IdStatus, TyEnv, TyInfo, ValInfo};
 use sml_statics_types::sym::{Equality, StartedSym, SymTyInfo, SymValEnv, SymsMarker};
 use sml_statics_types::ty::{BoundTyVarData, Ty, TyData, TyScheme, TyVarSrc, Tys};
-use sml_statics_types::{generalize, item::Item, mode::Mode, overload, util::n_ary_con};
+use sml_statics_types::{def, generalize, item::Item, mode::Mode, overload, util::n_ary_con};
 
 pub(crate) fn get(
   st: &mut St<'_>,
@@ -203,6 +203,7 @@ fn get_str_exp(
     // @def(51)
     sml_hir::StrExp::Path(path) => {
       let got_env = get_env(&bs.env, path.all_names());
+      st.info.insert_str_defs(str_exp.into(), path.all_names(), &got_env.str_defs);
       for e in got_env.disallow {
         st.err(str_exp, e.into());
       }
@@ -213,7 +214,7 @@ fn get_str_exp(
           }
           ac.append(&mut got_env.clone());
         }
-        Err(e) => st.err(str_exp, e.into()),
+        Err(e) => e.report(st, str_exp),
       }
     }
     // @def(52), @def(53)
@@ -232,6 +233,20 @@ fn get_str_exp(
           instance::env_of_sig(st, idx, &mut subst, &str_exp_env, &sig);
           realize::get_env(&mut st.syms_tys.tys, &subst, &mut to_add);
           enrich::get_env(st, idx, &str_exp_env, &to_add);
+          let mut missing = enrich::missing(&str_exp_env, &sig.env);
+          if !missing.is_empty() {
+            // give the types the missing specs would declare fresh syms, as if the structure
+            // declared them.
+            let unrealized: TyNameSet =
+              sig.ty_names.iter().copied().filter(|&sym| !subst.contains(sym)).collect();
+            gen_fresh_syms(st, &mut subst, &unrealized);
+            realize::get_env(&mut st.syms_tys.tys, &subst, &mut missing);
+            st.info.entries.missing.insert(idx, missing);
+          }
+          if let (Some(sig_def), Some(str_def)) = (sig_exp_name_def(st, ars, *sig_exp), st.def(idx))
+          {
+            st.info.entries.impls.entry(sig_def).or_default().insert(str_def);
+          }
         }
         Mode::BuiltinLib(_) => {
           // @test(hover::doc::std_basis_structure)
@@ -274,7 +289,10 @@ fn get_str_exp(
     // @def(54)
     sml_hir::StrExp::App(fun_name, arg_str_exp, flavor) => {
       let Some(fun_sig) = bs.fun_env.get(fun_name) else {
-        st.err(str_exp, ErrorKind::Undefined(Item::Functor, fun_name.clone()));
+        st.err_undefined(str_exp, Item::Functor, fun_name.clone(), || Suggestions {
+          similar: similar(fun_name.as_str(), bs.fun_env.iter().map(|(name, _)| name)),
+          qualified: Vec::new(),
+        });
         return;
       };
       if let Some(d) = &fun_sig.disallow {
@@ -345,7 +363,10 @@ fn get_sig_exp(
     // @def(63)
     sml_hir::SigExp::Name(name) => {
       let Some(sig) = bs.sig_env.get(name) else {
-        st.err(sig_exp, ErrorKind::Undefined(Item::Sig, name.clone()));
+        st.err_undefined(sig_exp, Item::Sig, name.clone(), || Suggestions {
+          similar: similar(name.as_str(), bs.sig_env.iter().map(|(name, _)| name)),
+          qualified: Vec::new(),
+        });
         return None;
       };
       if let Some(d) = &sig.disallow {
@@ -385,6 +406,22 @@ fn get_sig_exp(
   }
 }
 
+/// Returns the def of the signature named by the signature expression, looking through `where`.
+fn sig_exp_name_def(
+  st: &St<'_>,
+  ars: &sml_hir::Arenas,
+  mut sig_exp: sml_hir::SigExpIdx,
+) -> Option<def::Def> {
+  loop {
+    let idx = sig_exp?;
+    match &ars.sig_exp[idx] {
+      sml_hir::SigExp::Spec(_) => return None,
+      sml_hir::SigExp::Name(_) => return st.info.entries.defs.sig_exp.get(idx).copied(),
+      sml_hir::SigExp::Where(inner, _) => sig_exp = *inner,
+    }
+  }
+}
+
 fn gen_fresh_syms(st: &mut St<'_>, subst: &mut realize::TyRealization, ty_names: &TyNameSet) {
   let mut ac = Vec::<(StartedSym, SymTyInfo, Equality)>::new();
   for &sym in ty_names {
@@ -496,6 +533,7 @@ fn get_spec_one(
     // @def(72)
     sml_hir::Spec::DatatypeCopy(name, path) => {
       let ty_info = get_ty_info(&bs.env, path);
+      st.info.insert_str_defs(spec.into(), path.prefix(), &ty_info.str_defs);
       for e in ty_info.disallow {
         st.err(spec, e.into());
       }
@@ -512,7 +550,7 @@ fn get_spec_one(
             }
           }
         }
-        Err(e) => st.err(spec, e.into()),
+        Err(e) => e.report(st, spec),
       }
     }
     // @def(73), @def(83)
@@ -564,13 +602,14 @@ fn get_spec_one(
             .iter()
             .filter_map(|path| {
               let ty_con_paths = ty_con_paths::get(&inner_env, path);
+              st.info.insert_str_defs(spec.into(), path.all_names(), &ty_con_paths.str_defs);
               for e in ty_con_paths.disallow {
                 st.err(spec, e.into());
               }
               match ty_con_paths.val {
                 Ok(x) => Some((path, x)),
                 Err(e) => {
-                  st.err(spec, e.into());
+                  e.report(st, spec);
                   None
                 }
               }
@@ -7,9 +7,12 @@
 use crate::compatible::{eq_ty_fn, generalizes};
 use crate::{error::ErrorKind, st::St};
 use sml_statics_types::info::{IdStatus, TyInfo, ValInfo};
-use sml_statics_types::{env::Env, item::Item};
+use sml_statics_types::{def, env::Env, item::Item};
 
 pub(crate) fn get_env(st: &mut St<'_>, idx: sml_hir::Idx, general: &Env, specific: &Env) {
+  if let (Some(spec), Some(imp)) = (specific.def, general.def) {
+    add_impls(st, [spec], [imp]);
+  }
   for (name, specific) in specific.str_env.iter() {
     match general.str_env.get(name) {
       Some(general) => get_env(st, idx, general, specific),
@@ -18,18 +21,65 @@ pub(crate) fn get_env(st: &mut St<'_>, idx: sml_hir::Idx, general: &Env, specifi
   }
   for (name, specific) in specific.ty_env.iter() {
     match general.ty_env.get(name) {
-      Some(general) => get_ty_info(st, idx, general.clone(), specific.clone()),
+      Some(general) => {
+        add_impls(st, specific.def, general.def);
+        get_ty_info(st, idx, general.clone(), specific.clone());
+      }
       None => st.err(idx, ErrorKind::Missing(Item::Ty, name.clone())),
     }
   }
   for (name, specific) in specific.val_env.iter() {
     match general.val_env.get(name) {
-      Some(general) => get_val_info(st, idx, general, specific, name),
+      Some(general) => {
+        add_impls(st, specific.defs.iter().copied(), general.defs.iter().copied());
+        get_val_info(st, idx, general, specific, name);
+      }
       None => st.err(idx, ErrorKind::Missing(Item::Val, name.clone())),
     }
   }
 }
 
+/// Returns the parts of the specific env that the general env does not have at all. For structures
+/// both envs have, this has only the parts missing from the general structure, if any.
+pub(crate) fn missing(general: &Env, specific: &Env) -> Env {
+  let mut ret = Env::default();
+  for (name, specific) in specific.str_env.iter() {
+    match general.str_env.get(name) {
+      Some(general) => {
+        let nested = missing(general, specific);
+        if !nested.is_empty() {
+          ret.str_env.insert(name.clone(), nested);
+        }
+      }
+      None => {
+        ret.str_env.insert(name.clone(), specific.clone());
+      }
+    }
+  }
+  for (name, specific) in specific.ty_env.iter() {
+    if general.ty_env.get(name).is_none() {
+      ret.ty_env.insert(name.clone(), specific.clone());
+    }
+  }
+  for (name, specific) in specific.val_env.iter() {
+    if general.val_env.get(name).is_none() {
+      ret.val_env.insert(name.clone(), specific.clone());
+    }
+  }
+  ret
+}
+
+/// Records that the general things implement the specific ones.
+fn add_impls<S, G>(st: &mut St<'_>, specific: S, general: G)
+where
+  S: IntoIterator<Item = def::Def>,
+  G: IntoIterator<Item = def::Def> + Clone,
+{
+  for spec in specific {
+    st.info.entries.impls.entry(spec).or_default().extend(general.clone());
+  }
+}
+
 fn get_ty_info(st: &mut St<'_>, idx: sml_hir::Idx, mut general: TyInfo, specific: TyInfo) {
   eq_ty_fn(st, idx, specific.ty_scheme, general.ty_scheme.clone());
   if specific.val_env.is_empty() {
@@ -15,6 +15,11 @@ impl TyRealization {
     self.0.clear();
   }
 
+  /// Returns whether this maps `sym` to anything.
+  pub(crate) fn contains(&self, sym: Sym) -> bool {
+    self.0.contains_key(&sym)
+  }
+
   /// Inserts the mapping from `sym` to `ty_scheme`.
   ///
   /// Callers **must** ensure `sym` has the same arity as `ty_scheme`.
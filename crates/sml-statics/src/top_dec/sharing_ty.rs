@@ -16,6 +16,7 @@ pub(crate) fn get(
   let mut ac = None::<SharingTyScheme>;
   let syms = paths.iter().filter_map(|path| {
     let ty_info = get_ty_info(inner_env, path);
+    st.info.insert_str_defs(idx, path.prefix(), &ty_info.str_defs);
     for e in ty_info.disallow {
       st.err(idx, e.into());
     }
@@ -4,14 +4,14 @@ use crate::get_env::{get_env, GetEnvResult};
 use fast_hash::FxHashSet;
 use sml_statics_types::env::Env;
 
-pub(crate) fn get(env: &Env, path: &sml_path::Path) -> GetEnvResult<FxHashSet<sml_path::Path>> {
+pub(crate) fn get(env: &Env, path: &sml_path::Path) -> GetEnvResult<'_, FxHashSet<sml_path::Path>> {
   let got_env = get_env(env, path.all_names());
   let val = got_env.val.map(|env| {
     let mut ty_cons = FxHashSet::<sml_path::Path>::default();
     go(&mut Vec::new(), &mut ty_cons, env);
     ty_cons
   });
-  GetEnvResult { val, disallow: got_env.disallow }
+  GetEnvResult { val, disallow: got_env.disallow, str_defs: got_env.str_defs }
 }
 
 fn go(prefix: &mut Vec<str_util::Name>, ac: &mut FxHashSet<sml_path::Path>, env: &Env) {
@@ -27,24 +27,26 @@ pub(crate) fn get(
     }
     sml_hir::WhereKind::Structure(lhs, rhs) => {
       let lhs_ty_cons = ty_con_paths::get(inner_env, lhs);
+      st.info.insert_str_defs(idx, lhs.all_names(), &lhs_ty_cons.str_defs);
       for e in lhs_ty_cons.disallow {
         st.err(idx, e.into());
       }
       let lhs_ty_cons = match lhs_ty_cons.val {
         Ok(x) => x,
         Err(e) => {
-          st.err(idx, e.into());
+          e.report(st, idx);
           return;
         }
       };
       let rhs_ty_cons = ty_con_paths::get(&bs.env, rhs);
+      st.info.insert_str_defs(idx, rhs.all_names(), &rhs_ty_cons.str_defs);
       for e in rhs_ty_cons.disallow {
         st.err(idx, e.into());
       }
       let rhs_ty_cons = match rhs_ty_cons.val {
         Ok(x) => x,
         Err(e) => {
-          st.err(idx, e.into());
+          e.report(st, idx);
           return;
         }
       };
@@ -63,7 +65,7 @@ pub(crate) fn get(
             let ty_scheme = ty_info.ty_scheme.clone();
             get_where_type(st, idx, marker, inner_env, &lhs, ty_scheme, false);
           }
-          Err(e) => st.err(idx, e.into()),
+          Err(e) => e.report(st, idx),
         }
       }
     }
@@ -82,13 +84,14 @@ fn get_where_type(
   emit_cannot_realize: bool,
 ) {
   let ty_info = get_ty_info(inner_env, path);
+  st.info.insert_str_defs(idx, path.prefix(), &ty_info.str_defs);
   for e in ty_info.disallow {
     st.err(idx, e.into());
   }
   let path_ty_scheme = match ty_info.val {
     Ok(x) => &x.ty_scheme,
     Err(e) => {
-      st.err(idx, e.into());
+      e.report(st, idx);
       return;
     }
   };
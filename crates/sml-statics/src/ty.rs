@@ -1,6 +1,7 @@
 //! Checking types.
 
-use crate::{error::ErrorKind, util::record};
+use crate::error::{ErrorKind, Suggestions};
+use crate::util::record;
 use crate::{get_env::get_ty_info, info::TyEntry, st::St};
 use sml_statics_types::ty::{Ty, TyData, TyScheme, TyVarSrc};
 use sml_statics_types::{def, env::Cx, item::Item, util::apply_bv};
@@ -33,7 +34,7 @@ pub(crate) fn get(
     // @def(44)
     sml_hir::Ty::Var(v) => match cx.fixed.get(v) {
       None => {
-        st.err(ty, ErrorKind::Undefined(Item::TyVar, v.as_name().clone()));
+        st.err_undefined(ty, Item::TyVar, v.as_name().clone(), Suggestions::default);
         Ty::NONE
       }
       Some(fv) => {
@@ -58,6 +59,7 @@ pub(crate) fn get(
     // @def(46)
     sml_hir::Ty::Con(arguments, path) => {
       let ty_info = get_ty_info(&cx.env, path);
+      st.info.insert_str_defs(ty.into(), path.prefix(), &ty_info.str_defs);
       for e in ty_info.disallow {
         st.err(ty, e.into());
       }
@@ -80,7 +82,7 @@ pub(crate) fn get(
           }
         }
         Err(e) => {
-          st.err(ty, e.into());
+          e.report(st, ty);
           Ty::NONE
         }
       }
@@ -3,15 +3,22 @@
 #![deny(clippy::pedantic, missing_debug_implementations, missing_docs, rust_2018_idioms)]
 
 use sml_statics_types::info::{IdStatus, ValInfo};
-use sml_statics_types::ty::{TyData, Tys};
+use sml_statics_types::ty::{Ty, TyData, Tys};
 
 /// Gets the symbol kind.
 #[must_use]
 pub fn get(tys: &Tys, val_info: &ValInfo) -> sml_namespace::SymbolKind {
-  match val_info.id_status {
+  get_with_ty(tys, val_info.id_status, val_info.ty_scheme.ty)
+}
+
+/// Gets the symbol kind from the id status and the type, which may be the type of a particular use
+/// of the value.
+#[must_use]
+pub fn get_with_ty(tys: &Tys, id_status: IdStatus, ty: Ty) -> sml_namespace::SymbolKind {
+  match id_status {
     IdStatus::Con => sml_namespace::SymbolKind::Constructor,
     IdStatus::Exn(_) => sml_namespace::SymbolKind::Exception,
-    IdStatus::Val => match tys.data(val_info.ty_scheme.ty) {
+    IdStatus::Val => match tys.data(ty) {
       TyData::Fn(_) => sml_namespace::SymbolKind::Function,
       _ => sml_namespace::SymbolKind::Value,
     },
@@ -42,3 +42,20 @@ fn custom_node_range(node: SyntaxNode) -> Option<rowan::TextRange> {
 pub fn node_range(node: &SyntaxNode) -> rowan::TextRange {
   custom_node_range(node.clone()).unwrap_or_else(|| node.text_range())
 }
+
+/// Returns whether the token is trivia, like whitespace or a comment.
+pub fn is_trivia(token: &SyntaxToken) -> bool {
+  matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::BlockComment)
+}
+
+/// Returns the range of the node without leading or trailing trivia, and the text in that range.
+pub fn trimmed(node: &SyntaxNode) -> Option<(rowan::TextRange, rowan::SyntaxText)> {
+  let mut tokens = node
+    .descendants_with_tokens()
+    .filter_map(rowan::NodeOrToken::into_token)
+    .filter(|tok| !is_trivia(tok));
+  let first = tokens.next()?;
+  let end = tokens.last().map_or(first.text_range().end(), |tok| tok.text_range().end());
+  let range = rowan::TextRange::new(first.text_range().start(), end);
+  Some((range, node.text().slice(range - node.text_range().start())))
+}
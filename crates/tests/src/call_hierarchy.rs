@@ -0,0 +1,97 @@
+//! Tests for call hierarchy.
+
+use crate::check::one_file;
+
+enum Dir {
+  Incoming,
+  Outgoing,
+}
+
+/// Asserts that the calls in the direction for the function at the cursor in the single SML file
+/// are to or from the items with the names, with the given number of call sites each.
+#[track_caller]
+fn check(s: &str, dir: Dir, want: &[(&str, usize)]) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let item = an.prepare_call_hierarchy(path.wrap(file.cursor().start)).expect("no item");
+  let pos = item.range.path.wrap(item.selection_range.start);
+  let calls = match dir {
+    Dir::Incoming => an.incoming_calls(pos),
+    Dir::Outgoing => an.outgoing_calls(pos),
+  };
+  let got: Vec<_> =
+    calls.expect("no calls").iter().map(|x| (x.item.name.as_str(), x.ranges.len())).collect();
+  assert_eq!(want, got.as_slice());
+}
+
+#[test]
+fn incoming() {
+  check(
+    r#"
+fun f x = x + 1
+(** ^ cursor *)
+fun g x = f (f x)
+fun h x = g x + f x
+"#,
+    Dir::Incoming,
+    &[("g", 2), ("h", 1)],
+  );
+}
+
+#[test]
+fn outgoing() {
+  check(
+    r#"
+fun f x = x + 1
+fun g x = f (f x)
+fun h x = g x + f x
+(** ^ cursor *)
+"#,
+    Dir::Outgoing,
+    &[("f", 1), ("g", 1)],
+  );
+}
+
+#[test]
+fn recursive() {
+  check(
+    r#"
+fun fact 0 = 1
+(** ^ cursor *)
+  | fact n = n * fact (n - 1)
+"#,
+    Dir::Incoming,
+    &[("fact", 1)],
+  );
+}
+
+#[test]
+fn nested() {
+  check(
+    r#"
+fun f x = x
+fun g x =
+(** ^ cursor *)
+  let
+    fun h y = f y
+  in
+    h x
+  end
+"#,
+    Dir::Outgoing,
+    &[("h", 1)],
+  );
+}
+
+#[test]
+fn val_fn() {
+  check(
+    r#"
+val f = fn x => x
+val y = f 3
+(**     ^ cursor *)
+"#,
+    Dir::Incoming,
+    &[("y", 1)],
+  );
+}
@@ -1,10 +1,11 @@
 //! Test infra.
 
 mod expect;
-mod input;
 mod reason;
 mod show;
 
+pub(crate) mod input;
+pub(crate) mod one_file;
 pub(crate) mod raw;
 
 use diagnostic::Severity;
@@ -30,7 +30,7 @@ impl File {
 }
 
 /// See [`get_one`].
-const COMMENT_START: &str = "(**";
+pub(crate) const COMMENT_START: &str = "(**";
 
 /// Parses expectation comments from a line of text. The line will be the following in order:
 ///
@@ -49,7 +49,7 @@ const COMMENT_START: &str = "(**";
 /// If so, this returns `Some((line, col_range, msg))`, else returns `None`.
 ///
 /// Note the arrows might be a little wonky with non-ascii.
-fn get_one(line_n: usize, line_s: &str) -> Option<(Region, Expect)> {
+pub(crate) fn get_one(line_n: usize, line_s: &str) -> Option<(Region, Expect)> {
   let (before, inner) = line_s.split_once(COMMENT_START)?;
   let (inner, _) = inner.split_once("*)")?;
   let non_space_idx = inner.find(|c| c != ' ')?;
@@ -127,6 +127,12 @@ impl Expect {
     if let Some(msg) = msg.strip_prefix("exact: ") {
       return Self { kind: Kind::Exact, msg: msg.to_owned() };
     }
+    if msg == "cursor" {
+      return Self { kind: Kind::Cursor, msg: String::new() };
+    }
+    if let Some(msg) = msg.strip_prefix("mark: ") {
+      return Self { kind: Kind::Mark, msg: msg.to_owned() };
+    }
     Self { kind: Kind::Contains, msg: msg.to_owned() }
   }
 }
@@ -152,6 +158,10 @@ pub(crate) enum Kind {
   Exact,
   /// There should be an error that contains the message.
   Contains,
+  /// This is where the cursor is, for the tests of an editor feature.
+  Cursor,
+  /// An editor feature should report the message here, for the tests of that feature.
+  Mark,
 }
 
 impl fmt::Display for Kind {
@@ -169,6 +179,8 @@ impl fmt::Display for Kind {
       }
       Kind::Exact => f.write_str("exact"),
       Kind::Contains => f.write_str("contains"),
+      Kind::Cursor => f.write_str("cursor"),
+      Kind::Mark => f.write_str("mark"),
     }
   }
 }
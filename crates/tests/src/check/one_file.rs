@@ -0,0 +1,109 @@
+//! Analyzing a single SML file, for tests of the features that work on one.
+
+use crate::check::{expect, input, raw};
+use text_pos::{PositionUtf16, RangeUtf16};
+
+type Diagnostics = Vec<analysis::Diagnostic<RangeUtf16>>;
+
+/// Returns the analysis after analyzing the single SML file, the path of that file, and its
+/// diagnostics.
+pub(crate) fn get_with_diagnostics(s: &str) -> (analysis::Analysis, paths::PathId, Diagnostics) {
+  let (input, store) = input::get(raw::one_file_fs(s));
+  let mut an = analysis::Analysis::new(analysis::StdBasis::minimal(), analysis::Options::default());
+  let mut got = an.get_many(&input);
+  let path = *input
+    .sources
+    .keys()
+    .find(|&&p| store.get_path(p).as_path().ends_with("f.sml"))
+    .expect("no source file");
+  let diagnostics = got.remove(&path).unwrap_or_default();
+  (an, path, diagnostics)
+}
+
+/// Returns the analysis after analyzing the single SML file, and the path of that file.
+pub(crate) fn get(s: &str) -> (analysis::Analysis, paths::PathId) {
+  let (an, path, _) = get_with_diagnostics(s);
+  (an, path)
+}
+
+/// Returns the text after applying the edits, which do not overlap.
+pub(crate) fn apply_edits(s: &str, mut edits: Vec<(RangeUtf16, String)>) -> String {
+  let pos_db = text_pos::PositionDb::new(s);
+  edits.sort_unstable_by_key(|(range, _)| std::cmp::Reverse(range.start));
+  let mut ret = s.to_owned();
+  for (range, new_text) in edits {
+    let range = pos_db.text_range_utf16(range).expect("no range");
+    ret.replace_range(std::ops::Range::<usize>::from(range), new_text.as_str());
+  }
+  ret
+}
+
+/// A single SML file with expectation comments, as for [`crate::check::check`], that mark where the
+/// cursor is with `cursor`, and what the feature should report with `mark: `.
+///
+/// A mark with an inexact arrow, like `+`, points at the start of its line.
+///
+/// The lines with only an expectation comment are removed from the text, so they do not change
+/// what the feature reports. The ranges are in the text without them.
+pub(crate) struct Marked {
+  /// The text without the expectation comments.
+  pub(crate) text: String,
+  cursor: Option<RangeUtf16>,
+  marks: Vec<(RangeUtf16, String)>,
+}
+
+impl Marked {
+  #[track_caller]
+  pub(crate) fn new(s: &str) -> Self {
+    let mut text = String::with_capacity(s.len());
+    let mut removed = Vec::<u32>::new();
+    let mut expects = Vec::<(expect::Region, expect::Expect)>::new();
+    for (line_n, line_s) in s.split_inclusive('\n').enumerate() {
+      let only_comment = line_s.trim_start().starts_with(expect::COMMENT_START);
+      match expect::get_one(line_n, line_s) {
+        Some(x) if only_comment => {
+          removed.push(u32::try_from(line_n).expect("too many lines"));
+          expects.push(x);
+        }
+        _ => text.push_str(line_s),
+      }
+    }
+    let mut ret = Self { text, cursor: None, marks: Vec::new() };
+    for (region, expect) in expects {
+      let (line, col_start, col_end) = match region {
+        expect::Region::Exact { line, col_start, col_end } => (line, col_start, col_end),
+        expect::Region::Line(line) => {
+          assert!(matches!(expect.kind, expect::Kind::Mark), "{region}: inexact cursor");
+          (line, 0, 0)
+        }
+      };
+      assert!(!removed.contains(&line), "{region}: points at an expectation comment");
+      let above = removed.iter().filter(|&&n| n < line).count();
+      let line = line - u32::try_from(above).expect("too many lines");
+      let range = RangeUtf16 {
+        start: PositionUtf16 { line, col: col_start },
+        end: PositionUtf16 { line, col: col_end },
+      };
+      match expect.kind {
+        expect::Kind::Cursor => {
+          assert!(ret.cursor.replace(range).is_none(), "more than one cursor")
+        }
+        expect::Kind::Mark => ret.marks.push((range, expect.msg)),
+        kind => panic!("{region}: not a cursor or mark: {kind}"),
+      }
+    }
+    ret.marks.sort_by_key(|(range, _)| (range.start.line, range.start.col));
+    ret
+  }
+
+  /// Returns the range the cursor points at.
+  #[track_caller]
+  pub(crate) fn cursor(&self) -> RangeUtf16 {
+    self.cursor.expect("no cursor")
+  }
+
+  /// Returns the range and message of each mark, in order.
+  pub(crate) fn marks(&self) -> &[(RangeUtf16, String)] {
+    &self.marks
+  }
+}
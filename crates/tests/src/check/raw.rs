@@ -221,7 +221,11 @@ where
             ck.reasons.push(reason::Reason::MismatchedCompletions(path.wrap(region), want, got));
           }
         }
-        expect::Kind::Exact | expect::Kind::Contains => {}
+        // the tests of editor features check cursors and marks themselves. see `one_file`.
+        expect::Kind::Exact
+        | expect::Kind::Contains
+        | expect::Kind::Cursor
+        | expect::Kind::Mark => {}
       }
     }
   }
@@ -55,7 +55,9 @@ fn try_region(
       expect::Kind::Hover
       | expect::Kind::Def
       | expect::Kind::Use
-      | expect::Kind::Completions { .. } => Ok(false),
+      | expect::Kind::Completions { .. }
+      | expect::Kind::Cursor
+      | expect::Kind::Mark => Ok(false),
       expect::Kind::Exact => {
         if exp.msg == got {
           Ok(true)
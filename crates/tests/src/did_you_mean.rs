@@ -0,0 +1,55 @@
+//! Tests for the code actions to replace undefined names.
+
+use crate::check::one_file;
+
+/// Asserts that the only replacement for the undefined name at the cursor in the single SML file is
+/// the mark.
+#[track_caller]
+fn check_similar(s: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let got = an.did_you_mean(path.wrap(file.cursor().start));
+  let [(range, new_text)] = got.as_slice() else { panic!("wrong number of fixes: {got:?}") };
+  let [(want_range, want_text)] = file.marks() else { panic!("not one mark") };
+  assert_eq!(want_text, new_text);
+  assert_eq!((want_range.start, want_range.end), (range.start, range.end));
+}
+
+#[test]
+fn similar() {
+  check_similar(
+    r#"
+structure Str = struct val length = 3 end
+(**         vvvvvv mark: length *)
+val _ = Str.lenght
+(**          ^ cursor *)
+"#,
+  );
+}
+
+#[test]
+fn similar_ty_con() {
+  check_similar(
+    r#"
+(**               vvvvv mark: option *)
+val _ : int optin optin = NONE
+(**               ^ cursor *)
+"#,
+  );
+}
+
+#[test]
+fn qualify_by_ty() {
+  let file = one_file::Marked::new(
+    r#"
+structure A = struct fun f (x : string) = x end
+structure B = struct structure C = struct fun f (x : int) = x end end
+val _ = f 3
+(**     ^ cursor *)
+"#,
+  );
+  let (an, path) = one_file::get(&file.text);
+  let got: Vec<_> =
+    an.qualify(path.wrap(file.cursor().start)).into_iter().map(|(_, new_text)| new_text).collect();
+  assert_eq!(["B.C.f", "A.f"].as_slice(), got.as_slice());
+}
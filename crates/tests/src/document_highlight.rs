@@ -0,0 +1,102 @@
+//! Tests for document highlight.
+
+use crate::check::one_file;
+
+/// Asserts that the highlights for the item at the cursor in the single SML file start at the marks,
+/// with the kinds in the marks.
+#[track_caller]
+fn check(s: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let got: Vec<_> = an
+    .document_highlights(path.wrap(file.cursor().start))
+    .expect("no highlights")
+    .into_iter()
+    .map(|h| {
+      let kind = match h.kind {
+        analysis::DocumentHighlightKind::Read => "read",
+        analysis::DocumentHighlightKind::Write => "write",
+      };
+      (h.range.start, kind)
+    })
+    .collect();
+  let want: Vec<_> =
+    file.marks().iter().map(|(range, kind)| (range.start, kind.as_str())).collect();
+  assert_eq!(want, got);
+}
+
+#[test]
+fn val() {
+  check(
+    r#"
+val x = 3
+(** ^ mark: write *)
+(**     v cursor *)
+val y = x +
+(**     ^ mark: read *)
+    x
+(** ^ mark: read *)
+"#,
+  );
+}
+
+#[test]
+fn assign() {
+  check(
+    r#"
+(** v cursor *)
+val r = ref 0
+(** ^ mark: write *)
+(**           v mark: read *)
+val _ = r := !r + 1
+(**     ^ mark: write *)
+"#,
+  );
+}
+
+#[test]
+fn fun() {
+  check(
+    r#"
+fun f 0 = 0
+(** ^ mark: write *)
+(**       v mark: read *)
+  | f n = f (n - 1)
+(** ^ mark: write *)
+(**     v cursor *)
+val _ = f 3
+(**     ^ mark: read *)
+"#,
+  );
+}
+
+#[test]
+fn structure() {
+  check(
+    r#"
+structure S = struct val x = 3 end
+(**       ^ mark: write *)
+(**     v cursor *)
+val _ = S.x
+(**     ^ mark: read *)
+"#,
+  );
+}
+
+#[test]
+fn shadowed_structure() {
+  check(
+    r#"
+structure S = struct val x = 1 end
+(**       ^ mark: write *)
+local
+  structure S = struct val x = 2 end
+in
+  val _ = S.x
+end
+(**     v cursor *)
+val _ = S.x
+(**     ^ mark: read *)
+"#,
+  );
+}
@@ -0,0 +1,123 @@
+//! Tests for the code action to extract an expression into a `val`.
+
+use crate::check::one_file;
+
+/// Asserts that extracting the expression selected by the cursor in the single SML file results in
+/// the wanted file.
+#[track_caller]
+fn check(s: &str, top_level: bool, want: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let edits = an.extract_val(path.wrap(file.cursor()), top_level).expect("no extraction");
+  let got = one_file::apply_edits(&file.text, edits);
+  assert_eq!(want, got);
+}
+
+#[test]
+fn let_exp() {
+  check(
+    r#"
+fun f x = (x + 1) * 2
+(**       ^^^^^^^ cursor *)
+"#,
+    false,
+    r#"
+fun f x = let val extracted = (x + 1) in extracted * 2 end
+"#,
+  );
+}
+
+#[test]
+fn let_exp_in_arm() {
+  check(
+    r#"
+val g = fn x => [x + 1, 3]
+(**              ^^^^^ cursor *)
+"#,
+    false,
+    r#"
+val g = fn x => let val extracted = x + 1 in [extracted, 3] end
+"#,
+  );
+}
+
+#[test]
+fn let_exp_in_if() {
+  check(
+    r#"
+fun f x = if x = 0 then 0 else 10 div x + 1
+(**                            ^^^^^^^^ cursor *)
+"#,
+    false,
+    r#"
+fun f x = if x = 0 then 0 else let val extracted = 10 div x in extracted + 1 end
+"#,
+  );
+}
+
+#[test]
+fn let_exp_in_while() {
+  check(
+    r#"
+fun f r = while !r > 0 do r := !r - 1
+(**                            ^^^^^^ cursor *)
+"#,
+    false,
+    r#"
+fun f r = while !r > 0 do let val extracted = !r - 1 in r := extracted end
+"#,
+  );
+}
+
+#[test]
+fn top_level_no_params() {
+  check(
+    r#"
+val y = 3
+fun f x = (x, [y, 2])
+(**           ^^^^^^ cursor *)
+"#,
+    true,
+    r#"
+val y = 3
+val extracted = [y, 2]
+fun f x = (x, extracted)
+"#,
+  );
+}
+
+#[test]
+fn top_level_expansive() {
+  check(
+    r#"
+val y = 3
+fun f x = x + y * 2
+(**           ^^^^^ cursor *)
+"#,
+    true,
+    r#"
+val y = 3
+fun extracted () = y * 2
+fun f x = x + extracted ()
+"#,
+  );
+}
+
+#[test]
+fn top_level_params() {
+  check(
+    r#"
+val y = 3
+fun f x =
+  let val z = 4 in x + y * z end
+(**                ^^^^^^^^^ cursor *)
+"#,
+    true,
+    r#"
+val y = 3
+fun extracted x z = x + y * z
+fun f x =
+  let val z = 4 in extracted x z end
+"#,
+  );
+}
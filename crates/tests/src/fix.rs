@@ -0,0 +1,80 @@
+//! Tests for the mechanical fixes attached to diagnostics.
+
+use crate::check::one_file;
+
+/// Returns the single SML file after applying the fixes for all of its diagnostics.
+fn fix(s: &str) -> String {
+  let (_, _, diagnostics) = one_file::get_with_diagnostics(s);
+  let edits: Vec<_> =
+    diagnostics.into_iter().filter_map(|d| d.fix).flat_map(|fix| fix.edits).collect();
+  one_file::apply_edits(s, edits)
+}
+
+#[test]
+fn parens() {
+  let s = r#"
+fun f x = x
+val _ = f(1)
+"#;
+  let want = r#"
+fun f x = x
+val _ = f 1
+"#;
+  assert_eq!(want, fix(s));
+}
+
+#[test]
+fn sep() {
+  let s = r#"
+val _ = let val x = 1; in [x, x,] end
+"#;
+  let want = r#"
+val _ = let val x = 1 in [x, x] end
+"#;
+  assert_eq!(want, fix(s));
+}
+
+#[test]
+fn complex_bool() {
+  let s = r#"
+fun f (x : bool) = x
+val a = f true andalso true
+val b = if f false then false else true
+val c = if f true then true else f false
+"#;
+  let want = r#"
+fun f (x : bool) = x
+val a = f true
+val b = not (f false)
+val c = f true orelse f false
+"#;
+  assert_eq!(want, fix(s));
+}
+
+#[test]
+fn case() {
+  let s = r#"
+fun f (x : bool) = x
+val a = case f true of true => 1 | false => 2
+val b = case 3 of y => y
+"#;
+  let want = r#"
+fun f (x : bool) = x
+val a = if f true then 1 else 2
+val b = let val y = 3 in y end
+"#;
+  assert_eq!(want, fix(s));
+}
+
+#[test]
+fn eta_reduce() {
+  let s = r#"
+fun g (x : int) = x
+val h = fn x => g x
+"#;
+  let want = r#"
+fun g (x : int) = x
+val h = g
+"#;
+  assert_eq!(want, fix(s));
+}
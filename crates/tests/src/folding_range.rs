@@ -0,0 +1,155 @@
+//! Tests for folding ranges.
+
+use crate::check::one_file;
+use std::collections::BTreeMap;
+
+/// Asserts that the folding ranges in the single SML file span the lines of the marks.
+#[track_caller]
+fn check(s: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let ranges = an.folding_ranges(path).expect("no folding ranges");
+  check_ranges(ranges, &file);
+}
+
+/// Asserts that the folding ranges in the group file span the lines of the marks.
+#[track_caller]
+fn check_group(file_name: &str, s: &str) {
+  let file = one_file::Marked::new(s);
+  let ranges = analysis::group_folding_ranges(std::path::Path::new(file_name), &file.text)
+    .expect("no folding ranges");
+  check_ranges(ranges, &file);
+}
+
+/// Each folding range is marked twice with the same message: once on its first line, and once on
+/// its last line.
+#[track_caller]
+fn check_ranges(ranges: Vec<analysis::FoldingRange>, file: &one_file::Marked) {
+  let mut lines = BTreeMap::<&str, Vec<u32>>::new();
+  for (range, msg) in file.marks() {
+    lines.entry(msg.as_str()).or_default().push(range.start.line);
+  }
+  let mut want: Vec<_> = lines
+    .into_iter()
+    .map(|(msg, lines)| match lines[..] {
+      [start, end] => (start, end),
+      _ => panic!("{msg}: not marked exactly twice"),
+    })
+    .collect();
+  want.sort_unstable();
+  let mut got: Vec<_> =
+    ranges.into_iter().map(|x| (x.range.start.line, x.range.end.line)).collect();
+  got.sort_unstable();
+  assert_eq!(want, got);
+}
+
+#[test]
+fn struct_sig() {
+  check(
+    r#"
+signature SIG = sig
+(** + mark: sig *)
+  val x : int
+end
+(** + mark: sig *)
+structure S : SIG = struct
+(** + mark: struct *)
+  val x = 3
+end
+(** + mark: struct *)
+"#,
+  );
+}
+
+#[test]
+fn let_case_comment() {
+  check(
+    r#"
+(*
+(** + mark: comment *)
+ * hi
+ *)
+(** + mark: comment *)
+fun f x =
+  let
+(** + mark: let *)
+    val y = x
+  in
+    case y of
+(** + mark: case *)
+      0 => 1
+    | _ => 2
+(** + mark: case *)
+  end
+(** + mark: let *)
+val _ = case 3 of _ => 4
+"#,
+  );
+}
+
+#[test]
+fn trailing_comment() {
+  check(
+    r#"
+val _ =
+  case 3 of
+(** + mark: case *)
+    1 => 2
+  | _ => 3
+(** + mark: case *)
+  (* done *)
+val y = 4
+"#,
+  );
+}
+
+#[test]
+fn local() {
+  check(
+    r#"
+local
+(** + mark: local *)
+  val x = 3
+in
+  val y = x
+end
+(** + mark: local *)
+"#,
+  );
+}
+
+#[test]
+fn mlb() {
+  check_group(
+    "sources.mlb",
+    r#"
+basis B =
+(** + mark: basis *)
+  bas
+    a.sml
+  end
+(** + mark: basis *)
+local
+(** + mark: local *)
+  b.sml
+in
+  c.sml
+end
+(** + mark: local *)
+"#,
+  );
+}
+
+#[test]
+fn cm() {
+  check_group(
+    "sources.cm",
+    r#"
+Group is
+(** + mark: group *)
+  a.sml
+  b.sml
+(** + mark: group *)
+"#,
+  );
+}
@@ -0,0 +1,93 @@
+//! Tests for the code action to generate a signature from a structure.
+
+use crate::check::one_file;
+
+/// Returns the name of the signature generated for the structure at the cursor in the single SML
+/// file, and the file after adding it.
+fn generate(s: &str, ascribe: bool) -> Option<(String, String)> {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let (name, edits) = an.generate_sig(path.wrap(file.cursor().start), ascribe)?;
+  Some((name, one_file::apply_edits(&file.text, edits)))
+}
+
+#[test]
+fn smoke() {
+  let s = r#"
+structure Foo = struct
+(**       ^ cursor *)
+  type t = int
+  datatype 'a d = A | B of 'a * t
+  exception E of string
+  val x = 3
+  fun id x = x
+end
+"#;
+  let want = r#"
+signature FOO = sig
+  type t = int
+  datatype 'a d = A | B of 'a * int
+  exception E of string
+  val x : int
+  val id : 'a -> 'a
+end
+
+structure Foo = struct
+  type t = int
+  datatype 'a d = A | B of 'a * t
+  exception E of string
+  val x = 3
+  fun id x = x
+end
+"#;
+  let (name, got) = generate(s, false).expect("no signature");
+  assert_eq!("FOO", name);
+  assert_eq!(want, got);
+}
+
+#[test]
+fn nested_ascribe() {
+  let s = r#"
+structure FooBar = struct
+(** ^ cursor *)
+  structure Baz = struct
+    datatype t = A | B
+    and u = C of t
+    val a = C A
+  end
+  val b = Baz.a
+end
+"#;
+  let want = r#"
+signature FOO_BAR = sig
+  structure Baz : sig
+    datatype t = A | B
+    and u = C of t
+    val a : u
+  end
+  val b : Baz.u
+end
+
+structure FooBar : FOO_BAR = struct
+  structure Baz = struct
+    datatype t = A | B
+    and u = C of t
+    val a = C A
+  end
+  val b = Baz.a
+end
+"#;
+  let (name, got) = generate(s, true).expect("no signature");
+  assert_eq!("FOO_BAR", name);
+  assert_eq!(want, got);
+}
+
+#[test]
+fn already_ascribed() {
+  let s = r#"
+signature S = sig end
+structure Foo : S = struct end
+(**       ^ cursor *)
+"#;
+  assert!(generate(s, false).is_none());
+}
@@ -0,0 +1,156 @@
+//! Tests for links, go to def, hover, and completions in group files.
+
+use crate::check::input;
+
+/// Returns the input, analysis, path store, and the id of the group file with the name, after
+/// analyzing the files.
+fn get(
+  files: &[(&str, &str)],
+  name: &str,
+) -> (::input::Input, analysis::Analysis, paths::Store, paths::PathId) {
+  let (input, store) = input::get(files.iter().copied());
+  let mut an = analysis::Analysis::new(analysis::StdBasis::minimal(), analysis::Options::default());
+  _ = an.get_many(&input);
+  let path = *input
+    .groups
+    .keys()
+    .find(|&&p| store.get_path(p).as_path().ends_with(name))
+    .expect("no group file");
+  (input, an, store, path)
+}
+
+#[test]
+fn links() {
+  let (input, _, store, path) =
+    get(&[("s.mlb", "a.sml\n  b.sml\n"), ("a.sml", ""), ("b.sml", "")], "s.mlb");
+  let got: Vec<_> = analysis::group_links(&input, path)
+    .expect("no links")
+    .into_iter()
+    .map(|link| {
+      let target = store.get_path(link.target).as_path().file_name().unwrap().to_owned();
+      (link.range.start.line, link.range.start.col, link.range.end.col, target)
+    })
+    .collect();
+  let want = [(0, 0, 5, "a.sml".into()), (1, 2, 7, "b.sml".into())];
+  assert_eq!(want.as_slice(), got.as_slice());
+}
+
+#[test]
+fn def_basis() {
+  let mlb = r#"
+basis B = bas a.sml end
+open B
+"#;
+  let (input, an, _, path) = get(&[("s.mlb", mlb), ("a.sml", "")], "s.mlb");
+  let pos = text_pos::PositionUtf16 { line: 2, col: 5 };
+  let defs = an.group_defs(&input, path.wrap(pos)).expect("no defs");
+  let [def] = defs.as_slice() else { panic!("wrong number of defs: {defs:?}") };
+  assert_eq!(path, def.path);
+  assert_eq!((1, 6), (def.val.start.line, def.val.start.col));
+}
+
+#[test]
+fn def_cm_export() {
+  let cm = r#"
+Group
+  structure S
+is
+  a.sml
+"#;
+  let sml = r#"
+val x = 3
+structure S = struct end
+"#;
+  let (input, an, store, path) = get(&[("s.cm", cm), ("a.sml", sml)], "s.cm");
+  let pos = text_pos::PositionUtf16 { line: 2, col: 12 };
+  let defs = an.group_defs(&input, path.wrap(pos)).expect("no defs");
+  let [def] = defs.as_slice() else { panic!("wrong number of defs: {defs:?}") };
+  assert!(store.get_path(def.path).as_path().ends_with("a.sml"));
+  assert_eq!(2, def.val.start.line);
+}
+
+#[test]
+fn hover_exports() {
+  let sml = r#"
+structure S = struct end
+signature SIG = sig end
+functor F () = struct end
+val x = 3
+"#;
+  let (input, an, store, path) = get(&[("s.mlb", "a.sml"), ("a.sml", sml)], "s.mlb");
+  let pos = text_pos::PositionUtf16 { line: 0, col: 1 };
+  let (md, _) = an.group_md(&input, &store, path.wrap(pos)).expect("no hover");
+  let want = "a.sml`\n\n```sml\nstructure S\nsignature SIG\nfunctor F\n```";
+  assert!(md.ends_with(want), "wrong hover: {md}");
+}
+
+/// Returns the labels of the completions at the 0-indexed line and column in the group file with
+/// the name, among the files, whose root group file is in the root directory.
+fn completions(
+  files: &[(&str, &str)],
+  name: &str,
+  line: u32,
+  col: u32,
+  path_vars: &slash_var_path::UnresolvedEnv,
+) -> Vec<String> {
+  let map: fast_hash::FxHashMap<_, _> = files
+    .iter()
+    .map(|&(file, contents)| (input::ROOT.as_path().join(file), contents.to_owned()))
+    .collect();
+  let path = input::ROOT.as_path().join(name);
+  let contents = map[&path].clone();
+  let fs = paths::MemoryFileSystem::new(map);
+  let pos = text_pos::PositionUtf16 { line, col };
+  analysis::group_completions(&fs, &path, &contents, pos, path_vars, input::ROOT.as_path())
+    .expect("no completions")
+    .into_iter()
+    .map(|item| item.label)
+    .collect()
+}
+
+#[test]
+fn completions_mlb() {
+  let files = [("s.mlb", "a"), ("a.sml", ""), ("b.txt", ""), ("c.cm", ""), ("d.mlb", "")];
+  let got = completions(&files, "s.mlb", 0, 1, &slash_var_path::UnresolvedEnv::default());
+  let mut want: Vec<_> = mlb_syntax::KEYWORDS.iter().map(|&x| x.to_owned()).collect();
+  want.extend(["a.sml".to_owned(), "d.mlb".to_owned()]);
+  assert_eq!(want, got);
+}
+
+#[test]
+fn completions_cm() {
+  let files = [("s.cm", "Group is\n  "), ("a.sml", ""), ("b.mlb", ""), ("c.cm", "")];
+  let got = completions(&files, "s.cm", 1, 2, &slash_var_path::UnresolvedEnv::default());
+  let mut want: Vec<_> = cm_syntax::KEYWORDS.iter().map(|&x| x.to_owned()).collect();
+  want.extend(["a.sml".to_owned(), "c.cm".to_owned()]);
+  assert_eq!(want, got);
+}
+
+#[test]
+fn completions_path_var() {
+  let mut path_vars = slash_var_path::UnresolvedEnv::default();
+  for name in ["FOO", "BAR"] {
+    let entry = slash_var_path::EnvEntry {
+      kind: slash_var_path::EnvEntryKind::Value,
+      suffix: name.to_lowercase().into(),
+    };
+    path_vars.insert(name.into(), entry);
+  }
+  let files = [("s.mlb", "$(F")];
+  let got = completions(&files, "s.mlb", 0, 3, &path_vars);
+  assert_eq!(["BAR", "FOO"].as_slice(), got.as_slice());
+}
+
+#[test]
+fn completions_workspace_path_var_nested() {
+  let mut path_vars = slash_var_path::UnresolvedEnv::default();
+  let entry = slash_var_path::EnvEntry {
+    kind: slash_var_path::EnvEntryKind::WorkspacePath,
+    suffix: "lib".into(),
+  };
+  path_vars.insert("LIB".into(), entry);
+  let files =
+    [("s.mlb", "sub/t.mlb"), ("sub/t.mlb", "$(LIB)/"), ("sub/lib/b.sml", ""), ("lib/a.sml", "")];
+  let got = completions(&files, "sub/t.mlb", 0, 7, &path_vars);
+  assert_eq!(["a.sml"].as_slice(), got.as_slice());
+}
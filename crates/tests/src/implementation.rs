@@ -0,0 +1,95 @@
+//! Tests for go to implementation.
+
+use crate::check::one_file;
+
+/// Asserts that the implementations of the item at the cursor in the single SML file start at the
+/// marks.
+#[track_caller]
+fn check(s: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let got: Vec<_> = an
+    .get_impls(path.wrap(file.cursor().start))
+    .expect("no impls")
+    .into_iter()
+    .map(|x| x.val.start)
+    .collect();
+  let want: Vec<_> = file.marks().iter().map(|(range, _)| range.start).collect();
+  assert_eq!(want, got);
+}
+
+#[test]
+fn val_spec() {
+  check(
+    r#"
+signature SIG = sig
+  val foo : int -> int
+(**   ^^^ cursor *)
+end
+structure A : SIG = struct
+  fun foo x = x + 1
+(**   ^^^ mark: impl *)
+end
+structure B :> SIG = struct
+  val foo = fn x => x
+(**   ^^^ mark: impl *)
+end
+"#,
+  );
+}
+
+#[test]
+fn sig_name() {
+  check(
+    r#"
+signature SIG = sig
+(**       ^^^ cursor *)
+  val foo : int -> int
+end
+structure A : SIG = struct
+(**       ^ mark: impl *)
+  fun foo x = x + 1
+end
+structure B :> SIG = struct
+(**       ^ mark: impl *)
+  val foo = fn x => x
+end
+"#,
+  );
+}
+
+#[test]
+fn sig_name_use() {
+  check(
+    r#"
+signature SIG = sig
+  val foo : int -> int
+end
+(**       v mark: impl *)
+structure A : SIG = struct
+(**           ^^^ cursor *)
+  fun foo x = x + 1
+end
+structure B :> SIG = struct
+(**       ^ mark: impl *)
+  val foo = fn x => x
+end
+"#,
+  );
+}
+
+#[test]
+fn functor_result() {
+  check(
+    r#"
+signature SIG = sig
+(**       ^^^ cursor *)
+  type t
+end
+functor F () : SIG = struct
+(**     ^ mark: impl *)
+  type t = int
+end
+"#,
+  );
+}
@@ -0,0 +1,97 @@
+//! Tests for the code action to inline a `val`.
+
+use crate::check::one_file;
+
+/// Returns the result of inlining the `val` at the cursor in the single SML file, if it can be
+/// inlined.
+fn get(s: &str) -> Option<String> {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let (_, edits) = an.inline_val(path.wrap(file.cursor().start))?;
+  Some(one_file::apply_edits(&file.text, edits))
+}
+
+/// Asserts that inlining the `val` at the cursor results in the wanted file.
+#[track_caller]
+fn check(s: &str, want: &str) {
+  let got = get(s).expect("no inlining");
+  assert_eq!(want, got);
+}
+
+/// Asserts that the `val` at the cursor cannot be inlined.
+#[track_caller]
+fn check_none(s: &str) {
+  assert_eq!(None, get(s));
+}
+
+#[test]
+fn smoke() {
+  check(
+    r#"
+fun f y =
+  let
+    val x = (y, 3)
+(**     ^ cursor *)
+  in
+    #1 x + #2 x
+  end
+"#,
+    r#"
+fun f y =
+  let
+  in
+    #1 (y, 3) + #2 (y, 3)
+  end
+"#,
+  );
+}
+
+#[test]
+fn fixity() {
+  check(
+    r#"
+fun f y =
+  let
+    val x = y :: []
+(**     ^ cursor *)
+  in
+    (x @ x, 0 :: x, fn g => g x)
+  end
+"#,
+    r#"
+fun f y =
+  let
+  in
+    ((y :: []) @ y :: [], 0 :: y :: [], fn g => g (y :: []))
+  end
+"#,
+  );
+}
+
+#[test]
+fn expansive() {
+  check_none(
+    r#"
+fun f y =
+  let
+    val x = f y
+(**     ^ cursor *)
+  in
+    x + x
+  end
+"#,
+  );
+}
+
+#[test]
+fn shadowed() {
+  check_none(
+    r#"
+val y = 1
+val x = y
+(** ^ cursor *)
+val y = 2
+val z = x
+"#,
+  );
+}
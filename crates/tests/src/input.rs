@@ -5,4 +5,5 @@ mod config;
 mod diagnostics;
 mod misc;
 mod mlb;
+mod roots;
 mod slash_var_path;
@@ -49,3 +49,22 @@ workspace.root = "a.mlb"
   );
   cov_mark::check("undefined_path_var_import");
 }
+
+#[test]
+fn standalone() {
+  let mut store = paths::Store::new();
+  let path = store.get_id(&crate::check::input::ROOT);
+  let mut input = input::Input::default();
+  input.add_standalone(path, "val x : int = false".to_owned());
+  let mut an = analysis::Analysis::new(analysis::StdBasis::minimal(), analysis::Options::default());
+  let got = an.get_many(&input);
+  let ds = got.get(&path).expect("no diagnostics for the standalone file");
+  let [d] = ds.as_slice() else { panic!("wrong number of diagnostics: {ds:?}") };
+  let want = "incompatible types: `int` and `bool` are different type constructors: expected \
+              `int`, found `bool`";
+  assert_eq!(want, d.message);
+  assert_eq!(
+    (0, 0, 0, 19),
+    (d.range.start.line, d.range.start.col, d.range.end.line, d.range.end.col)
+  );
+}
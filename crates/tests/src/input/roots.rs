@@ -0,0 +1,58 @@
+//! Tests for finding the roots in a workspace folder.
+
+use crate::check::input::ROOT;
+use fast_hash::FxHashMap;
+use std::path::PathBuf;
+
+/// Asserts that the roots in a folder with the files are the directories, relative to the folder.
+/// The folder itself is `""`.
+#[track_caller]
+fn check(files: &[&str], want: &[&str]) {
+  let map: FxHashMap<PathBuf, String> =
+    files.iter().map(|&name| (ROOT.as_path().join(name), String::new())).collect();
+  let fs = paths::MemoryFileSystem::new(map);
+  let got: Vec<_> = input::roots(&fs, &ROOT)
+    .into_iter()
+    .map(|path| {
+      let path = path.as_path().strip_prefix(ROOT.as_path()).expect("root outside folder");
+      path.to_str().expect("non-utf8 path").to_owned()
+    })
+    .collect();
+  assert_eq!(want, got.as_slice());
+}
+
+#[test]
+fn folder() {
+  check(&["a/a.sml", "sources.mlb"], &[""]);
+}
+
+#[test]
+fn nested() {
+  check(&["a/millet.toml", "b/c/millet.toml", "d/d.sml"], &["a", "b/c"]);
+}
+
+#[test]
+fn folder_and_nested() {
+  check(&["millet.toml", "a/millet.toml"], &["", "a"]);
+}
+
+#[test]
+fn max_depth() {
+  check(&["a/b/c/d/e/millet.toml", "f/g/h/i/j/k/millet.toml"], &["a/b/c/d/e"]);
+}
+
+#[test]
+fn skip_dirs() {
+  check(
+    &[
+      ".git/millet.toml",
+      "_build/millet.toml",
+      "build/millet.toml",
+      "node_modules/a/millet.toml",
+      "src/millet.toml",
+      "target/millet.toml",
+      "vendor/millet.toml",
+    ],
+    &["src"],
+  );
+}
@@ -0,0 +1,320 @@
+//! Tests for the language server, talking to it over an in-memory connection like a client would.
+
+use lsp_server::{Message, Request, RequestId, Response};
+use lsp_types::{notification::Notification as _, request::Request as _, Url};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// How long to wait for the server to send a message before giving up.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// For making a distinct directory for each client, since tests run in parallel.
+static DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// A client connected to a language server for a workspace folder with some files on disk.
+struct Client {
+  conn: lsp_server::Connection,
+  server: std::thread::JoinHandle<()>,
+  dir: PathBuf,
+  next_id: i32,
+  /// Every message the server sent, in order.
+  got: Vec<Message>,
+}
+
+impl Client {
+  /// Writes the files, with names relative to a new workspace folder, and starts a server for that
+  /// folder with a client with the capabilities.
+  fn new(files: &[(&str, &str)], capabilities: lsp_types::ClientCapabilities) -> Self {
+    let dir = std::env::temp_dir().join(format!(
+      "millet-lang-srv-{}-{}",
+      std::process::id(),
+      DIRS.fetch_add(1, Ordering::SeqCst)
+    ));
+    std::fs::create_dir_all(&dir).expect("couldn't create dir");
+    let dir = std::fs::canonicalize(dir).expect("couldn't canonicalize dir");
+    for &(name, contents) in files {
+      std::fs::write(dir.join(name), contents).expect("couldn't write file");
+    }
+    let (conn, server_conn) = lsp_server::Connection::memory();
+    let server = std::thread::spawn(move || lang_srv::run(&server_conn).expect("server error"));
+    let mut ret = Self { conn, server, dir, next_id: 0, got: Vec::new() };
+    let folder = lsp_types::WorkspaceFolder { uri: ret.url(""), name: "test".to_owned() };
+    ret.request::<lsp_types::request::Initialize>(lsp_types::InitializeParams {
+      capabilities,
+      workspace_folders: Some(vec![folder]),
+      ..lsp_types::InitializeParams::default()
+    });
+    ret.notify::<lsp_types::notification::Initialized>(lsp_types::InitializedParams {});
+    ret
+  }
+
+  /// Returns the URL of the file with the name relative to the workspace folder.
+  fn url(&self, name: &str) -> Url {
+    Url::from_file_path(self.dir.join(name)).expect("couldn't make url")
+  }
+
+  /// Sends the request, and returns its result once the server responds.
+  #[track_caller]
+  fn request<R>(&mut self, params: R::Params) -> R::Result
+  where
+    R: lsp_types::request::Request,
+  {
+    let id =
+      self.send_request(R::METHOD, serde_json::to_value(params).expect("couldn't serialize"));
+    loop {
+      match self.recv() {
+        Message::Response(res) if res.id == id => {
+          let result = res.result.unwrap_or_else(|| panic!("error response: {:?}", res.error));
+          return serde_json::from_value(result).expect("couldn't deserialize");
+        }
+        Message::Request(req) => self.respond(&req),
+        Message::Response(_) | Message::Notification(_) => {}
+      }
+    }
+  }
+
+  fn notify<N>(&self, params: N::Params)
+  where
+    N: lsp_types::notification::Notification,
+  {
+    let params = serde_json::to_value(params).expect("couldn't serialize");
+    let notif = lsp_server::Notification { method: N::METHOD.to_owned(), params };
+    self.conn.sender.send(notif.into()).expect("server stopped");
+  }
+
+  /// Receives messages until one satisfies the predicate.
+  #[track_caller]
+  fn wait_for<F>(&mut self, f: F)
+  where
+    F: Fn(&Message) -> bool,
+  {
+    loop {
+      let msg = self.recv();
+      if let Message::Request(req) = &msg {
+        self.respond(req);
+      }
+      if f(&msg) {
+        return;
+      }
+    }
+  }
+
+  /// Returns the params of the notifications the server sent with the method, in order.
+  fn notifications<N>(&self) -> Vec<N::Params>
+  where
+    N: lsp_types::notification::Notification,
+  {
+    self
+      .got
+      .iter()
+      .filter_map(|msg| match msg {
+        Message::Notification(notif) if notif.method == N::METHOD => {
+          Some(serde_json::from_value(notif.params.clone()).expect("couldn't deserialize"))
+        }
+        _ => None,
+      })
+      .collect()
+  }
+
+  /// Shuts down the server, waits for it to stop, and removes the files.
+  fn shutdown(mut self) {
+    let id = self.send_request(lsp_types::request::Shutdown::METHOD, serde_json::Value::Null);
+    // don't respond to anything else, since the server wants only the exit notification now.
+    while !matches!(self.recv(), Message::Response(res) if res.id == id) {}
+    self.notify::<lsp_types::notification::Exit>(());
+    drop(self.conn);
+    self.server.join().expect("server panicked");
+    std::fs::remove_dir_all(&self.dir).expect("couldn't remove dir");
+  }
+
+  fn send_request(&mut self, method: &str, params: serde_json::Value) -> RequestId {
+    self.next_id += 1;
+    let id = RequestId::from(self.next_id);
+    let req = Request { id: id.clone(), method: method.to_owned(), params };
+    self.conn.sender.send(req.into()).expect("server stopped");
+    id
+  }
+
+  #[track_caller]
+  fn recv(&mut self) -> Message {
+    let msg = self.conn.receiver.recv_timeout(TIMEOUT).expect("no message from server");
+    self.got.push(msg.clone());
+    msg
+  }
+
+  /// Responds to a request from the server. The server does not need anything in the responses it
+  /// gets in these tests.
+  fn respond(&self, req: &Request) {
+    let res = Response::new_ok(req.id.clone(), serde_json::Value::Null);
+    self.conn.sender.send(res.into()).expect("server stopped");
+  }
+}
+
+/// The files for a root with a single SML file, which has an error.
+const FILES: [(&str, &str); 2] = [("sources.mlb", "a.sml"), ("a.sml", "val x : int = false")];
+
+fn pull_capabilities() -> lsp_types::ClientCapabilities {
+  lsp_types::ClientCapabilities {
+    text_document: Some(lsp_types::TextDocumentClientCapabilities {
+      diagnostic: Some(lsp_types::DiagnosticClientCapabilities::default()),
+      ..lsp_types::TextDocumentClientCapabilities::default()
+    }),
+    ..lsp_types::ClientCapabilities::default()
+  }
+}
+
+fn document_diagnostic_params(
+  uri: Url,
+  previous_result_id: Option<String>,
+) -> lsp_types::DocumentDiagnosticParams {
+  lsp_types::DocumentDiagnosticParams {
+    text_document: lsp_types::TextDocumentIdentifier { uri },
+    identifier: None,
+    previous_result_id,
+    work_done_progress_params: lsp_types::WorkDoneProgressParams::default(),
+    partial_result_params: lsp_types::PartialResultParams::default(),
+  }
+}
+
+#[test]
+fn pull_document_diagnostics() {
+  let mut client = Client::new(&FILES, pull_capabilities());
+  let url = client.url("a.sml");
+  let got = client.request::<lsp_types::request::DocumentDiagnosticRequest>(
+    document_diagnostic_params(url.clone(), None),
+  );
+  let lsp_types::DocumentDiagnosticReportResult::Report(lsp_types::DocumentDiagnosticReport::Full(
+    full,
+  )) = got
+  else {
+    panic!("not a full report: {got:?}")
+  };
+  let full = full.full_document_diagnostic_report;
+  assert_eq!(1, full.items.len());
+  let result_id = full.result_id.expect("no result id");
+  // the diagnostics are the same as last time.
+  let got = client.request::<lsp_types::request::DocumentDiagnosticRequest>(
+    document_diagnostic_params(url.clone(), Some(result_id.clone())),
+  );
+  let lsp_types::DocumentDiagnosticReportResult::Report(
+    lsp_types::DocumentDiagnosticReport::Unchanged(unchanged),
+  ) = got
+  else {
+    panic!("not an unchanged report: {got:?}")
+  };
+  assert_eq!(result_id, unchanged.unchanged_document_diagnostic_report.result_id);
+  // the client had some other diagnostics.
+  let got = client.request::<lsp_types::request::DocumentDiagnosticRequest>(
+    document_diagnostic_params(url, Some("other".to_owned())),
+  );
+  let lsp_types::DocumentDiagnosticReportResult::Report(lsp_types::DocumentDiagnosticReport::Full(
+    full,
+  )) = got
+  else {
+    panic!("not a full report: {got:?}")
+  };
+  assert_eq!(Some(result_id), full.full_document_diagnostic_report.result_id);
+  client.shutdown();
+}
+
+#[test]
+fn pull_workspace_diagnostics() {
+  let mut client = Client::new(&FILES, pull_capabilities());
+  let url = client.url("a.sml");
+  let gone = client.url("gone.sml");
+  let params = |previous_result_ids| lsp_types::WorkspaceDiagnosticParams {
+    identifier: None,
+    previous_result_ids,
+    work_done_progress_params: lsp_types::WorkDoneProgressParams::default(),
+    partial_result_params: lsp_types::PartialResultParams::default(),
+  };
+  let got = client.request::<lsp_types::request::WorkspaceDiagnosticRequest>(params(Vec::new()));
+  let lsp_types::WorkspaceDiagnosticReportResult::Report(report) = got else {
+    panic!("not a report: {got:?}")
+  };
+  let result_id = report
+    .items
+    .into_iter()
+    .find_map(|item| match item {
+      lsp_types::WorkspaceDocumentDiagnosticReport::Full(full) if full.uri == url => {
+        full.full_document_diagnostic_report.result_id
+      }
+      _ => None,
+    })
+    .expect("no full report with a result id");
+  // a file the client had diagnostics for is no longer in the input.
+  let previous = vec![
+    lsp_types::PreviousResultId { uri: url.clone(), value: result_id },
+    lsp_types::PreviousResultId { uri: gone.clone(), value: "other".to_owned() },
+  ];
+  let got = client.request::<lsp_types::request::WorkspaceDiagnosticRequest>(params(previous));
+  let lsp_types::WorkspaceDiagnosticReportResult::Report(report) = got else {
+    panic!("not a report: {got:?}")
+  };
+  let mut unchanged = Vec::<Url>::new();
+  let mut full = Vec::<Url>::new();
+  for item in report.items {
+    match item {
+      lsp_types::WorkspaceDocumentDiagnosticReport::Full(x) => {
+        assert!(x.full_document_diagnostic_report.items.is_empty(), "{}", x.uri);
+        full.push(x.uri);
+      }
+      lsp_types::WorkspaceDocumentDiagnosticReport::Unchanged(x) => unchanged.push(x.uri),
+    }
+  }
+  assert_eq!(vec![url], unchanged);
+  assert!(full.contains(&gone));
+  client.shutdown();
+}
+
+#[test]
+fn work_done_progress() {
+  let capabilities = lsp_types::ClientCapabilities {
+    window: Some(lsp_types::WindowClientCapabilities {
+      work_done_progress: Some(true),
+      ..lsp_types::WindowClientCapabilities::default()
+    }),
+    ..lsp_types::ClientCapabilities::default()
+  };
+  let mut client = Client::new(&FILES, capabilities);
+  client.wait_for(|msg| {
+    matches!(msg, Message::Notification(notif)
+      if notif.method == lsp_types::notification::PublishDiagnostics::METHOD)
+  });
+  let created: Vec<_> = client
+    .got
+    .iter()
+    .filter_map(|msg| match msg {
+      Message::Request(req) if req.method == lsp_types::request::WorkDoneProgressCreate::METHOD => {
+        let params: lsp_types::WorkDoneProgressCreateParams =
+          serde_json::from_value(req.params.clone()).expect("couldn't deserialize");
+        Some(params.token)
+      }
+      _ => None,
+    })
+    .collect();
+  let mut titles = Vec::<String>::new();
+  let mut ended = Vec::<lsp_types::NumberOrString>::new();
+  for params in client.notifications::<lsp_types::notification::Progress>() {
+    let lsp_types::ProgressParamsValue::WorkDone(value) = params.value;
+    match value {
+      lsp_types::WorkDoneProgress::Begin(begin) => {
+        assert!(created.contains(&params.token), "began before create: {:?}", params.token);
+        titles.push(begin.title);
+      }
+      lsp_types::WorkDoneProgress::Report(_) => {
+        assert!(!ended.contains(&params.token), "report after end: {:?}", params.token);
+      }
+      lsp_types::WorkDoneProgress::End(_) => ended.push(params.token),
+    }
+  }
+  assert!(titles.iter().any(|x| x == "Loading input"), "{titles:?}");
+  assert!(titles.iter().any(|x| x == "Analyzing"), "{titles:?}");
+  // every progress that began has ended once diagnostics are published.
+  assert_eq!(created.len(), titles.len());
+  for token in &created {
+    assert!(ended.contains(token), "didn't end: {token:?}");
+  }
+  client.shutdown();
+}
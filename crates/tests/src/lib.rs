@@ -8,6 +8,7 @@
 #![allow(clippy::single_match_else)]
 
 mod big;
+mod call_hierarchy;
 mod cannot_rebind;
 mod check;
 mod circularity;
@@ -15,35 +16,52 @@ mod common;
 mod completions;
 mod datatype_copy;
 mod deviations;
+mod did_you_mean;
 mod disallow;
 mod docs;
+mod document_highlight;
 mod dupe;
 mod empty;
 mod equality;
 mod exn;
+mod extract_val;
+mod fix;
 mod fixity;
+mod folding_range;
 mod forbid_opaque_asc;
 mod functor;
 mod generalize;
+mod generate_sig;
 mod goto_def;
+mod group_file;
 mod hover;
+mod implementation;
 mod incomplete;
 mod infix_without_op;
+mod inline_val;
 mod input;
+mod lang_srv;
 mod literal;
 mod local;
 mod matching;
 mod misc;
+mod missing_arms;
+mod missing_specs;
 mod num_record;
 mod open;
 mod overload;
 mod pat;
+mod rename;
 mod repo;
 mod rest_pat;
 mod rust;
+mod selection_range;
+mod semantic_tokens;
 mod sep;
+mod settings;
 mod shadow;
 mod sig;
+mod signature_help;
 mod smoke;
 mod std_basis;
 mod symbolic;
@@ -53,3 +71,4 @@ mod unused;
 mod use_builtin;
 mod val_rec;
 mod well_known;
+mod workspace_symbols;
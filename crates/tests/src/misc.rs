@@ -292,6 +292,29 @@ val lam = lambda x: x + 1
   );
 }
 
+#[test]
+fn suggest_similar() {
+  check(
+    r#"
+val fooBar = 3
+val fooBaz = 4
+val _ = fooBax
+(**     ^^^^^^ undefined value: `fooBax` (did you mean `fooBar` or `fooBaz`?) *)
+"#,
+  );
+}
+
+#[test]
+fn suggest_similar_in_struct() {
+  check(
+    r#"
+structure S = struct val length = 3 end
+val _ = S.lenght
+(**     ^^^^^^^^ undefined value: `lenght` (did you mean `length`?) *)
+"#,
+  );
+}
+
 #[test]
 fn op_bool_op() {
   check(
@@ -0,0 +1,244 @@
+//! Tests for the code action to add missing arms.
+
+use crate::check::one_file;
+
+/// Asserts that adding the missing arms at the cursor in the single SML file results in the wanted
+/// file.
+#[track_caller]
+fn check(s: &str, want: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let edit = an.add_missing_arms(path.wrap(file.cursor().start)).expect("no missing arms");
+  let got = one_file::apply_edits(&file.text, vec![edit]);
+  assert_eq!(want, got);
+}
+
+/// Asserts that there are no missing arms to add at the cursor in the single SML file.
+#[track_caller]
+fn check_none(s: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  assert!(an.add_missing_arms(path.wrap(file.cursor().start)).is_none());
+}
+
+#[test]
+fn case() {
+  check(
+    r#"
+datatype d = A | B of int
+fun f x =
+  case x of
+(** ^ cursor *)
+    A => 1
+"#,
+    r#"
+datatype d = A | B of int
+fun f x =
+  case x of
+    A => 1
+  | B _ => _
+"#,
+  );
+}
+
+#[test]
+fn case_bar() {
+  check(
+    r#"
+datatype d = A | B of int | C
+fun f x =
+  case x of
+(** ^ cursor *)
+    A => 1
+  | C => 2
+"#,
+    r#"
+datatype d = A | B of int | C
+fun f x =
+  case x of
+    A => 1
+  | C => 2
+  | B _ => _
+"#,
+  );
+}
+
+#[test]
+fn fn_() {
+  check(
+    r#"
+datatype d = A | B of int
+val f = fn A => 1
+(**     ^ cursor *)
+"#,
+    r#"
+datatype d = A | B of int
+val f = fn A => 1
+  | B _ => _
+"#,
+  );
+}
+
+#[test]
+fn fun() {
+  check(
+    r#"
+datatype d = A | B of int
+fun f A = 1
+(** ^ cursor *)
+"#,
+    r#"
+datatype d = A | B of int
+fun f A = 1
+  | f (B _) = _
+"#,
+  );
+}
+
+#[test]
+fn nested_con() {
+  check(
+    r#"
+datatype d = A | B of int
+fun f x =
+  case x of
+(** ^ cursor *)
+    NONE => 0
+  | SOME A => 1
+"#,
+    r#"
+datatype d = A | B of int
+fun f x =
+  case x of
+    NONE => 0
+  | SOME A => 1
+  | SOME (B _) => _
+"#,
+  );
+}
+
+#[test]
+fn tuple() {
+  check(
+    r#"
+datatype d = A | B
+fun f x =
+  case x of
+(** ^ cursor *)
+    (B, _) => 1
+"#,
+    r#"
+datatype d = A | B
+fun f x =
+  case x of
+    (B, _) => 1
+  | (A, _) => _
+"#,
+  );
+}
+
+#[test]
+fn fun_curried() {
+  check(
+    r#"
+datatype d = A | B
+fun f B _ = 1
+(** ^ cursor *)
+"#,
+    r#"
+datatype d = A | B
+fun f B _ = 1
+  | f A _ = _
+"#,
+  );
+}
+
+#[test]
+fn record_rest() {
+  check(
+    r#"
+datatype d = A | B
+fun f (x : {a : d, b : int}) =
+  case x of
+(** ^ cursor *)
+    {a = A, ...} => 1
+"#,
+    r#"
+datatype d = A | B
+fun f (x : {a : d, b : int}) =
+  case x of
+    {a = A, ...} => 1
+  | {a = B, b = _} => _
+"#,
+  );
+}
+
+#[test]
+fn list_cons() {
+  check(
+    r#"
+fun f xs =
+  case xs of
+(** ^ cursor *)
+    [] => 0
+"#,
+    r#"
+fun f xs =
+  case xs of
+    [] => 0
+  | _ :: _ => _
+"#,
+  );
+}
+
+#[test]
+fn last_body_case() {
+  check(
+    r#"
+datatype d = A | B
+fun f x y =
+  case x of
+(** ^ cursor *)
+    A =>
+      case y of
+        A => 1
+      | B => 2
+"#,
+    r#"
+datatype d = A | B
+fun f x y =
+  case x of
+    A =>
+      (case y of
+        A => 1
+      | B => 2)
+  | B => _
+"#,
+  );
+}
+
+#[test]
+fn fun_last_body_if() {
+  check(
+    r#"
+datatype d = A | B
+fun f A b = if b then 0 else case b of true => 1 | false => 2
+(** ^ cursor *)
+"#,
+    r#"
+datatype d = A | B
+fun f A b = (if b then 0 else case b of true => 1 | false => 2)
+  | f B _ = _
+"#,
+  );
+}
+
+#[test]
+fn binding() {
+  check_none(
+    r#"
+val SOME x = SOME 3
+(** ^ cursor *)
+"#,
+  );
+}
@@ -0,0 +1,114 @@
+//! Tests for the code action to add stubs for missing specifications.
+
+use crate::check::one_file;
+
+/// Asserts that adding the stubs at the cursor in the single SML file results in the wanted file.
+#[track_caller]
+fn check(s: &str, want: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let edits = an.add_missing_specs(path.wrap(file.cursor().start)).expect("no missing specs");
+  let got = one_file::apply_edits(&file.text, edits);
+  assert_eq!(want, got);
+}
+
+#[test]
+fn smoke() {
+  check(
+    r#"
+signature S = sig
+  type t
+  datatype d = A | B of t
+  exception E of d
+  val x : int
+  val f : t -> d
+  val y : d list
+end
+structure Foo : S = struct
+(**                 ^ cursor *)
+  val x = 1
+end
+"#,
+    r#"
+signature S = sig
+  type t
+  datatype d = A | B of t
+  exception E of d
+  val x : int
+  val f : t -> d
+  val y : d list
+end
+structure Foo : S = struct
+  val x = 1
+  type t = unit
+  datatype d = A | B of t
+  exception E of d
+  fun f _ = raise Fail "unimplemented"
+  val y : d list = raise Fail "unimplemented"
+end
+"#,
+  );
+}
+
+#[test]
+fn realized() {
+  check(
+    r#"
+signature S = sig
+  type t
+  val mk : int -> t
+  val get : t
+end
+structure Foo : S = struct type t = string end
+(**                 ^ cursor *)
+"#,
+    r#"
+signature S = sig
+  type t
+  val mk : int -> t
+  val get : t
+end
+structure Foo : S = struct type t = string
+  fun mk _ = raise Fail "unimplemented"
+  val get : string = raise Fail "unimplemented"
+end
+"#,
+  );
+}
+
+#[test]
+fn nested_partial() {
+  check(
+    r#"
+signature S = sig
+  structure A : sig
+    val x : int
+    val y : int
+  end
+  val z : int
+end
+structure Foo : S = struct
+(**                 ^ cursor *)
+  structure A = struct
+    val x = 1
+  end
+end
+"#,
+    r#"
+signature S = sig
+  structure A : sig
+    val x : int
+    val y : int
+  end
+  val z : int
+end
+structure Foo : S = struct
+  structure A = struct
+    val x = 1
+    val y : int = raise Fail "unimplemented"
+  end
+  val z : int = raise Fail "unimplemented"
+end
+"#,
+  );
+}
@@ -0,0 +1,212 @@
+//! Tests for rename.
+
+use crate::check::one_file;
+
+/// Renames the item at the cursor in the single SML file to `new_name`, and asserts the resulting
+/// file is `want`, or if `want` is `Err`, that the error message contains the given message.
+#[track_caller]
+fn check(s: &str, new_name: &str, want: Result<&str, &str>) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let got = an
+    .rename(path.wrap(file.cursor().start), new_name)
+    .map(|mut edits| one_file::apply_edits(&file.text, edits.remove(&path).unwrap_or_default()));
+  match (want, got) {
+    (Ok(want), Ok(got)) => pretty_assertions::assert_str_eq!(want, got),
+    (Err(want), Err(got)) => {
+      let got = got.to_string();
+      assert!(got.contains(want), "want error containing {want:?}, got {got:?}");
+    }
+    (Ok(_), Err(e)) => panic!("want ok, got error: {e}"),
+    (Err(_), Ok(got)) => panic!("want error, got ok: {got}"),
+  }
+}
+
+#[test]
+fn val() {
+  check(
+    r#"
+val x = 3
+(** ^ cursor *)
+val y = x + x
+"#,
+    "z",
+    Ok(
+      r#"
+val z = 3
+val y = z + z
+"#,
+    ),
+  );
+}
+
+#[test]
+fn from_use() {
+  check(
+    r#"
+val x = 3
+val y = x + x
+(**         ^ cursor *)
+"#,
+    "z",
+    Ok(
+      r#"
+val z = 3
+val y = z + z
+"#,
+    ),
+  );
+}
+
+#[test]
+fn fun() {
+  check(
+    r#"
+fun fact 0 = 1
+(** ^ cursor *)
+  | fact n = n * fact (n - 1)
+val _ = fact 3
+"#,
+    "factorial",
+    Ok(
+      r#"
+fun factorial 0 = 1
+  | factorial n = n * factorial (n - 1)
+val _ = factorial 3
+"#,
+    ),
+  );
+}
+
+#[test]
+fn con() {
+  check(
+    r#"
+datatype t = A | B of int
+(**          ^ cursor *)
+fun f x = case x of A => 0 | B n => n
+val _ = f A
+"#,
+    "C",
+    Ok(
+      r#"
+datatype t = C | B of int
+fun f x = case x of C => 0 | B n => n
+val _ = f C
+"#,
+    ),
+  );
+}
+
+#[test]
+fn structure() {
+  check(
+    r#"
+structure S = struct val x = 3 end
+val y = S.x
+(**     ^ cursor *)
+"#,
+    "T",
+    Ok(
+      r#"
+structure T = struct val x = 3 end
+val y = T.x
+"#,
+    ),
+  );
+}
+
+#[test]
+fn std_basis() {
+  check(
+    r#"
+val _ = 1 + 2
+(**       ^ cursor *)
+"#,
+    "plus",
+    Err("std basis"),
+  );
+}
+
+#[test]
+fn keyword() {
+  check(
+    r#"
+val x = 3
+(** ^ cursor *)
+"#,
+    "val",
+    Err("not a valid name"),
+  );
+}
+
+#[test]
+fn symbolic_structure() {
+  check(
+    r#"
+structure S = struct end
+(**       ^ cursor *)
+"#,
+    "+",
+    Err("not a valid name"),
+  );
+}
+
+#[test]
+fn con_to_var_pat() {
+  check(
+    r#"
+datatype t = A | B
+(**          ^ cursor *)
+fun f x = case x of A => 1 | b => 2
+"#,
+    "b",
+    Err("identifier status"),
+  );
+}
+
+#[test]
+fn nested_structure() {
+  check(
+    r#"
+structure A = struct
+  structure B = struct val x = 1 end
+(**         ^ cursor *)
+  val y = B.x
+end
+val z = A.B.x
+"#,
+    "C",
+    Ok(
+      r#"
+structure A = struct
+  structure C = struct val x = 1 end
+  val y = C.x
+end
+val z = A.C.x
+"#,
+    ),
+  );
+}
+
+#[test]
+fn shadowed_structure() {
+  check(
+    r#"
+structure S = struct val x = 1 end
+val y = S.x
+(**     ^ cursor *)
+structure S = struct val x = 2 end
+val z = S.x
+"#,
+    "T",
+    Ok(
+      r#"
+structure T = struct val x = 1 end
+val y = T.x
+structure S = struct val x = 2 end
+val z = S.x
+"#,
+    ),
+  );
+}
@@ -0,0 +1,85 @@
+//! Tests for selection ranges.
+
+use crate::check::one_file;
+
+/// Asserts that expanding the selection from the cursor in the single SML file selects each of the
+/// texts in order, possibly with other selections in between.
+#[track_caller]
+fn check(s: &str, want: &[&str]) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let pos_db = text_pos::PositionDb::new(&file.text);
+  let got: Vec<_> = an
+    .selection_ranges(path.wrap(file.cursor().start))
+    .expect("no selection ranges")
+    .into_iter()
+    .map(|range| {
+      let range = pos_db.text_range_utf16(range).expect("no range");
+      &file.text[std::ops::Range::<usize>::from(range)]
+    })
+    .collect();
+  let mut iter = got.iter();
+  for &w in want {
+    assert!(iter.any(|&g| g == w), "want {w:?} in order in {got:#?}");
+  }
+}
+
+#[test]
+fn case_in_struct() {
+  check(
+    r#"
+structure S = struct
+  fun g x =
+    case x of
+      0 => 1
+    | n => (g n)
+(**         ^ cursor *)
+end
+"#,
+    &[
+      "g",
+      "g n",
+      "(g n)",
+      "n => (g n)",
+      "case x of
+      0 => 1
+    | n => (g n)",
+      "struct
+  fun g x =
+    case x of
+      0 => 1
+    | n => (g n)
+end",
+    ],
+  );
+}
+
+#[test]
+fn let_exp() {
+  check(
+    r#"
+val x =
+  let
+    val y = 1 + 2
+(**         ^ cursor *)
+  in
+    y
+  end
+"#,
+    &["1", "1 + 2", "val y = 1 + 2"],
+  );
+}
+
+#[test]
+fn trivia() {
+  check(
+    r#"
+val x =
+  (* before *)
+    1 + 2 (* after *)
+(** ^ cursor *)
+val y = x
+"#,
+    &["1", "1 + 2", "val x =\n  (* before *)\n    1 + 2"],
+  );
+}
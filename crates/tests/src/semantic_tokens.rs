@@ -0,0 +1,122 @@
+//! Tests for semantic tokens.
+
+use crate::check::one_file;
+
+/// Asserts that the semantic token that starts at each mark in the single SML file has the
+/// description of that mark.
+#[track_caller]
+fn check(s: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let tokens = an.semantic_tokens(path, None).expect("no semantic tokens");
+  for (range, want) in file.marks() {
+    let start = range.start;
+    let token = tokens
+      .iter()
+      .find(|t| t.range.start == start)
+      .unwrap_or_else(|| panic!("no token at {start:?}"));
+    let got = describe(token);
+    assert_eq!(want, &got, "at {start:?}");
+  }
+}
+
+fn describe(token: &analysis::SemanticToken) -> String {
+  let mut ret = match token.kind {
+    analysis::SemanticTokenKind::Symbol(kind) => match kind {
+      sml_namespace::SymbolKind::Structure => "structure",
+      sml_namespace::SymbolKind::Signature => "signature",
+      sml_namespace::SymbolKind::Functor => "functor",
+      sml_namespace::SymbolKind::Type => "type",
+      sml_namespace::SymbolKind::Constructor => "con",
+      sml_namespace::SymbolKind::Exception => "exn",
+      sml_namespace::SymbolKind::Function => "fun",
+      sml_namespace::SymbolKind::Value => "val",
+    },
+    analysis::SemanticTokenKind::TyVar => "ty var",
+  }
+  .to_owned();
+  if token.std_basis {
+    ret.push_str(" std");
+  }
+  if token.is_ref {
+    ret.push_str(" ref");
+  }
+  ret
+}
+
+#[test]
+fn con_and_var() {
+  check(
+    r#"
+(**         v type *)
+datatype 'a t
+(**      ^^ ty var *)
+  = A
+(** ^ con *)
+  | B of 'a
+(** ^ con *)
+(**   v val *)
+fun f x =
+(** ^ fun *)
+  case x of
+    A => 0
+(** ^ con *)
+(**   v val *)
+  | B y =>
+(** ^ con *)
+(** v val *)
+    y + 1
+(**   ^ fun std *)
+val g
+(** ^ fun *)
+(** vvv type std *)
+  : int t
+(**     ^ type *)
+  -> int = f
+(**        ^ fun *)
+"#,
+  );
+}
+
+#[test]
+fn module() {
+  check(
+    r#"
+signature SIG = sig val x : int end
+(**       ^^^ signature *)
+(**           vvv signature *)
+structure S : SIG = struct val x = 3 end
+(**       ^ structure *)
+(**       v val *)
+val _ = S.x
+(**     ^ structure *)
+"#,
+  );
+}
+
+#[test]
+fn exn() {
+  check(
+    r#"
+exception E
+(**       ^ exn *)
+(**            v exn *)
+val _ = (raise E) handle E => ()
+(**                      ^ exn *)
+"#,
+  );
+}
+
+#[test]
+fn std_basis_and_ref() {
+  check(
+    r#"
+(**     vvv con std *)
+val r = ref 0
+(** ^ val ref *)
+(**     v val ref *)
+val _ = r := !r + 1
+(**       ^^ fun std *)
+"#,
+  );
+}
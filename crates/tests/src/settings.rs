@@ -0,0 +1,62 @@
+//! Tests for the shapes of the options the client sends.
+
+use config::init::{DiagnosticsIgnore, FormatEngine, Options, Settings};
+
+#[track_caller]
+fn check(options: &Options) {
+  assert!(!options.token_hover.0);
+  assert!(options.fs_watcher.0);
+  assert!(matches!(options.format, FormatEngine::Naive));
+  assert!(options.diagnostics.on_change);
+  assert!(options.diagnostics.more_info_hint.0);
+  assert!(matches!(options.diagnostics.ignore, DiagnosticsIgnore::All));
+}
+
+#[test]
+fn init_options() {
+  let val = serde_json::json!({
+    "token_hover": false,
+    "format": "naive",
+    "diagnostics": { "on_change": true, "ignore": "all" }
+  });
+  let options: Options = serde_json::from_value(val).unwrap();
+  check(&options);
+}
+
+#[test]
+fn settings() {
+  let val = serde_json::json!({
+    "format": { "engine": "naive" },
+    "server": {
+      "hover": { "token": { "enable": false } },
+      "diagnostics": { "onChange": { "enable": true }, "ignore": "all" }
+    }
+  });
+  let settings: Settings = serde_json::from_value(val).unwrap();
+  check(&settings.into());
+}
+
+#[test]
+fn configuration_response() {
+  let val = serde_json::json!([{
+    "format": { "engine": "naive" },
+    "server": {
+      "hover": { "token": { "enable": false } },
+      "diagnostics": { "onChange": { "enable": true }, "ignore": "all" }
+    }
+  }]);
+  let [settings]: [Settings; 1] = serde_json::from_value(val).unwrap();
+  check(&settings.into());
+}
+
+#[test]
+fn settings_default() {
+  let settings: Settings = serde_json::from_value(serde_json::json!({})).unwrap();
+  let options = Options::from(settings);
+  assert!(options.token_hover.0);
+  assert!(options.fs_watcher.0);
+  assert!(matches!(options.format, FormatEngine::None));
+  assert!(!options.diagnostics.on_change);
+  assert!(options.diagnostics.more_info_hint.0);
+  assert!(matches!(options.diagnostics.ignore, DiagnosticsIgnore::AfterSyntax));
+}
@@ -415,6 +415,50 @@ val _: int = S.x
   );
 }
 
+#[test]
+fn opaque_missing_spec() {
+  check(
+    r#"
+signature SIG = sig
+  type t
+  type u
+  val x: t
+end
+
+structure S :> SIG = struct
+(**                  ^^^^^^ missing type required by signature: `u` *)
+  type t = int
+  val x = 3
+end
+
+val _ = S.x
+(**       ^ hover: S.t *)
+"#,
+  );
+}
+
+#[test]
+fn transparent_missing_spec() {
+  check(
+    r#"
+signature SIG = sig
+  type t
+  type u
+  val x: t
+end
+
+structure S : SIG = struct
+(**                 ^^^^^^ missing type required by signature: `u` *)
+  type t = int
+  val x = 3
+end
+
+val _ = S.x
+(**       ^ hover: int *)
+"#,
+  );
+}
+
 #[test]
 fn functor_sig() {
   check(
@@ -0,0 +1,71 @@
+//! Tests for signature help.
+
+use crate::check::one_file;
+
+/// Asserts that the signature help at the cursor in the single SML file has the label and active
+/// parameter.
+#[track_caller]
+fn check(s: &str, label: &str, active_param: &str) {
+  let file = one_file::Marked::new(s);
+  let (an, path) = one_file::get(&file.text);
+  let help = an.signature_help(path.wrap(file.cursor().start)).expect("no signature help");
+  assert_eq!(label, help.label);
+  let idx = help.active_param.expect("no active param");
+  let [start, end] = help.params[usize::try_from(idx).unwrap()];
+  // all the labels in these tests are ASCII, so UTF-16 offsets are byte offsets.
+  let got = &help.label[usize::try_from(start).unwrap()..usize::try_from(end).unwrap()];
+  assert_eq!(active_param, got);
+}
+
+#[test]
+fn curried() {
+  check(
+    r#"
+fun f (x : int) (y : string) = x
+val _ = f 3 "hi"
+(**          ^ cursor *)
+"#,
+    "f : int -> string -> int",
+    "string",
+  );
+}
+
+#[test]
+fn tupled() {
+  check(
+    r#"
+fun g (x : int, y : bool) = y
+val _ = g (1, true)
+(**            ^ cursor *)
+"#,
+    "g : int * bool -> bool",
+    "bool",
+  );
+}
+
+#[test]
+fn fn_param() {
+  check(
+    r#"
+fun app f (x : int) : int = f x
+val _ = app (fn x => x) 3
+(**          ^ cursor *)
+"#,
+    "app : (int -> int) -> int -> int",
+    "(int -> int)",
+  );
+}
+
+#[test]
+fn functor() {
+  check(
+    r#"
+signature SIG = sig end
+functor F (X : SIG) = struct end
+structure S = F (struct end)
+(**               ^ cursor *)
+"#,
+    "F (X : SIG)",
+    "X : SIG",
+  );
+}
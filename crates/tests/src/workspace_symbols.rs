@@ -0,0 +1,96 @@
+//! Tests for workspace symbols.
+
+use crate::check::one_file;
+
+/// Asserts that searching for the query in the single SML file returns the qualified names.
+#[track_caller]
+fn check(s: &str, query: &str, want: &[&str]) {
+  let (an, _) = one_file::get(s);
+  let got: Vec<_> = an
+    .workspace_symbols(query)
+    .into_iter()
+    .map(|sym| match sym.container_name {
+      Some(container) => format!("{container}.{}", sym.name),
+      None => sym.name,
+    })
+    .collect();
+  assert_eq!(want, got);
+}
+
+#[test]
+fn nested() {
+  check(
+    r#"
+structure Foo = struct
+  structure Bar = struct
+    fun baz x = x
+  end
+end
+"#,
+    "baz",
+    &["Foo.Bar.baz"],
+  );
+}
+
+#[test]
+fn fuzzy() {
+  check(
+    r#"
+fun resolveImports x = x
+fun parse x = x
+val reimport = 3
+"#,
+    "rsim",
+    &["resolveImports"],
+  );
+}
+
+#[test]
+fn prefix_first() {
+  check(
+    r#"
+val xyz = 1
+val yz = 2
+val zyz = 3
+"#,
+    "yz",
+    &["yz", "xyz", "zyz"],
+  );
+}
+
+#[test]
+fn qualified() {
+  check(
+    r#"
+structure A = struct val x = 1 end
+structure B = struct val x = 2 end
+"#,
+    "b.x",
+    &["B.x"],
+  );
+}
+
+#[test]
+fn limit() {
+  let mut s = "val x = 0\n".to_owned();
+  for idx in 0..200 {
+    s.push_str(&format!("val x{idx} = 0\n"));
+  }
+  let (an, _) = one_file::get(&s);
+  let got = an.workspace_symbols("x");
+  assert_eq!(128, got.len());
+  assert_eq!("x", got[0].name);
+}
+
+#[test]
+fn limit_across_analyses() {
+  let mut many = String::new();
+  for idx in 0..200 {
+    many.push_str(&format!("val x{idx} = 0\n"));
+  }
+  let (many, _) = one_file::get(&many);
+  let (best, _) = one_file::get("val x = 0\n");
+  let got = analysis::workspace_symbols([&many, &best], "x");
+  assert_eq!(128, got.len());
+  assert_eq!("x", got[0].name);
+}
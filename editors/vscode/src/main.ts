@@ -1,6 +1,7 @@
 import * as path from "path";
 import * as vscode from "vscode";
 import {
+  DidChangeConfigurationNotification,
   LanguageClient,
   type LanguageClientOptions,
   type ServerOptions,
@@ -25,21 +26,38 @@ export async function activate(cx: vscode.ExtensionContext) {
         : cx.asAbsolutePath(path.join("out", `millet-ls${ext}`)),
   };
   const clientOpts: LanguageClientOptions = {
-    documentSelector: [{ scheme: "file", language: "sml" }],
-    // @sync(init-options)
-    initializationOptions: {
-      token_hover: config.get("server.hover.token.enable"),
-      fs_watcher: config.get("server.fileSystemWatcher.enable"),
-      format: config.get("format.engine"),
-      diagnostics: {
-        on_change: config.get("server.diagnostics.onChange.enable"),
-        more_info_hint: config.get("server.diagnostics.moreInfoHint.enable"),
-        ignore: config.get("server.diagnostics.ignore"),
-      },
-    },
+    documentSelector: [
+      { scheme: "file", language: "sml" },
+      { scheme: "file", language: "sml-nj-cm" },
+      { scheme: "file", language: "mlb" },
+    ],
+    initializationOptions: getOptions(config),
   };
   client = new LanguageClient("millet", serverOpts, clientOpts);
   await client.start();
+  cx.subscriptions.push(
+    vscode.workspace.onDidChangeConfiguration((e) => {
+      if (client === null || !e.affectsConfiguration("millet")) {
+        return;
+      }
+      // the server asks for the settings.
+      void client.sendNotification(DidChangeConfigurationNotification.type, { settings: null });
+    }),
+  );
+}
+
+function getOptions(config: vscode.WorkspaceConfiguration) {
+  // @sync(init-options)
+  return {
+    token_hover: config.get("server.hover.token.enable"),
+    fs_watcher: config.get("server.fileSystemWatcher.enable"),
+    format: config.get("format.engine"),
+    diagnostics: {
+      on_change: config.get("server.diagnostics.onChange.enable"),
+      more_info_hint: config.get("server.diagnostics.moreInfoHint.enable"),
+      ignore: config.get("server.diagnostics.ignore"),
+    },
+  };
 }
 
 export async function deactivate() {